@@ -0,0 +1,88 @@
+#ifndef SIG_TOOL_H
+#define SIG_TOOL_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Success; for sigtool_verify, the signature is valid
+#define SIGTOOL_OK 0
+
+// sigtool_verify only: the signature is well-formed but does not verify
+#define SIGTOOL_INVALID_SIGNATURE 1
+
+// A required pointer is NULL; pointers may only be NULL when their length is 0
+#define SIGTOOL_ERR_NULL_POINTER -1
+
+// The scheme name is not valid UTF-8 or names no scheme this library was built with
+#define SIGTOOL_ERR_UNSUPPORTED_SCHEME -2
+
+// The public key does not parse for the scheme
+#define SIGTOOL_ERR_PUBLIC_KEY -3
+
+// The private key does not parse for the scheme
+#define SIGTOOL_ERR_PRIVATE_KEY -4
+
+// The signature does not parse for the scheme
+#define SIGTOOL_ERR_SIGNATURE_ENCODING -5
+
+// Signing failed
+#define SIGTOOL_ERR_SIGNING -6
+
+// The output buffer is smaller than the signature; *sig_len holds the length needed
+#define SIGTOOL_ERR_BUFFER_TOO_SMALL -7
+
+// Verification failed for a reason other than a bad signature
+#define SIGTOOL_ERR_VERIFICATION -8
+
+// The library panicked; this is a bug
+#define SIGTOOL_ERR_INTERNAL -9
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Verify a signature over a message. Returns SIGTOOL_OK when it is valid,
+// SIGTOOL_INVALID_SIGNATURE when it is not, and a negative SIGTOOL_ERR_* code when the
+// scheme, key or signature cannot be used.
+//
+// # Safety
+//
+// `scheme` must be a NUL-terminated string, and `pubkey`, `msg` and `sig` must each point
+// to at least the given number of readable bytes (or be NULL with a length of 0).
+int32_t sigtool_verify(const char *scheme,
+                       const uint8_t *pubkey,
+                       size_t pubkey_len,
+                       const uint8_t *msg,
+                       size_t msg_len,
+                       const uint8_t *sig,
+                       size_t sig_len);
+
+// Sign a message with a private key. On entry `*sig_len` is the size of the `sig_out`
+// buffer; on return it is the signature's length. With a NULL `sig_out` nothing is written
+// but the length, and the call returns SIGTOOL_OK, so callers ask for the length, allocate,
+// and call again. A signature with a given key and message has the same length from call
+// to call, even for the schemes that sign with fresh randomness.
+//
+// # Safety
+//
+// `scheme` must be a NUL-terminated string, `privkey` and `msg` must each point to at least
+// the given number of readable bytes (or be NULL with a length of 0), `sig_len` must point
+// to a writable size_t, and a non-NULL `sig_out` must point to `*sig_len` writable bytes.
+int32_t sigtool_sign(const char *scheme,
+                     const uint8_t *privkey,
+                     size_t privkey_len,
+                     const uint8_t *msg,
+                     size_t msg_len,
+                     uint8_t *sig_out,
+                     size_t *sig_len);
+
+// A static, NUL-terminated description of a SIGTOOL_* code, which the caller must not free
+const char *sigtool_strerror(int32_t code);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SIG_TOOL_H */
@@ -0,0 +1,176 @@
+use crate::storage::AUDIT_FILE;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// A record of signing and key-management operations, appended as one JSON object per line
+// to audit.log in the keystore. With an HMAC key every line also carries an HMAC-SHA256
+// over itself and the previous line's HMAC, so editing, reordering or removing a line
+// breaks the chain from there on. Lines cut from the end of the log leave no trace.
+
+// Appending reads back only this much of the log to find the previous line's HMAC
+const TAIL_BYTES: u64 = 64 * 1024;
+
+#[derive(Error, Debug)]
+pub enum AuditError {
+    #[error("I/O error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Audit log line {0} is not a valid record: {1}")]
+    Parse(usize, String),
+
+    #[error("Audit log line {0} fails verification: {1}")]
+    Tampered(usize, String),
+
+    #[error("The last line of the audit log is not a valid record: {0}")]
+    LastLine(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuditRecord {
+    pub timestamp: u64,
+    pub command: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+
+    // SHA-256 of the message signed, hex-encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_sha256: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+
+    pub success: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    // Hex HMAC-SHA256 of the previous line's HMAC followed by this record without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(command: &str) -> Self {
+        Self {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn chained_hmac(&self, key: &[u8], previous: &str) -> Result<String, AuditError> {
+        let unsigned = AuditRecord { hmac: None, ..self.clone() };
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+        mac.update(previous.as_bytes());
+        mac.update(&serde_json::to_vec(&unsigned)?);
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(keystore_dir: impl AsRef<Path>) -> Self {
+        Self { path: keystore_dir.as_ref().join(AUDIT_FILE) }
+    }
+
+    // Append a record, chaining its HMAC to the previous line's when a key is given. The
+    // log stays locked from reading that line until the new one is on disk.
+    pub fn append(&self, mut record: AuditRecord, hmac_key: Option<&[u8]>) -> Result<(), AuditError> {
+        let mut options = OpenOptions::new();
+        options.read(true).append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        file.lock()?;
+
+        if let Some(key) = hmac_key {
+            let previous = match last_line(&mut file)? {
+                Some(line) => serde_json::from_str::<AuditRecord>(&line)
+                    .map_err(|e| AuditError::LastLine(e.to_string()))?
+                    .hmac
+                    .unwrap_or_default(),
+                None => String::new(),
+            };
+            record.hmac = Some(record.chained_hmac(key, &previous)?);
+        }
+
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    // Every record in the log, oldest first; a keystore that never logged has none
+    pub fn records(&self) -> Result<Vec<AuditRecord>, AuditError> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| AuditError::Parse(i + 1, e.to_string())))
+            .collect()
+    }
+
+    // Check the HMAC chain from the first line, returning how many records it covers
+    pub fn verify(&self, hmac_key: &[u8]) -> Result<usize, AuditError> {
+        let records = self.records()?;
+        let mut previous = String::new();
+        for (i, record) in records.iter().enumerate() {
+            let Some(hmac) = &record.hmac else {
+                return Err(AuditError::Tampered(i + 1, "the line has no HMAC".into()));
+            };
+            if *hmac != record.chained_hmac(hmac_key, &previous)? {
+                return Err(AuditError::Tampered(i + 1, "HMAC mismatch: the line was altered, or lines before it were".into()));
+            }
+            previous = hmac.clone();
+        }
+        Ok(records.len())
+    }
+}
+
+// The last line of the log, read from its end
+fn last_line(file: &mut File) -> Result<Option<String>, AuditError> {
+    let len = file.metadata()?.len();
+    let mut start = len.saturating_sub(TAIL_BYTES);
+    loop {
+        file.seek(SeekFrom::Start(start))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        // Starting mid-line may split a character, but never one in the last line
+        let tail = String::from_utf8_lossy(&tail);
+        let tail = tail.trim_end();
+        match tail.rfind('\n') {
+            Some(i) => return Ok(Some(tail[i + 1..].to_string())),
+            // A line longer than the tail: read the whole log instead
+            None if start > 0 => start = 0,
+            None if tail.is_empty() => return Ok(None),
+            None => return Ok(Some(tail.to_string())),
+        }
+    }
+}
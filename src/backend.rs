@@ -0,0 +1,688 @@
+use crate::crypto::registry::{self, DynScheme};
+use crate::crypto::SignatureScheme;
+use crate::storage::{fingerprint_of, save_signature_with_info, validate_key_name, KeyEntry, KeyStore, SignatureInfo, StorageError};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+// Whole key entries saved, loaded, listed and deleted by name, so code that signs and
+// verifies can be handed a KeyStore or a MemoryKeyStore alike. Not to be confused with
+// storage::KeyStorageBackend, which is where a KeyStore keeps its private keys.
+pub trait SigningKeyStore {
+    // Save a key, replacing any of the same name
+    fn save(&self, entry: &KeyEntry) -> Result<(), StorageError>;
+
+    // Save a key, failing with KeyExists rather than replacing one of the same name
+    fn create(&self, entry: &KeyEntry) -> Result<(), StorageError>;
+
+    // A key along with its private key
+    fn load(&self, name: &str) -> Result<KeyEntry, StorageError>;
+
+    fn list(&self) -> Result<Vec<String>, StorageError>;
+
+    fn delete(&self, name: &str) -> Result<(), StorageError>;
+
+    // Move a key to a new name, failing with KeyExists rather than replacing a key of that
+    // name unless overwrite is set
+    fn rename(&self, from: &str, to: &str, overwrite: bool) -> Result<(), StorageError>;
+}
+
+impl SigningKeyStore for KeyStore {
+    fn save(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        self.save_key_entry(entry)
+    }
+
+    fn create(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        self.create_key_entry(entry)
+    }
+
+    fn load(&self, name: &str) -> Result<KeyEntry, StorageError> {
+        self.load_key_entry(name)
+    }
+
+    fn list(&self) -> Result<Vec<String>, StorageError> {
+        Ok(self.key_names()?.into_iter().collect())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), StorageError> {
+        self.delete_key(name)
+    }
+
+    fn rename(&self, from: &str, to: &str, overwrite: bool) -> Result<(), StorageError> {
+        self.rename_key(from, to, overwrite)
+    }
+}
+
+// Keys kept only in memory, for embedders and tests with no keystore directory to use
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    keys: Mutex<HashMap<String, KeyEntry>>,
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A panic while the lock was held cannot leave the map half-updated
+    fn keys(&self) -> MutexGuard<'_, HashMap<String, KeyEntry>> {
+        self.keys.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SigningKeyStore for MemoryKeyStore {
+    fn save(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        validate_key_name(&entry.metadata.name)?;
+        self.keys().insert(entry.metadata.name.clone(), entry.clone());
+        Ok(())
+    }
+
+    fn create(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        validate_key_name(&entry.metadata.name)?;
+        match self.keys().entry(entry.metadata.name.clone()) {
+            Entry::Occupied(_) => Err(StorageError::KeyExists(entry.metadata.name.clone())),
+            Entry::Vacant(slot) => {
+                slot.insert(entry.clone());
+                Ok(())
+            }
+        }
+    }
+
+    fn load(&self, name: &str) -> Result<KeyEntry, StorageError> {
+        self.keys().get(name).cloned().ok_or_else(|| StorageError::KeyNotFound(name.to_string()))
+    }
+
+    fn list(&self) -> Result<Vec<String>, StorageError> {
+        let mut names: Vec<String> = self.keys().keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), StorageError> {
+        self.keys().remove(name).map(|_| ()).ok_or_else(|| StorageError::KeyNotFound(name.to_string()))
+    }
+
+    fn rename(&self, from: &str, to: &str, overwrite: bool) -> Result<(), StorageError> {
+        let mut keys = self.keys();
+        if !keys.contains_key(from) {
+            return Err(StorageError::KeyNotFound(from.to_string()));
+        }
+        validate_key_name(to)?;
+        if from == to {
+            return Ok(());
+        }
+        if !overwrite && keys.contains_key(to) {
+            return Err(StorageError::KeyExists(to.to_string()));
+        }
+        if let Some(mut entry) = keys.remove(from) {
+            entry.metadata.name = to.to_string();
+            keys.insert(to.to_string(), entry);
+        }
+        Ok(())
+    }
+}
+
+// Sign a message with a stored key of scheme S, returning the serialized signature
+pub fn sign_with<S: SignatureScheme>(store: &impl SigningKeyStore, name: &str, message: &[u8]) -> Result<Vec<u8>, StorageError> {
+    let entry = store.load(name)?;
+    check_scheme::<S>(&entry)?;
+    check_can_sign(&entry)?;
+    let private_key = hex::decode(&entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?;
+    Ok(registered_scheme(S::name())?.sign_bytes(&private_key, message)?)
+}
+
+// Verify a serialized signature against a stored key of scheme S
+pub fn verify_with<S: SignatureScheme>(store: &impl SigningKeyStore, name: &str, message: &[u8], signature: &[u8]) -> Result<bool, StorageError> {
+    let entry = store.load(name)?;
+    check_scheme::<S>(&entry)?;
+    let public_key = hex::decode(&entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    Ok(registered_scheme(S::name())?.verify_bytes(&public_key, message, signature)?)
+}
+
+fn check_scheme<S: SignatureScheme>(entry: &KeyEntry) -> Result<(), StorageError> {
+    if entry.metadata.scheme != S::name() {
+        return Err(StorageError::WrongScheme(
+            entry.metadata.name.clone(),
+            entry.metadata.scheme.clone(),
+            S::name().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Only keys with a private key that have not expired sign
+pub(crate) fn check_can_sign(entry: &KeyEntry) -> Result<(), StorageError> {
+    if entry.private_key.is_empty() {
+        return Err(StorageError::PublicOnly(entry.metadata.name.clone()));
+    }
+    if let Some(expires_at) = entry.metadata.expires_at.filter(|_| entry.metadata.is_expired()) {
+        return Err(StorageError::KeyExpired(entry.metadata.name.clone(), expires_at));
+    }
+    Ok(())
+}
+
+// A signature made by sign_with_key, with what it takes to verify it without the keystore
+#[derive(Debug, Clone)]
+pub struct SignatureBundle {
+    pub scheme: String,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub fingerprint: String,
+    // SHA-256 of the message signed, hex-encoded
+    pub message_sha256: String,
+}
+
+impl SignatureBundle {
+    // Write the bundle as a signature file, which sig-tool verify --standalone checks
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StorageError> {
+        let info = SignatureInfo {
+            key_fingerprint: Some(self.fingerprint.clone()),
+            public_key: Some(hex::encode(&self.public_key)),
+            message_sha256: Some(self.message_sha256.clone()),
+            ..Default::default()
+        };
+        save_signature_with_info(path, &self.scheme, &self.signature, &info)
+    }
+}
+
+/// Sign a message with a stored key of any scheme, found by name.
+///
+/// ```
+/// use sig_tool::backend::{sign_with_key, verify_bundle, SigningKeyStore, MemoryKeyStore};
+/// use sig_tool::crypto::{SignatureScheme, ED25519};
+/// use sig_tool::storage::KeyEntry;
+///
+/// let store = MemoryKeyStore::new();
+/// let (private_key, public_key) = ED25519::generate_keypair()?;
+/// store.save(&KeyEntry::new::<ED25519>("deploy", &private_key, &public_key)?)?;
+///
+/// let bundle = sign_with_key(&store, "deploy", b"release 1.2.0")?;
+/// assert!(verify_bundle(&bundle, b"release 1.2.0")?);
+/// assert!(!verify_bundle(&bundle, b"release 1.2.1")?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn sign_with_key(store: &impl SigningKeyStore, name: &str, message: &[u8]) -> Result<SignatureBundle, StorageError> {
+    let entry = store.load(name)?;
+    check_can_sign(&entry)?;
+    let registered = registered_scheme(&entry.metadata.scheme)?;
+    let private_key = hex::decode(&entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?;
+    let public_key = hex::decode(&entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    Ok(SignatureBundle {
+        signature: registered.sign_bytes(&private_key, message)?,
+        fingerprint: fingerprint_of(&entry.metadata.scheme, &public_key),
+        scheme: entry.metadata.scheme,
+        public_key,
+        message_sha256: hex::encode(Sha256::digest(message)),
+    })
+}
+
+// Verify a bundle's signature against its own public key. Whether that is a key to trust
+// is for the caller to decide, e.g. by its fingerprint.
+pub fn verify_bundle(bundle: &SignatureBundle, message: &[u8]) -> Result<bool, StorageError> {
+    Ok(registered_scheme(&bundle.scheme)?.verify_bytes(&bundle.public_key, message, &bundle.signature)?)
+}
+
+fn registered_scheme(scheme: &str) -> Result<&'static dyn DynScheme, StorageError> {
+    registry::lookup(scheme).ok_or_else(|| StorageError::UnsupportedScheme(scheme.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{ECDSA, ED25519};
+    use crate::storage::{write_atomic, write_private, KeyMetadata, CURRENT_VERSION, INDEX_FILE, MAX_KEY_NAME_LEN};
+    use std::fs;
+    use std::path::PathBuf;
+
+    // A store for the suite to run against, with a temporary directory for the files a test
+    // writes. The file KeyStore keeps its keys in that directory, and its tests also check
+    // what it leaves there.
+    trait Fixture: Sync {
+        type Store: SigningKeyStore + Sync;
+
+        fn open(test: &str) -> Self;
+
+        fn store(&self) -> &Self::Store;
+
+        fn dir(&self) -> &Path;
+
+        fn keystore(&self) -> Option<&KeyStore>;
+    }
+
+    struct FileFixture {
+        dir: PathBuf,
+        keystore: KeyStore,
+    }
+
+    struct MemoryFixture {
+        dir: PathBuf,
+        store: MemoryKeyStore,
+    }
+
+    fn temp_dir(kind: &str, test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sig-tool-backend-{}-{}-{}", kind, test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    impl Fixture for FileFixture {
+        type Store = KeyStore;
+
+        fn open(test: &str) -> Self {
+            let dir = temp_dir("file", test);
+            let keystore = KeyStore::new(&dir).unwrap();
+            Self { dir, keystore }
+        }
+
+        fn store(&self) -> &KeyStore {
+            &self.keystore
+        }
+
+        fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        fn keystore(&self) -> Option<&KeyStore> {
+            Some(&self.keystore)
+        }
+    }
+
+    impl Fixture for MemoryFixture {
+        type Store = MemoryKeyStore;
+
+        fn open(test: &str) -> Self {
+            let dir = temp_dir("memory", test);
+            fs::create_dir_all(&dir).unwrap();
+            Self { dir, store: MemoryKeyStore::new() }
+        }
+
+        fn store(&self) -> &MemoryKeyStore {
+            &self.store
+        }
+
+        fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        fn keystore(&self) -> Option<&KeyStore> {
+            None
+        }
+    }
+
+    impl Drop for FileFixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    impl Drop for MemoryFixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    // Every test of the suite, once against a file KeyStore and once against a MemoryKeyStore
+    macro_rules! suite {
+        ($($(#[$attr:meta])* $test:ident,)*) => {
+            mod file_key_store {
+                $($(#[$attr])* #[test] fn $test() { super::$test(&<super::FileFixture as super::Fixture>::open(stringify!($test))); })*
+            }
+            mod memory_key_store {
+                $($(#[$attr])* #[test] fn $test() { super::$test(&<super::MemoryFixture as super::Fixture>::open(stringify!($test))); })*
+            }
+        };
+    }
+
+    suite! {
+        conformance,
+        delete_removes_the_key,
+        concurrent_writers_keep_every_key,
+        saving_over_a_key_uses_a_fresh_temporary_file,
+        names_that_escape_or_mislead_are_refused,
+        truncated_key_files_are_reported,
+        #[cfg(unix)]
+        private_keys_are_kept_private,
+        create_refuses_to_overwrite,
+        rename_moves_the_key,
+        key_files_from_a_newer_version_are_refused,
+        ed448_key_round_trips,
+        #[cfg(feature = "ml-dsa")]
+        ml_dsa_signature_file_round_trips,
+    }
+
+    fn save_key(store: &impl SigningKeyStore, name: &str) -> KeyEntry {
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        store.create(&KeyEntry::new::<ED25519>(name, &private_key, &public_key).unwrap()).unwrap();
+        store.load(name).unwrap()
+    }
+
+    // The names of the keys a file keystore's index lists
+    fn indexed_names(dir: &Path) -> Vec<String> {
+        let index: serde_json::Value = serde_json::from_slice(&fs::read(dir.join(INDEX_FILE)).unwrap()).unwrap();
+        index["keys"].as_object().unwrap().keys().cloned().collect()
+    }
+
+    // What every SigningKeyStore has to do
+    fn conformance(fixture: &impl Fixture) {
+        let store = fixture.store();
+        assert!(matches!(store.load("deploy"), Err(StorageError::KeyNotFound(_))));
+        assert!(store.list().unwrap().is_empty());
+
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        let deploy = KeyEntry::new::<ED25519>("deploy", &private_key, &public_key).unwrap();
+        store.save(&deploy).unwrap();
+        let loaded = store.load("deploy").unwrap();
+        assert_eq!((loaded.private_key, loaded.public_key), (deploy.private_key.clone(), deploy.public_key.clone()));
+        assert_eq!(loaded.metadata.scheme, ED25519::name());
+
+        let (private_key, public_key) = ECDSA::generate_keypair().unwrap();
+        store.save(&KeyEntry::new::<ECDSA>("build", &private_key, &public_key).unwrap()).unwrap();
+        let public_only = KeyEntry { metadata: KeyMetadata::new("verify-only", ED25519::name()), private_key: String::new(), ..deploy.clone() };
+        store.save(&public_only).unwrap();
+        assert_eq!(store.list().unwrap(), ["build", "deploy", "verify-only"]);
+        let escaping = KeyEntry { metadata: KeyMetadata::new("../deploy", ED25519::name()), ..deploy.clone() };
+        assert!(matches!(store.save(&escaping), Err(StorageError::InvalidKeyName(..))));
+
+        let signature = sign_with::<ED25519>(store, "deploy", b"release 1.2.0").unwrap();
+        assert!(verify_with::<ED25519>(store, "deploy", b"release 1.2.0", &signature).unwrap());
+        assert!(!verify_with::<ED25519>(store, "deploy", b"release 1.2.1", &signature).unwrap());
+        assert!(verify_with::<ED25519>(store, "verify-only", b"release 1.2.0", &signature).unwrap());
+        assert!(matches!(sign_with::<ECDSA>(store, "deploy", b"release 1.2.0"), Err(StorageError::WrongScheme(..))));
+        assert!(matches!(sign_with::<ED25519>(store, "verify-only", b"release 1.2.0"), Err(StorageError::PublicOnly(_))));
+
+        let bundle = sign_with_key(store, "build", b"release 1.2.0").unwrap();
+        assert_eq!(bundle.scheme, ECDSA::name());
+        assert!(verify_bundle(&bundle, b"release 1.2.0").unwrap());
+        assert!(!verify_bundle(&bundle, b"release 1.2.1").unwrap());
+        assert!(matches!(sign_with_key(store, "verify-only", b"release 1.2.0"), Err(StorageError::PublicOnly(_))));
+
+        let mut expired = KeyEntry { metadata: KeyMetadata::new("expired", ED25519::name()), ..deploy.clone() };
+        expired.metadata.expires_at = Some(1);
+        store.save(&expired).unwrap();
+        assert!(matches!(sign_with::<ED25519>(store, "expired", b"release 1.2.0"), Err(StorageError::KeyExpired(_, 1))));
+        assert!(matches!(sign_with_key(store, "expired", b"release 1.2.0"), Err(StorageError::KeyExpired(_, 1))));
+        assert!(verify_with::<ED25519>(store, "expired", b"release 1.2.0", &signature).unwrap());
+        store.delete("expired").unwrap();
+
+        store.delete("deploy").unwrap();
+        assert!(matches!(store.load("deploy"), Err(StorageError::KeyNotFound(_))));
+        assert!(matches!(store.delete("deploy"), Err(StorageError::KeyNotFound(_))));
+        assert_eq!(store.list().unwrap(), ["build", "verify-only"]);
+    }
+
+    fn delete_removes_the_key(fixture: &impl Fixture) {
+        let store = fixture.store();
+        save_key(store, "kept");
+        save_key(store, "deleted");
+
+        store.delete("deleted").unwrap();
+        assert!(matches!(store.delete("deleted"), Err(StorageError::KeyNotFound(name)) if name == "deleted"));
+        assert!(matches!(store.load("deleted"), Err(StorageError::KeyNotFound(_))));
+        assert_eq!(store.list().unwrap(), ["kept"]);
+
+        // Shredding overwrites the key file before removing it
+        if let Some(keystore) = fixture.keystore() {
+            save_key(keystore, "shredded");
+            keystore.list_keys().unwrap();
+            keystore.shred_key("shredded").unwrap();
+            assert!(!fixture.dir().join("deleted.json").exists());
+            assert!(!fixture.dir().join("shredded.json").exists());
+            assert!(matches!(keystore.shred_key("shredded"), Err(StorageError::KeyNotFound(_))));
+            let names: Vec<String> = keystore.list_keys().unwrap().keys.into_iter().map(|key| key.metadata.name).collect();
+            assert_eq!(names, ["kept"]);
+        }
+    }
+
+    // Writers, in separate processes for a file keystore, save and delete keys at once; none
+    // may lose another's key, nor both create the same key
+    fn concurrent_writers_keep_every_key(fixture: &impl Fixture) {
+        fn write(store: &impl SigningKeyStore, i: usize) -> bool {
+            let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+            store.create(&KeyEntry::new::<ED25519>(&format!("key-{}", i), &private_key, &public_key).unwrap()).unwrap();
+            store.delete(&format!("key-{}", i % 2)).ok();
+            store.create(&KeyEntry::new::<ED25519>("contended", &private_key, &public_key).unwrap()).is_ok()
+        }
+
+        save_key(fixture.store(), "first");
+        if let Some(keystore) = fixture.keystore() {
+            keystore.list_keys().unwrap();
+        }
+        let saved: Vec<bool> = std::thread::scope(|scope| {
+            let writers: Vec<_> = (0..8)
+                .map(|i| scope.spawn(move || match fixture.keystore() {
+                    Some(_) => write(&KeyStore::new(fixture.dir()).unwrap(), i),
+                    None => write(fixture.store(), i),
+                }))
+                .collect();
+            writers.into_iter().map(|writer| writer.join().unwrap()).collect()
+        });
+        assert_eq!(saved.iter().filter(|saved| **saved).count(), 1);
+
+        let names = fixture.store().list().unwrap();
+        assert!(names.contains(&"first".to_string()) && names.contains(&"contended".to_string()));
+        assert!((2..8).all(|i| names.contains(&format!("key-{}", i))));
+        if fixture.keystore().is_some() {
+            assert_eq!(indexed_names(fixture.dir()), names);
+        }
+    }
+
+    fn saving_over_a_key_uses_a_fresh_temporary_file(fixture: &impl Fixture) {
+        let store = fixture.store();
+        let dir = fixture.dir();
+        // Where a fixed temporary name would have put it
+        fs::write(dir.join(".deploy.json.tmp"), b"planted").unwrap();
+
+        save_key(store, "deploy");
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        let replacement = KeyEntry::new::<ED25519>("deploy", &private_key, &public_key).unwrap();
+        store.save(&replacement).unwrap();
+        assert_eq!(store.load("deploy").unwrap().public_key, replacement.public_key);
+        assert_eq!(store.list().unwrap(), ["deploy"]);
+        assert_eq!(fs::read(dir.join(".deploy.json.tmp")).unwrap(), b"planted");
+
+        let path = dir.join("notes.txt");
+        fs::write(dir.join(".notes.txt.tmp"), b"planted").unwrap();
+        write_atomic(&path, b"first").unwrap();
+        write_private(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read(dir.join(".notes.txt.tmp")).unwrap(), b"planted");
+
+        let leftovers: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with('.') && ![".lock", ".deploy.json.tmp", ".notes.txt.tmp"].contains(&name.as_str()))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+
+    fn names_that_escape_or_mislead_are_refused(fixture: &impl Fixture) {
+        let store = fixture.store();
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        let entry = KeyEntry::new::<ED25519>("deploy", &private_key, &public_key).unwrap();
+        let named = |name: &str| KeyEntry { metadata: KeyMetadata::new(name, ED25519::name()), ..entry.clone() };
+
+        for name in ["deploy", "release-2024_10", "v1.2.0", "a.b", &"x".repeat(MAX_KEY_NAME_LEN)] {
+            assert!(validate_key_name(name).is_ok(), "{:?}", name);
+            store.save(&named(name)).unwrap();
+            assert_eq!(store.load(name).unwrap().metadata.name, name);
+        }
+
+        for name in [
+            "", "..", "../deploy", "a..b", "keys/deploy", "/etc/passwd", "keys\\deploy", "..\\deploy",
+            ".hidden", ".", "deploy\0.json", "deploy\n", "deploy name", "C:deploy",
+            // Non-ASCII letters and confusables of ASCII ones: Cyrillic а, fullwidth ｄ,
+            // a combining accent, a zero-width space and a right-to-left override
+            "deplоy", "ｄeploy", "de\u{301}ploy", "deploy\u{200b}", "\u{202e}yolped",
+            &"x".repeat(MAX_KEY_NAME_LEN + 1),
+        ] {
+            assert!(matches!(validate_key_name(name), Err(StorageError::InvalidKeyName(..))), "{:?}", name);
+            assert!(matches!(store.save(&named(name)), Err(StorageError::InvalidKeyName(..))), "{:?}", name);
+            assert!(matches!(store.rename("deploy", name, false), Err(StorageError::InvalidKeyName(..))), "{:?}", name);
+        }
+
+        assert!(matches!(store.save(&named("index")), Err(StorageError::ReservedName(_))));
+        assert_eq!(store.list().unwrap().len(), 5);
+    }
+
+    fn truncated_key_files_are_reported(fixture: &impl Fixture) {
+        let store = fixture.store();
+        save_key(store, "intact");
+        save_key(store, "truncated");
+        assert_eq!(store.list().unwrap(), ["intact", "truncated"]);
+
+        let (Some(keystore), dir) = (fixture.keystore(), fixture.dir()) else {
+            return;
+        };
+        keystore.list_keys().unwrap();
+        // What a crash part way through writing the file in place would have left
+        let contents = fs::read(dir.join("truncated.json")).unwrap();
+        fs::write(dir.join("truncated.json"), &contents[..contents.len() / 2]).unwrap();
+        fs::remove_file(dir.join(INDEX_FILE)).unwrap();
+
+        let listing = keystore.list_keys().unwrap();
+        let names: Vec<&str> = listing.keys.iter().map(|key| key.metadata.name.as_str()).collect();
+        assert_eq!(names, ["intact"]);
+        assert_eq!(listing.unreadable.len(), 1);
+        assert_eq!(listing.unreadable[0].0, "truncated.json");
+        assert!(matches!(store.load("truncated"), Err(StorageError::Json(_))));
+
+        // Saving writes through a temporary file that does not stay behind
+        save_key(store, "another");
+        assert!(!dir.join(".another.json.tmp").exists());
+    }
+
+    #[cfg(unix)]
+    fn private_keys_are_kept_private(fixture: &impl Fixture) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let store = fixture.store();
+        let saved = save_key(store, "private");
+        assert_eq!(store.load("private").unwrap().private_key, saved.private_key);
+
+        let (Some(keystore), dir) = (fixture.keystore(), fixture.dir()) else {
+            return;
+        };
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(dir), 0o700);
+        assert_eq!(mode(&dir.join("private.json")), 0o600);
+        assert!(keystore.check_permissions(true).unwrap().is_empty());
+
+        fs::set_permissions(dir.join("private.json"), fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(keystore.check_permissions(false).unwrap(), [dir.join("private.json")]);
+        assert!(matches!(keystore.check_permissions(true), Err(StorageError::InsecurePermissions(_))));
+    }
+
+    fn create_refuses_to_overwrite(fixture: &impl Fixture) {
+        let store = fixture.store();
+        let original = save_key(store, "key");
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        let replacement = KeyEntry::new::<ED25519>("key", &private_key, &public_key).unwrap();
+
+        assert!(matches!(store.create(&replacement), Err(StorageError::KeyExists(name)) if name == "key"));
+        assert_eq!(store.load("key").unwrap().public_key, original.public_key);
+
+        store.save(&replacement).unwrap();
+        assert_eq!(store.load("key").unwrap().public_key, hex::encode(ED25519::serialize_public_key(&public_key).unwrap()));
+
+        // save_keypair chooses between the two with its overwrite flag
+        if let Some(keystore) = fixture.keystore() {
+            let refused = keystore.save_keypair::<ED25519>("key", &private_key, &public_key, false);
+            assert!(matches!(refused, Err(StorageError::KeyExists(_))));
+            keystore.save_keypair::<ED25519>("key", &private_key, &public_key, true).unwrap();
+        }
+    }
+
+    fn rename_moves_the_key(fixture: &impl Fixture) {
+        let store = fixture.store();
+        let original = save_key(store, "old");
+        let taken = save_key(store, "taken");
+        if let Some(keystore) = fixture.keystore() {
+            keystore.list_keys().unwrap();
+        }
+
+        store.rename("old", "new", false).unwrap();
+        assert!(matches!(store.load("old"), Err(StorageError::KeyNotFound(_))));
+        let renamed = store.load("new").unwrap();
+        assert_eq!(renamed.metadata.name, "new");
+        assert_eq!((&renamed.private_key, &renamed.public_key), (&original.private_key, &original.public_key));
+        assert_eq!(store.list().unwrap(), ["new", "taken"]);
+
+        let refused = store.rename("new", "taken", false);
+        assert!(matches!(refused, Err(StorageError::KeyExists(name)) if name == "taken"));
+        assert!(store.load("new").is_ok());
+        assert_eq!(store.load("taken").unwrap().public_key, taken.public_key);
+        assert!(matches!(store.rename("old", "other", false), Err(StorageError::KeyNotFound(_))));
+        assert!(matches!(store.rename("new", "../escape", false), Err(StorageError::InvalidKeyName(..))));
+
+        store.rename("new", "taken", true).unwrap();
+        assert_eq!(store.list().unwrap(), ["taken"]);
+        assert_eq!(store.load("taken").unwrap().public_key, original.public_key);
+
+        if fixture.keystore().is_some() {
+            assert!(!fixture.dir().join("old.json").exists() && !fixture.dir().join("new.json").exists());
+            assert_eq!(indexed_names(fixture.dir()), ["taken"]);
+        }
+    }
+
+    fn key_files_from_a_newer_version_are_refused(fixture: &impl Fixture) {
+        let store = fixture.store();
+        let saved = save_key(store, "future");
+        let mut stored = serde_json::to_value(&saved).unwrap();
+        stored["version"] = (CURRENT_VERSION + 1).into();
+        let contents = serde_json::to_vec_pretty(&stored).unwrap();
+        let newer = CURRENT_VERSION + 1;
+        assert!(matches!(KeyEntry::parse(&contents), Err(StorageError::UnsupportedVersion(v)) if v == newer));
+
+        let (Some(keystore), dir) = (fixture.keystore(), fixture.dir()) else {
+            return;
+        };
+        let path = dir.join("future.json");
+        fs::write(&path, &contents).unwrap();
+        assert!(matches!(store.load("future"), Err(StorageError::UnsupportedVersion(_))));
+        assert!(matches!(keystore.migrate_key("future"), Err(StorageError::UnsupportedVersion(_))));
+        // Nothing rewrites it in a format the newer release would not recognise
+        assert_eq!(fs::read(&path).unwrap(), contents);
+
+        let listing = keystore.reindex().unwrap();
+        assert!(listing.keys.is_empty());
+        assert_eq!(listing.unreadable.len(), 1);
+    }
+
+    fn ed448_key_round_trips(fixture: &impl Fixture) {
+        use crate::crypto::Ed448;
+
+        let store = fixture.store();
+        let (private_key, public_key) = Ed448::generate_keypair().unwrap();
+        store.create(&KeyEntry::new::<Ed448>("ed448", &private_key, &public_key).unwrap()).unwrap();
+
+        let entry = store.load("ed448").unwrap();
+        assert_eq!(entry.metadata.scheme, "Ed448");
+        let private_key = Ed448::deserialize_private_key(&hex::decode(&entry.private_key).unwrap()).unwrap();
+        let public_key = Ed448::deserialize_public_key(&hex::decode(&entry.public_key).unwrap()).unwrap();
+        let signature = Ed448::sign(&private_key, b"message").unwrap();
+        assert!(Ed448::verify(&public_key, b"message", &signature).unwrap());
+        assert!(verify_with::<Ed448>(store, "ed448", b"message", &Ed448::serialize_signature(&signature).unwrap()).unwrap());
+    }
+
+    #[cfg(feature = "ml-dsa")]
+    fn ml_dsa_signature_file_round_trips(fixture: &impl Fixture) {
+        use crate::crypto::MlDsa65;
+        use crate::storage::{load_signature, save_signature};
+
+        let store = fixture.store();
+        let (private_key, public_key) = MlDsa65::generate_keypair().unwrap();
+        store.create(&KeyEntry::new::<MlDsa65>("pq", &private_key, &public_key).unwrap()).unwrap();
+
+        let signature = sign_with::<MlDsa65>(store, "pq", b"message").unwrap();
+        let path = fixture.dir().join("message.sig");
+        save_signature(&path, MlDsa65::name(), &signature).unwrap();
+        let (scheme, loaded) = load_signature(&path).unwrap();
+        assert_eq!(scheme, "ML-DSA-65");
+        assert_eq!(loaded, signature);
+
+        let signature = MlDsa65::deserialize_signature(&loaded).unwrap();
+        assert!(MlDsa65::verify(&public_key, b"message", &signature).unwrap());
+    }
+}
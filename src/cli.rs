@@ -1,8 +1,41 @@
-use clap::{Parser, Subcommand};
-use crate::crypto::{SignatureScheme, ECDSA, BLS};
-use crate::storage::{KeyStore, StorageError, save_signature, load_signature};
-use std::path::PathBuf;
+use base64::prelude::*;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap::builder::{PossibleValue, PossibleValuesParser, StringValueParser, TypedValueParser};
+use crate::crypto::{SignatureError, SignatureScheme, ECDSA, BLS, ED25519, Schnorr, EcdsaP256, RsaPss, BLSMinSig};
+use crate::crypto::bip322::{self, AddressType, BIP322_SCHEME};
+use crate::crypto::bitcoin::{self, BITCOIN_MESSAGE_SCHEME};
+use crate::crypto::bls::{self, PointEncoding};
+use crate::crypto::bls_min_sig;
+use crate::crypto::eip2335::{self, Keystore};
+use crate::crypto::web3_keystore::{Web3Keystore, Web3KeystoreError};
+use crate::crypto::pem;
+use crate::crypto::registry::{self, Aggregatable, DynScheme};
+use crate::crypto::prehash::{Prehasher, PREHASH_ALGORITHMS};
+use crate::crypto::ecdsa::{
+    EIP191_SCHEME, KECCAK256_RECOVERABLE_SCHEME, KECCAK256_SCHEME, RECOVERABLE_SCHEME, RECOVERABLE_SIGNATURE_LEN,
+    SignatureEncoding,
+};
+use crate::crypto::rsa_pss::RSA_KEY_BITS;
+use crate::crypto::schnorr::TWEAKED_SCHEME;
+use crate::storage::{KeyEntry, KeyMetadata, KeyStore, KeySummary, StorageError, save_signature, save_signature_with_info, load_signature_with_info, SignatureInfo, Committee, CommitteeMember, AggregateSigner, KeyShare, ShareInfo, CURRENT_VERSION, save_key_share, load_key_share, KeyExport, save_key_export, load_key_export, BackupArchive, save_backup, load_backup, write_atomic, write_private, validate_key_name, fingerprint_of, SIGNATURE_FILE_VERSION, SignatureFileContents, decode_signature, FILE_BACKEND, HEX_ENCODING, BASE64_ENCODING, TreeManifest, TreeEntry};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use indicatif::{ProgressBar, ProgressStyle};
+use crate::audit::{AuditError, AuditLog, AuditRecord};
+use crate::completions::{self, CompletionError};
+use crate::keychain::{KeychainBackend, KEYCHAIN_BACKEND};
+use crate::paths::{self, PathError};
+use crate::profiles::{Profile, ProfileError, Profiles, validate_profile_name};
+use crate::ssh::{self, Agent, SshError, SSH_AGENT_SCHEME, SSH_ED25519};
+use crate::yubikey::{self, YubiKeyBackend, PIV_SLOTS, YUBIKEY_BACKEND};
+use crate::signer::{BlockingSigner, KeyStoreSigner, SignerError};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 use std::fs;
+use std::io::{IsTerminal, Read, Write};
+use std::time::Instant;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -10,279 +43,5645 @@ pub struct Cli {
     #[clap(subcommand)]
     pub command: Commands,
     
-    #[clap(long, default_value = "~/.sig-tool")]
-    pub keystore: String,
+    /// Keystore directory; takes precedence over --profile, then $SIG_TOOL_KEYSTORE, then the
+    /// keystore set in ~/.sig-tool/profiles.toml, then ~/.sig-tool
+    #[clap(long)]
+    pub keystore: Option<String>,
+    
+    /// Use the keystore of a profile from ~/.sig-tool/profiles.toml. Without one keygen falls
+    /// back on $SIG_TOOL_DEFAULT_SCHEME, then the scheme set in that file
+    #[clap(long)]
+    pub profile: Option<String>,
+    
+    /// Print details to stderr: where the keystore is, the scheme used, message sizes and
+    /// the time signing and verification took
+    #[clap(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+    
+    /// Print only errors and the line a script needs, e.g. the signature; verify reports
+    /// through its exit code alone
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+    
+    /// Refuse to use a keystore whose directory or key files other users can read
+    #[clap(long, global = true)]
+    pub strict_permissions: bool,
+    
+    /// Where private keys are kept: in the key files, in the OS keychain (the macOS Keychain,
+    /// Windows Credential Manager or the Secret Service) with only metadata and public keys on
+    /// disk, or on a YubiKey's PIV applet (needs a build with the yubikey feature)
+    #[clap(long, global = true, default_value = FILE_BACKEND, value_parser = [FILE_BACKEND, KEYCHAIN_BACKEND, YUBIKEY_BACKEND])]
+    pub backend: String,
+    
+    /// Open the keystore read-only: keys can be loaded and listed, but nothing in it changes.
+    /// Implied when the keystore directory exists but cannot be written to
+    #[clap(long, global = true)]
+    pub read_only: bool,
+    
+    /// File holding a key to chain an HMAC-SHA256 through every audit log line written, so
+    /// audit verify can detect lines that were altered or removed
+    #[clap(long, global = true, value_parser = parse_path)]
+    pub audit_hmac_key: Option<PathBuf>,
+    
+    /// Text encoding of the signatures sign prints and saves, and of the public keys show-key
+    /// prints. Signature files record it; verify reads either
+    #[clap(long, global = true, default_value = HEX_ENCODING, value_parser = [HEX_ENCODING, BASE64_ENCODING])]
+    pub text_encoding: String,
+    
+    /// Print a single JSON object instead of the usual output, whatever the command;
+    /// errors go to stderr as {"error": {"kind", "message", "exit_code"}}
+    #[clap(long, global = true)]
+    pub json: bool,
+}
+
+// Clap parser for keygen --scheme values: those of the registered schemes
+fn keygen_schemes() -> PossibleValuesParser {
+    PossibleValuesParser::new(registry::keygen_names())
+}
+
+// Accepts any scheme name, while offering the keygen schemes to shell completion
+#[derive(Clone)]
+struct SchemeNameParser;
+
+impl TypedValueParser for SchemeNameParser {
+    type Value = String;
+    
+    fn parse_ref(&self, command: &clap::Command, arg: Option<&clap::Arg>, value: &std::ffi::OsStr) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(command, arg, value)
+    }
+    
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(registry::keygen_names().map(PossibleValue::new)))
+    }
+}
+
+// Prefix of a --key naming an environment variable that holds a hex private key. Key
+// names cannot contain ':', so it never shadows a keystore key.
+const ENV_KEY_PREFIX: &str = "env:";
+const ENV_KEY_SCHEMES: [&str; 3] = ["ecdsa", "bls", "bls-min-sig"];
+
+// Passphrase for export-key, import-key, backup and restore when --passphrase-file is not given
+const PASSPHRASE_ENV: &str = "SIG_TOOL_PASSPHRASE";
+
+// Keystore when neither --keystore nor --profile is given, and keygen scheme when neither
+// --scheme nor a profile's is; both before the defaults in the profiles file
+const KEYSTORE_ENV: &str = "SIG_TOOL_KEYSTORE";
+const DEFAULT_SCHEME_ENV: &str = "SIG_TOOL_DEFAULT_SCHEME";
+
+// What verify calls a --public-key in messages, in place of a key name
+const PUBLIC_KEY_NAME: &str = "--public-key";
+
+// What sign and the audit log call a --private-key, which must never be repeated
+const PRIVATE_KEY_NAME: &str = "--private-key";
+
+const DEFAULT_KEYSTORE: &str = "~/.sig-tool";
+// sign and verify show a progress bar for message files larger than this
+const PROGRESS_THRESHOLD: u64 = 64 * 1024 * 1024;
+// Reading more of stdin than this into memory warns; --prehash hashes it as it is read instead
+const STDIN_WARNING_BYTES: usize = 256 * 1024 * 1024;
+// --prehash reads messages through a buffer of this size, however long they are
+const HASH_CHUNK_BYTES: usize = 1024 * 1024;
+// Messages larger than this are left out of signature files unless --max-embed-size allows them
+const DEFAULT_MAX_EMBED_SIZE: usize = 4 * 1024 * 1024;
+// Domain separation for sign --signed-timestamp payloads
+const SIGNED_TIMESTAMP_PREFIX: &[u8] = b"sig-tool signed-timestamp v1\0";
+const PROFILES_FILE: &str = "~/.sig-tool/profiles.toml";
+
+// How a command reports what it did: human-readable lines, or with --json a single
+// JSON object printed once it is done
+struct Output<'a> {
+    verbose: bool,
+    quiet: bool,
+    json: bool,
+    text_encoding: &'a str,
+}
+
+impl Output<'_> {
+    fn print_json(&self, value: &serde_json::Value) {
+        println!("{}", value);
+    }
+    
+    // The one line a script reads from a command: bare with --quiet, labelled otherwise,
+    // and left to the JSON report with --json
+    fn result(&self, label: &str, value: &str) {
+        if self.json {
+            return;
+        }
+        if self.quiet {
+            println!("{}", value);
+        } else {
+            println!("{}: {}", label, value);
+        }
+    }
+}
+
+// println! for what a command reports, which --json replaces
+macro_rules! outln {
+    ($out:expr, $($arg:tt)*) => {
+        if !$out.json {
+            println!($($arg)*);
+        }
+    };
+}
+
+// println! for progress and confirmations, which --quiet and --json suppress
+macro_rules! infoln {
+    ($out:expr, $($arg:tt)*) => {
+        if !$out.json && !$out.quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
+// eprintln! for --verbose details, kept off stdout so it stays parseable
+macro_rules! verboseln {
+    ($out:expr, $($arg:tt)*) => {
+        if $out.verbose {
+            eprintln!($($arg)*);
+        }
+    };
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Manage named keystore profiles
+    Profile {
+        #[clap(subcommand)]
+        action: ProfileAction,
+    },
+    
+    /// Query and verify the keystore's log of signing and key-management operations
+    Audit {
+        #[clap(subcommand)]
+        action: AuditAction,
+    },
+    
+    /// Print a shell completion script, e.g. sig-tool completions bash > ~/.local/share/bash-completion/completions/sig-tool
+    Completions {
+        /// Shell to write the script for
+        #[clap(value_parser = completions::SHELLS)]
+        shell: String,
+    },
+    
+    /// Show what a signature file holds and whether it is well-formed, without a keystore or
+    /// the message; exits with 1 when problems are found
+    #[clap(name = "inspect-sig")]
+    InspectSig {
+        /// Signature file to inspect
+        #[clap(value_parser = parse_path)]
+        file: PathBuf,
+    },
+    
+    /// List the keystore's key names, one per line, for the completion scripts
+    #[clap(name = completions::COMPLETE_KEYS, hide = true)]
+    CompleteKeys,
+    
     #[clap(name = "keygen")]
     KeyGen {
         /// Name to identify the key
-        #[clap(short, long)]
-        name: String,
+        #[clap(short, long, value_parser = parse_key_name, required_unless_present = "no_save")]
+        name: Option<String>,
+        
+        /// Signature scheme to use (defaults to the profile's scheme, or ecdsa)
+        #[clap(short, long, value_parser = keygen_schemes())]
+        scheme: Option<String>,
+        
+        /// Derive the key from a seed with EIP-2333 instead of generating it (bls only)
+        #[clap(long, requires_all = ["seed_file", "path"])]
+        derive: bool,
+        
+        /// File containing the hex-encoded seed (at least 32 bytes). Without --derive the key
+        /// is generated deterministically from it
+        #[clap(long, value_parser = parse_path)]
+        seed_file: Option<PathBuf>,
+        
+        /// EIP-2333 derivation path, e.g. m/12381/3600/0/0/0
+        #[clap(long, requires = "derive")]
+        path: Option<String>,
+        
+        /// Overwrite an existing key with the same name, and with --backend yubikey, the key
+        /// already in the PIV slot
+        #[clap(long)]
+        force: bool,
+        
+        /// Expire the key after a duration (e.g. 90d, 12h) or on a date (YYYY-MM-DD, UTC)
+        #[clap(long)]
+        expires_in: Option<String>,
+        
+        /// PIV slot to generate the key in with --backend yubikey (p256 keys only)
+        #[clap(long, value_parser = PIV_SLOTS)]
+        slot: Option<String>,
+        
+        /// Print the key pair instead of saving it, e.g. for test vectors; the keystore is not
+        /// touched and may be read-only
+        #[clap(long, conflicts_with_all = ["name", "force", "expires_in", "slot"])]
+        no_save: bool,
         
-        /// Signature scheme to use
-        #[clap(short, long, default_value = "ecdsa", value_parser = ["ecdsa", "bls"])]
-        scheme: String,
+        /// Also print the private key of a --no-save key pair
+        #[clap(long, requires = "no_save", conflicts_with_all = ["name", "force", "expires_in", "slot"])]
+        show_private: bool,
     },
     
     /// List all saved keys
     #[clap(name = "list-keys")]
-    ListKeys,
+    ListKeys {
+        /// Also show the Ethereum address of each ECDSA-secp256k1 key
+        #[clap(long)]
+        addresses: bool,
+        
+        /// Only list keys with this tag
+        #[clap(long)]
+        tag: Option<String>,
+        
+        /// Also show when each key last signed and how many signatures it has made
+        #[clap(long)]
+        long: bool,
+        
+        /// Output format: aligned columns with local times, JSON metadata (as --json prints
+        /// it) or CSV for spreadsheets; without it keys are listed one per line
+        #[clap(long, value_parser = ["table", "json", "csv"])]
+        format: Option<String>,
+    },
+    
+    /// Rebuild the keystore index from the key files
+    Reindex,
+    
+    /// Upgrade every key file to the current format
+    Migrate,
+    
+    /// Set or clear the expiry of a key
+    #[clap(name = "set-expiry")]
+    SetExpiry {
+        /// Key to update
+        #[clap(short, long)]
+        key: String,
+        
+        /// Expire the key after a duration from now (e.g. 90d, 12h) or on a date (YYYY-MM-DD, UTC)
+        #[clap(long, required_unless_present = "never")]
+        expires_in: Option<String>,
+        
+        /// Remove the expiry
+        #[clap(long, conflicts_with = "expires_in")]
+        never: bool,
+    },
+    
+    /// Add or remove tags on a key
+    #[clap(name = "tag")]
+    Tag {
+        /// Key to tag
+        #[clap(short, long)]
+        key: String,
+        
+        /// Comma-separated tags to add
+        #[clap(long, use_value_delimiter = true, value_delimiter = ',', required_unless_present = "remove")]
+        add: Vec<String>,
+        
+        /// Comma-separated tags to remove
+        #[clap(long, use_value_delimiter = true, value_delimiter = ',')]
+        remove: Vec<String>,
+    },
+    
+    /// Delete a key from the keystore
+    #[clap(name = "delete-key")]
+    DeleteKey {
+        /// Key to delete
+        #[clap(short, long)]
+        key: String,
+        
+        /// Delete without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+        
+        /// Overwrite the key file before removing it
+        #[clap(long)]
+        shred: bool,
+    },
+    
+    /// Rename a key in the keystore
+    #[clap(name = "rename-key")]
+    RenameKey {
+        /// Current name of the key
+        #[clap(long)]
+        from: String,
+        
+        /// New name for the key
+        #[clap(long, value_parser = parse_key_name)]
+        to: String,
+        
+        /// Overwrite an existing key with the new name
+        #[clap(long)]
+        force: bool,
+    },
+    
+    /// Export a key to a passphrase-encrypted file for moving it to another machine
+    #[clap(name = "export-key")]
+    ExportKey {
+        /// Key to export
+        #[clap(short, long)]
+        key: String,
+        
+        /// Output file for the encrypted key
+        #[clap(short, long, value_parser = parse_path)]
+        output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+        
+        /// File containing the passphrase to encrypt the key under (otherwise
+        /// SIG_TOOL_PASSPHRASE, or a prompt that asks twice)
+        #[clap(long, value_parser = parse_path)]
+        passphrase_file: Option<PathBuf>,
+        
+        /// File format: sig-tool's own, an EIP-2335 keystore for Ethereum validator clients (BLS
+        /// keys only), or a web3 (geth UTC--...json) keystore (ECDSA-secp256k1 keys only)
+        #[clap(long, default_value = "sig-tool", value_parser = ["sig-tool", "eip2335", "web3"])]
+        format: String,
+        
+        /// Key derivation function for EIP-2335 and web3 keystores
+        #[clap(long, value_parser = ["scrypt", "pbkdf2"])]
+        kdf: Option<String>,
+    },
+    
+    /// Import a key written by export-key
+    #[clap(name = "import-key")]
+    ImportKey {
+        /// Encrypted key file
+        #[clap(short, long, value_parser = parse_path)]
+        input: PathBuf,
+        
+        /// File containing the passphrase the key was exported under (otherwise
+        /// SIG_TOOL_PASSPHRASE, or a prompt)
+        #[clap(long, value_parser = parse_path)]
+        passphrase_file: Option<PathBuf>,
+        
+        /// Save the key under this name instead of the exported one (EIP-2335 and web3
+        /// keystores default to the file name)
+        #[clap(short, long, value_parser = parse_key_name)]
+        name: Option<String>,
+        
+        /// File format: sig-tool's own, an EIP-2335 keystore from Ethereum validator tooling,
+        /// or a web3 (geth UTC--...json) keystore
+        #[clap(long, default_value = "sig-tool", value_parser = ["sig-tool", "eip2335", "web3"])]
+        format: String,
+    },
+    
+    /// Import a public key to verify signatures with, from an OpenSSH .pub file (ssh-ed25519)
+    #[clap(name = "import-public-key")]
+    ImportPublicKey {
+        /// Public key file, e.g. ~/.ssh/id_ed25519.pub
+        #[clap(short, long, value_parser = parse_path)]
+        input: PathBuf,
+        
+        /// Save the key under this name instead of the file name
+        #[clap(short, long, value_parser = parse_key_name)]
+        name: Option<String>,
+    },
+    
+    /// Back up every file in the keystore to a passphrase-encrypted archive
+    #[clap(name = "backup")]
+    Backup {
+        /// Output file for the archive
+        #[clap(short, long, value_parser = parse_path)]
+        output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+        
+        /// File containing the passphrase to encrypt the archive under (otherwise
+        /// SIG_TOOL_PASSPHRASE, or a prompt that asks twice)
+        #[clap(long, value_parser = parse_path)]
+        passphrase_file: Option<PathBuf>,
+    },
+    
+    /// Restore keys from an archive written by backup
+    #[clap(name = "restore")]
+    Restore {
+        /// Archive to restore from
+        #[clap(short, long, value_parser = parse_path)]
+        input: PathBuf,
+        
+        /// File containing the passphrase the archive was encrypted under (otherwise
+        /// SIG_TOOL_PASSPHRASE, or a prompt)
+        #[clap(long, value_parser = parse_path)]
+        passphrase_file: Option<PathBuf>,
+        
+        /// Replace keys that already exist instead of skipping them
+        #[clap(long)]
+        overwrite: bool,
+    },
+    
+    /// Show the public key of a saved key
+    #[clap(name = "show-key")]
+    ShowKey {
+        /// Key to show
+        #[clap(short, long)]
+        key: String,
+        
+        /// Point encoding for BLS public keys (defaults to compressed)
+        #[clap(long, value_parser = ["compressed", "uncompressed"])]
+        encoding: Option<String>,
+        
+        /// Output format for the public key: hex, base64, or pem for schemes with a standard
+        /// SubjectPublicKeyInfo encoding (defaults to hex)
+        #[clap(long, value_parser = ["hex", "base64", "pem"])]
+        format: Option<String>,
+        
+        /// Also write the public key to a file: raw bytes, or the PEM document with --format pem
+        #[clap(long, value_parser = parse_path)]
+        out: Option<PathBuf>,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+    },
+    
+    /// Show what a key file holds and check that its public key belongs to its private key;
+    /// exits with 1 when problems are found
+    #[clap(name = "inspect-key")]
+    InspectKey {
+        /// Key to inspect
+        #[clap(short, long, required_unless_present = "path", conflicts_with = "path")]
+        key: Option<String>,
+        
+        /// Key file to inspect instead, e.g. one outside the keystore; the keystore is not opened
+        #[clap(long, value_parser = parse_path)]
+        path: Option<PathBuf>,
+    },
     
     /// Sign a message
     #[clap(name = "sign")]
     Sign {
-        /// Key to use for signing
-        #[clap(short, long)]
-        key: String,
+        /// Key to use for signing, or env:VAR to use the hex private key in environment
+        /// variable VAR without a keystore (with --scheme)
+        #[clap(short, long, required_unless_present_any = ["ssh_agent", "private_key"])]
+        key: Option<String>,
+        
+        /// Hex private key to sign with instead of a keystore key (with --scheme), or @path to
+        /// read it from a file and keep it out of shell history
+        #[clap(long, conflicts_with_all = ["key", "ssh_agent"])]
+        private_key: Option<String>,
+        
+        /// Scheme of the private key in an env:VAR --key or a --private-key
+        #[clap(long, value_parser = ENV_KEY_SCHEMES)]
+        scheme: Option<String>,
         
         /// Message to sign (string)
         #[clap(short, long)]
         message: Option<String>,
         
-        /// File containing message to sign
-        #[clap(short, long)]
+        /// File containing message to sign, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
         file: Option<PathBuf>,
         
+        /// Precomputed 32-byte SHA-256 digest (hex) to sign instead of a message (ECDSA-secp256k1 and BLS only)
+        #[clap(long, conflicts_with_all = ["message", "file", "prehash"])]
+        digest: Option<String>,
+        
         /// Output file for the signature
-        #[clap(short, long)]
+        #[clap(short, long, value_parser = parse_path)]
         output: Option<PathBuf>,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+        
+        /// Hash the message with this function as it is read and sign the digest; recorded in
+        /// the signature file so verify hashes the same way
+        #[clap(long, value_parser = PREHASH_ALGORITHMS)]
+        prehash: Option<String>,
+        
+        /// Produce a 65-byte r||s||v recoverable signature (ECDSA-secp256k1 only)
+        #[clap(long)]
+        recoverable: bool,
+        
+        /// Sign as EIP-191 personal_sign: prefixed message, Keccak-256, recoverable (ECDSA-secp256k1 only)
+        #[clap(long, conflicts_with_all = ["prehash", "digest", "sig_encoding"])]
+        eip191: bool,
+        
+        /// Sign in the legacy Bitcoin Core message format and print the base64 signature (ECDSA-secp256k1 only)
+        #[clap(long, conflicts_with_all = ["prehash", "digest", "sig_encoding", "recoverable", "eip191"])]
+        bitcoin_message: bool,
+        
+        /// Make a BIP-322 simple signature and print the base64 witness stack
+        #[clap(long, conflicts_with_all = ["prehash", "digest", "sig_encoding", "recoverable", "eip191", "bitcoin_message"])]
+        bip322: bool,
+        
+        /// Address type to sign for with --bip322: p2wpkh (ECDSA keys) or p2tr (Schnorr keys)
+        #[clap(long, value_parser = ["p2wpkh", "p2tr"], requires = "bip322")]
+        address_type: Option<String>,
+        
+        /// Domain separation tag to sign under (BLS only, defaults to the NUL ciphersuite DST)
+        #[clap(long)]
+        dst: Option<String>,
+        
+        /// Named DST preset instead of a raw --dst: eth2, filecoin, generic-nul or generic-pop (min-pk only)
+        #[clap(long, conflicts_with = "dst")]
+        ciphersuite: Option<String>,
+        
+        /// Message-augmentation mode: prefix the message with the signer's public key (BLS min-pk only)
+        #[clap(long)]
+        aug: bool,
+        
+        /// Point encoding for BLS output (defaults to compressed)
+        #[clap(long, value_parser = ["compressed", "uncompressed"])]
+        encoding: Option<String>,
+        
+        /// Hedge the ECDSA-secp256k1 nonce with 32 bytes of extra entropy: hex, or "random"
+        /// to draw it from the OS (signatures are then no longer deterministic)
+        #[clap(long, conflicts_with_all = ["bitcoin_message", "bip322"])]
+        extra_entropy: Option<String>,
+        
+        /// Signature encoding for ECDSA-secp256k1 output: DER or 64-byte r||s (defaults to der)
+        #[clap(long, value_parser = ["der", "compact"], conflicts_with = "recoverable")]
+        sig_encoding: Option<String>,
+        
+        /// Sign even if the key has expired
+        #[clap(long)]
+        allow_expired: bool,
+        
+        /// Sign with an Ed25519 key loaded in ssh-agent ($SSH_AUTH_SOCK) instead of a keystore key
+        #[clap(long, requires = "ssh_key", conflicts_with = "key")]
+        ssh_agent: bool,
+        
+        /// Comment or SHA256 fingerprint (as ssh-add -l prints) of the agent key to sign with
+        #[clap(long, requires = "ssh_agent")]
+        ssh_key: Option<String>,
+        
+        /// Do not record the signature in the key's usage count, e.g. on a read-only keystore
+        #[clap(long)]
+        no_track: bool,
+        
+        /// Write only the raw signature bytes: to --output instead of a JSON signature file, or
+        /// to stdout for piping into other tools
+        #[clap(long, conflicts_with_all = ["bitcoin_message", "bip322"])]
+        raw: bool,
+        
+        /// Store the message (base64) in the signature file, so verify needs only the file
+        #[clap(long, requires = "output", conflicts_with_all = ["digest", "prehash", "bitcoin_message", "bip322", "raw"])]
+        embed: bool,
+        
+        /// Largest message --embed stores, in bytes
+        #[clap(long, default_value_t = DEFAULT_MAX_EMBED_SIZE, requires = "embed")]
+        max_embed_size: usize,
+        
+        /// Sign the timestamp together with the message, so verify --max-age and
+        /// --min-timestamp can rely on it rather than only advise
+        #[clap(long, requires = "output", conflicts_with_all = ["digest", "prehash", "eip191", "bitcoin_message", "bip322", "raw", "ssh_agent"])]
+        signed_timestamp: bool,
+        
+        /// Do not show a progress bar while reading a large --file
+        #[clap(long)]
+        no_progress: bool,
     },
     
     /// Verify a signature
     #[clap(name = "verify")]
     Verify {
-        /// Key to use for verification
+        /// Key to use for verification (optional for Bitcoin message and BIP-322 signatures)
+        #[clap(short, long)]
+        key: Option<String>,
+        
+        /// Hex public key to verify against instead of a keystore key; its scheme comes from
+        /// --scheme or the signature file
+        #[clap(long, conflicts_with_all = ["key", "bitcoin_message", "bip322"])]
+        public_key: Option<String>,
+        
+        /// Signature file to verify (or a base64 signature with --bitcoin-message)
+        #[clap(short, long, value_parser = parse_path)]
+        signature: PathBuf,
+        
+        /// Message that was signed (string); optional when the signature file embeds it, and
+        /// then checked against the embedded message
+        #[clap(short, long)]
+        message: Option<String>,
+        
+        /// File containing message that was signed, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
+        file: Option<PathBuf>,
+        
+        /// Precomputed 32-byte SHA-256 digest (hex), for signatures made with sign --digest
+        #[clap(long, conflicts_with_all = ["message", "file", "prehash"])]
+        digest: Option<String>,
+        
+        /// Hash the message with this function before verifying; must match the one a signature
+        /// file records, and is needed for raw and older keccak256 signatures
+        #[clap(long, value_parser = PREHASH_ALGORITHMS)]
+        prehash: Option<String>,
+        
+        /// Verify as EIP-191 personal_sign (implied by an EIP-191 signature file)
+        #[clap(long, conflicts_with_all = ["prehash", "digest"])]
+        eip191: bool,
+        
+        /// Verify a legacy Bitcoin Core signed message against --address or the key's P2PKH address
+        /// (implied by a Bitcoin message signature file)
+        #[clap(long, conflicts_with_all = ["prehash", "digest", "eip191"])]
+        bitcoin_message: bool,
+        
+        /// Verify a BIP-322 simple signature against --address, the key's address or the
+        /// address recorded in the signature file (implied by a BIP-322 signature file)
+        #[clap(long, conflicts_with_all = ["prehash", "digest", "eip191", "bitcoin_message"])]
+        bip322: bool,
+        
+        /// Address the message must be signed by (with --bitcoin-message or --bip322)
+        #[clap(long)]
+        address: Option<String>,
+        
+        /// Domain separation tag to verify under (BLS only, defaults to the one recorded in the signature file)
+        #[clap(long)]
+        dst: Option<String>,
+        
+        /// Named DST preset instead of a raw --dst: eth2, filecoin, generic-nul or generic-pop (min-pk only)
+        #[clap(long, conflicts_with = "dst")]
+        ciphersuite: Option<String>,
+        
+        /// Verify in message-augmentation mode (implied by an AUG signature file)
+        #[clap(long)]
+        aug: bool,
+        
+        /// The signature file holds raw signature bytes, as written by sign --raw
+        #[clap(long, requires = "scheme", conflicts_with_all = ["bitcoin_message", "bip322"])]
+        raw: bool,
+        
+        /// Accept an ECDSA-secp256k1 signature with a high S, as OpenSSL and other tools that do
+        /// not normalize S produce, by verifying its low-S form. Either form then verifies, so
+        /// leave this off where signatures must not be malleable
+        #[clap(long, conflicts_with_all = ["bitcoin_message", "bip322"])]
+        allow_high_s: bool,
+        
+        /// Scheme of a raw signature or a --public-key: a keygen scheme such as ecdsa or bls, or
+        /// a scheme name as signature files record it. Must agree with a scheme the file records
+        #[clap(long, value_parser = SchemeNameParser)]
+        scheme: Option<String>,
+        
+        /// Reject a valid signature made longer ago than this (30s, 15m, 1h, 7d) as INVALID-STALE.
+        /// Advisory unless signed with --signed-timestamp: the recorded time is not otherwise signed
+        #[clap(long, conflicts_with_all = ["raw", "bitcoin_message", "bip322"])]
+        max_age: Option<String>,
+        
+        /// Reject a valid signature made before this RFC 3339 time as INVALID-STALE. Advisory
+        /// unless signed with --signed-timestamp, like --max-age
+        #[clap(long, conflicts_with_all = ["raw", "bitcoin_message", "bip322"])]
+        min_timestamp: Option<String>,
+        
+        /// Verify with the public key embedded in the signature file, without a keystore. This
+        /// shows only that the holder of that key signed; pin the fingerprint it reports
+        #[clap(long, conflicts_with_all = ["key", "public_key", "digest", "prehash", "eip191", "bitcoin_message", "bip322", "address", 
+                                           "dst", "ciphersuite", "aug", "raw", "allow_high_s", "scheme", "max_age", "min_timestamp"])]
+        standalone: bool,
+        
+        /// Do not show a progress bar while reading a large --file
+        #[clap(long)]
+        no_progress: bool,
+    },
+    
+    /// Verify the signature file beside each artifact in a directory: app.tar.gz.sig signs app.tar.gz
+    #[clap(name = "verify-manifest")]
+    VerifyManifest {
+        /// Directory holding the artifacts and their signature files
+        #[clap(long, value_parser = parse_path)]
+        dir: PathBuf,
+        
+        /// Key the artifacts were signed with
+        #[clap(short, long)]
+        key: String,
+        
+        /// File names of the signature files; each signs the file named without its extension
+        #[clap(long, default_value = "*.sig")]
+        pattern: String,
+        
+        /// Do not fail on artifacts without a signature file
+        #[clap(long)]
+        allow_unsigned: bool,
+    },
+    
+    /// Sign a directory: one signature over the path, size and SHA-256 of every file under it
+    #[clap(name = "sign-tree")]
+    SignTree {
+        /// Directory to sign
+        #[clap(long, value_parser = parse_path)]
+        dir: PathBuf,
+        
+        /// Key to sign with
         #[clap(short, long)]
         key: String,
         
-        /// Signature file to verify
+        /// Output file for the manifest and its signature
+        #[clap(short, long, value_parser = parse_path)]
+        output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+    },
+    
+    /// Check a directory against a sign-tree signature, listing added, removed and modified files
+    #[clap(name = "verify-tree")]
+    VerifyTree {
+        /// Directory to check
+        #[clap(long, value_parser = parse_path)]
+        dir: PathBuf,
+        
+        /// Key the directory was signed with
         #[clap(short, long)]
+        key: String,
+        
+        /// Signature file written by sign-tree
+        #[clap(short, long, value_parser = parse_path)]
         signature: PathBuf,
+    },
+    
+    /// Derive a BIP-341 taproot output key from a secp256k1 key
+    #[clap(name = "tweak")]
+    Tweak {
+        /// secp256k1 key (Schnorr or ECDSA) to tweak
+        #[clap(short, long)]
+        key: String,
+        
+        /// Merkle root of the taproot script tree (32-byte hex); omit for key-path-only outputs
+        #[clap(long)]
+        merkle_root: Option<String>,
+        
+        /// Name for the tweaked key (defaults to <key>-tweaked)
+        #[clap(long, value_parser = parse_key_name)]
+        save_as: Option<String>,
+    },
+    
+    /// Recover the secp256k1 public key that made a 65-byte recoverable ECDSA signature
+    #[clap(name = "recover")]
+    Recover {
+        /// Recoverable signature: a signature file or r||s||v hex
+        #[clap(short, long)]
+        signature: String,
         
         /// Message that was signed (string)
         #[clap(short, long)]
         message: Option<String>,
         
-        /// File containing message that was signed
-        #[clap(short, long)]
+        /// File containing message that was signed, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
         file: Option<PathBuf>,
+        
+        /// Digest the signature was computed over (defaults to the one recorded in the signature file, else sha256)
+        #[clap(long, value_parser = ["keccak256", "sha256"])]
+        hash: Option<String>,
+        
+        /// The message was signed with EIP-191 personal_sign (implied by an EIP-191 signature file)
+        #[clap(long, conflicts_with = "hash")]
+        eip191: bool,
     },
     
     /// Aggregate BLS signatures
     #[clap(name = "aggregate")]
     Aggregate {
-        /// Signature files to aggregate (comma-separated)
-        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',')]
+        /// Signature files to aggregate (comma-separated). A glob pattern such as 'sigs/*.sig'
+        /// or a directory, for every *.sig in it, is expanded in path order
+        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',', value_parser = parse_path)]
         signatures: Vec<PathBuf>,
         
         /// Output file for the aggregated signature
-        #[clap(short, long)]
+        #[clap(short, long, value_parser = parse_path)]
         output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+        
+        /// Domain separation tag the signatures were made under (must match the recorded one)
+        #[clap(long)]
+        dst: Option<String>,
+        
+        /// Named DST preset instead of a raw --dst: eth2, filecoin, generic-nul or generic-pop (min-pk only)
+        #[clap(long, conflicts_with = "dst")]
+        ciphersuite: Option<String>,
+        
+        /// Point encoding for BLS output (defaults to compressed)
+        #[clap(long, value_parser = ["compressed", "uncompressed"])]
+        encoding: Option<String>,
+        
+        /// Committee member keys in committee order (comma-separated); records who signed in a bitmap
+        #[clap(long, use_value_delimiter = true, value_delimiter = ',')]
+        committee: Vec<String>,
+        
+        /// Message that was signed, used to attribute signatures to committee members
+        #[clap(short, long)]
+        message: Option<String>,
+        
+        /// File containing the message that was signed, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
+        file: Option<PathBuf>,
+        
+        /// JSON array of {"signature": "<file>", "message": "<string, hex:<bytes> or @file>"} entries
+        /// giving each signature its own message, in --signatures order (or instead of --signatures)
+        #[clap(long, conflicts_with_all = ["committee", "message", "file"], value_parser = parse_path)]
+        mapping: Option<PathBuf>,
     },
     
     /// Verify an aggregated BLS signature
+    ///
+    /// With --message/--file every key signed the same message. With
+    /// --messages-file each signer signed its own message: entry i of the JSON
+    /// array pairs a key with the message it signed, and when --keys is also
+    /// given it must list the same keys in the same order. Aggregates made with
+    /// --committee need no keys: the signers are read from their bitmap, and their
+    /// keys from the keystore, which must hold the keys the file records. Aggregates
+    /// made with --mapping need neither keys nor messages: they record each
+    /// signer's key fingerprint, looked up in the keystore, and where its message
+    /// is, checked against its SHA-256.
     #[clap(name = "verify-aggregate")]
     VerifyAggregate {
         /// Public keys to use for verification (comma-separated)
         #[clap(short, long, use_value_delimiter = true, value_delimiter = ',')]
         keys: Vec<String>,
         
+        /// Aggregated public key created with aggregate-keys, instead of listing every member
+        #[clap(long, conflicts_with_all = ["keys", "messages_file", "require_pop"])]
+        key: Option<String>,
+        
         /// Aggregated signature file to verify
-        #[clap(short, long)]
+        #[clap(short, long, value_parser = parse_path)]
         signature: PathBuf,
         
         /// Message that was signed (string)
         #[clap(short, long)]
         message: Option<String>,
         
-        /// File containing message that was signed
-        #[clap(short, long)]
+        /// File containing message that was signed, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
         file: Option<PathBuf>,
+        
+        /// JSON array of {"key": "<name>", "message": "<string, hex:<bytes> or @file>"} entries, in signer order
+        #[clap(long, conflicts_with_all = ["message", "file"], value_parser = parse_path)]
+        messages_file: Option<PathBuf>,
+        
+        /// Directory of <key>.pop files; refuse to verify unless every key has a valid proof of possession
+        #[clap(long, value_parser = parse_path)]
+        require_pop: Option<PathBuf>,
+        
+        /// Domain separation tag to verify under (defaults to the one recorded in the signature file)
+        #[clap(long)]
+        dst: Option<String>,
+        
+        /// Named DST preset instead of a raw --dst: eth2, filecoin, generic-nul or generic-pop (min-pk only)
+        #[clap(long, conflicts_with = "dst")]
+        ciphersuite: Option<String>,
+        
+        /// Verify in message-augmentation mode (implied by an AUG signature file)
+        #[clap(long)]
+        aug: bool,
     },
-}
-
-pub fn run_cli(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    // Expand ~ to home directory if needed
-    let keystore_path = if cli.keystore.starts_with("~/") {
-        let home = dirs::home_dir().expect("Could not find home directory");
-        home.join(&cli.keystore[2..])
-    } else {
-        PathBuf::from(cli.keystore)
-    };
     
-    let keystore = KeyStore::new(keystore_path)?;
-    
-    match cli.command {
-        Commands::KeyGen { name, scheme } => {
-            match scheme.as_str() {
-                "ecdsa" => {
-                    let (private_key, public_key) = ECDSA::generate_keypair()?;
-                    keystore.save_keypair::<ECDSA>(&name, &private_key, &public_key)?;
-                    println!("Generated ECDSA key pair: {}", name);
-                }
-                "bls" => {
-                    let (private_key, public_key) = BLS::generate_keypair()?;
-                    keystore.save_keypair::<BLS>(&name, &private_key, &public_key)?;
-                    println!("Generated BLS key pair: {}", name);
-                }
-                _ => {
-                    return Err(format!("Unsupported signature scheme: {}", scheme).into());
-                }
-            }
-        }
+    /// Aggregate BLS signatures in memory and verify the result in one step
+    #[clap(name = "aggregate-verify")]
+    AggregateVerify {
+        /// Signature files to aggregate (comma-separated)
+        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',', value_parser = parse_path)]
+        signatures: Vec<PathBuf>,
         
-        Commands::ListKeys => {
-            let keys = keystore.list_keys()?;
-            println!("Found {} keys:", keys.len());
-            for key in keys {
-                println!("- {} ({}, created: {})", key.name, key.scheme, key.created_at);
-            }
-        }
+        /// Public keys of the signers (comma-separated)
+        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',')]
+        keys: Vec<String>,
         
-        Commands::Sign { key, message, file, output } => {
-            let key_entry = keystore.load_key_entry(&key)?;
-            let msg = get_message(message, file)?;
-            
-            match key_entry.metadata.scheme.as_str() {
-                "ECDSA-secp256k1" => {
-                    let private_key_bytes = hex::decode(&key_entry.private_key)
-                        .map_err(|_| StorageError::InvalidFormat)?;
-                    let private_key = ECDSA::deserialize_private_key(&private_key_bytes)?;
-                    
-                    let signature = ECDSA::sign(&private_key, &msg)?;
-                    let sig_bytes = ECDSA::serialize_signature(&signature)?;
-                    
-                    if let Some(output_path) = output {
-                        save_signature(&output_path, "ECDSA-secp256k1", &sig_bytes)?;
-                        println!("Signature saved to {:?}", output_path);
-                    } else {
-                        println!("Signature: {}", hex::encode(&sig_bytes));
-                    }
-                }
-                "BLS12-381-min-pk" => {
-                    let private_key_bytes = hex::decode(&key_entry.private_key)
-                        .map_err(|_| StorageError::InvalidFormat)?;
-                    let private_key = BLS::deserialize_private_key(&private_key_bytes)?;
-                    
-                    let signature = BLS::sign(&private_key, &msg)?;
-                    let sig_bytes = BLS::serialize_signature(&signature)?;
-                    if let Some(output_path) = output {
-                        save_signature(&output_path, "BLS12-381-min-pk", &sig_bytes)?;
-                        println!("Signature saved to {:?}", output_path);
-                    } else {
-                        println!("Signature: {}", hex::encode(&sig_bytes));
-                    }
-                }
-                _ => {
-                    return Err(format!("Unsupported signature scheme: {}", key_entry.metadata.scheme).into());
-                }
+        /// Message that was signed (string)
+        #[clap(short, long)]
+        message: Option<String>,
+        
+        /// File containing message that was signed, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
+        file: Option<PathBuf>,
+        
+        /// Require exactly one key per signature file
+        #[clap(long)]
+        paired: bool,
+        
+        /// Also save the aggregate here when it verifies
+        #[clap(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+    },
+    
+    /// Verify many independent BLS signatures with a single batched check
+    #[clap(name = "verify-batch")]
+    VerifyBatch {
+        /// JSON lines of {"key": "<name>", "message_hex": "<hex>", "signature": "<path or hex>"},
+        /// with an optional "dst" for hex signatures not made under the default DST
+        #[clap(short, long, value_parser = parse_path)]
+        input: PathBuf,
+    },
+    
+    /// Aggregate BLS public keys into a single public-only key entry
+    #[clap(name = "aggregate-keys")]
+    AggregateKeys {
+        /// BLS keys to aggregate (comma-separated)
+        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',')]
+        keys: Vec<String>,
+        
+        /// Name for the aggregated key
+        #[clap(long, value_parser = parse_key_name)]
+        save_as: String,
+        
+        /// Directory of <key>.pop files; refuse to aggregate unless every key has a valid proof of possession
+        #[clap(long, value_parser = parse_path)]
+        require_pop: Option<PathBuf>,
+    },
+    
+    /// Split a BLS key into Shamir shares for threshold signing
+    #[clap(name = "split-key")]
+    SplitKey {
+        /// BLS key to split
+        #[clap(short, long)]
+        key: String,
+        
+        /// Number of shares needed to sign
+        #[clap(short, long)]
+        threshold: u32,
+        
+        /// Number of shares to create
+        #[clap(long)]
+        shares: u32,
+        
+        /// Directory for the <key>-share-<i>.json files
+        #[clap(short, long, default_value = ".", value_parser = parse_path)]
+        output_dir: PathBuf,
+    },
+    
+    /// Sign a message with a key share, producing a partial signature
+    #[clap(name = "sign-share")]
+    SignShare {
+        /// Share file created by split-key
+        #[clap(long, value_parser = parse_path)]
+        share: PathBuf,
+        
+        /// Message to sign (string)
+        #[clap(short, long)]
+        message: Option<String>,
+        
+        /// File containing message to sign, or - to read it from stdin
+        #[clap(short, long, value_parser = parse_path)]
+        file: Option<PathBuf>,
+        
+        /// Output file for the partial signature
+        #[clap(short, long, value_parser = parse_path)]
+        output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+    },
+    
+    /// Combine threshold partial signatures into a full signature
+    #[clap(name = "combine")]
+    Combine {
+        /// Partial signature files (comma-separated)
+        #[clap(short, long, use_value_delimiter = true, value_delimiter = ',', value_parser = parse_path)]
+        partials: Vec<PathBuf>,
+        
+        /// Output file for the combined signature
+        #[clap(short, long, value_parser = parse_path)]
+        output: PathBuf,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+    },
+    
+    /// Create a proof of possession for a BLS key
+    #[clap(name = "pop")]
+    Pop {
+        /// BLS key to prove possession of
+        #[clap(short, long)]
+        key: String,
+        
+        /// Output file for the proof (defaults to <key>.pop)
+        #[clap(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+        
+        /// Replace the output file if it exists, without asking
+        #[clap(long, visible_alias = "yes")]
+        force: bool,
+        
+        /// Point encoding for BLS output (defaults to compressed)
+        #[clap(long, value_parser = ["compressed", "uncompressed"])]
+        encoding: Option<String>,
+    },
+    
+    /// Verify a proof of possession for a BLS key
+    #[clap(name = "verify-pop")]
+    VerifyPop {
+        /// BLS key the proof is for
+        #[clap(short, long)]
+        key: String,
+        
+        /// Proof of possession file
+        #[clap(short, long, value_parser = parse_path)]
+        pop: PathBuf,
+    },
+}
+
+// Scheme names for threshold key shares and the partial signatures made with them
+const SHARE_SCHEME: &str = "BLS12-381-min-pk-share";
+const PARTIAL_SCHEME: &str = "BLS12-381-min-pk-partial";
+
+// How a command that ran to completion came out. Verification commands finish with Invalid
+// when a signature does not check out, which exits with its own code; failures to run at
+// all are errors.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Success,
+    Invalid,
+}
+
+impl Outcome {
+    fn verified(is_valid: bool) -> Self {
+        if is_valid { Outcome::Success } else { Outcome::Invalid }
+    }
+}
+
+// Why a command failed to run. Commands raise the argument, signature file, file and
+// verification errors themselves; the errors of the keystore, schemes and signers convert
+// where they are raised. Each kind has a documented exit code and a name for --json.
+#[derive(Error, Debug)]
+pub enum CliError {
+    // Arguments that cannot be used together, or with the key or file given
+    #[error("{0}")]
+    Usage(String),
+
+    #[error(transparent)]
+    Path(#[from] PathError),
+
+    #[error(transparent)]
+    Profile(#[from] ProfileError),
+
+    // A line of a file listing work for a command, such as verify-batch's input
+    #[error("Line {line} of {path:?}: {message}")]
+    Input { path: PathBuf, line: usize, message: String },
+
+    // An output file that exists, when the command was not told to replace it
+    #[error("Refusing to overwrite {}; pass --force to replace it", path.display())]
+    OutputExists { path: PathBuf },
+
+    // An argument's value that does not parse, with what it takes
+    #[error("Invalid {argument} {value:?}: expected {expected}")]
+    InvalidValue { argument: &'static str, value: String, expected: &'static str },
+
+    #[error("Key not found: {key}")]
+    KeyNotFound { key: String },
+
+    #[error("Invalid signature file {path:?}: {source}")]
+    SignatureFile { path: PathBuf, source: StorageError },
+
+    // A signature, share or proof file of another scheme than the command works with
+    #[error("Expected a {expected} signature, found: {found}")]
+    WrongSignatureScheme { expected: String, found: String },
+
+    // A signature a command depends on does not verify; verify itself reports Outcome::Invalid
+    #[error("{0}")]
+    VerificationFailed(String),
+
+    #[error(transparent)]
+    Storage(StorageError),
+
+    #[error("Key {key} expired at {}; rotate it{}", format_timestamp(*expires_at),
+            if *overridable { ", extend it with set-expiry or pass --allow-expired" } else { " or extend it with set-expiry" })]
+    KeyExpired { key: String, expires_at: u64, overridable: bool },
+
+    // held says why: the key is on a hardware token, or public-only
+    #[error("Key {key} is {held} and cannot be exported; share its public key instead")]
+    NotExportable { key: String, held: String },
+
+    #[error("{failed} keys could not be migrated")]
+    Migration { failed: usize },
+
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+
+    // A private key given on the command line, in a file or the environment that does not
+    // decode; origin says where it came from, never what it is
+    #[error("{origin} does not hold a hex-encoded {scheme} private key")]
+    InvalidPrivateKey { origin: String, scheme: &'static str },
+
+    // A key file whose public key or address is not the one its private key gives
+    #[error("{0}")]
+    KeyMismatch(String),
+
+    #[error(transparent)]
+    Web3Keystore(#[from] Web3KeystoreError),
+
+    // A remote signer that cannot be reached or refuses to sign
+    #[error(transparent)]
+    Signer(SignerError),
+
+    #[error(transparent)]
+    Ssh(#[from] SshError),
+
+    #[error("{}", match path { Some(path) => format!("Could not read {:?}: {}", path, source), None => source.to_string() })]
+    Io { path: Option<PathBuf>, source: std::io::Error },
+
+    #[error(transparent)]
+    Audit(#[from] AuditError),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Completion(#[from] CompletionError),
+}
+
+impl CliError {
+    // The name --json reports the error under
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::Usage(_) => "usage",
+            CliError::Path(_) => "path",
+            CliError::Profile(_) => "profile",
+            CliError::Input { .. } => "input",
+            CliError::OutputExists { .. } => "output_exists",
+            CliError::InvalidValue { .. } => "invalid_value",
+            CliError::KeyNotFound { .. } => "key_not_found",
+            CliError::SignatureFile { .. } => "signature_file",
+            CliError::WrongSignatureScheme { .. } => "wrong_scheme",
+            CliError::VerificationFailed(_) => "verification_failed",
+            CliError::Storage(_) => "storage",
+            CliError::KeyExpired { .. } => "key_expired",
+            CliError::NotExportable { .. } => "not_exportable",
+            CliError::Migration { .. } => "migration",
+            CliError::Signature(_) => "signature",
+            CliError::InvalidPrivateKey { .. } => "invalid_private_key",
+            CliError::KeyMismatch(_) => "key_mismatch",
+            CliError::Web3Keystore(_) => "web3_keystore",
+            CliError::Signer(_) => "signer",
+            CliError::Ssh(_) => "ssh_agent",
+            CliError::Io { .. } => "io",
+            CliError::Audit(_) => "audit",
+            CliError::Json(_) => "json",
+            CliError::Completion(_) => "completion",
+        }
+    }
+
+    //   1: a signature failed verification (also Outcome::Invalid)
+    //   2: bad arguments or argument values, paths, profiles or input files, and output
+    //      files that exist (also clap's parse errors)
+    //   3: the key is not in the keystore
+    //   4: a signature file cannot be read, is malformed or is of the wrong scheme
+    //   5: any other keystore or signer error, including expired and unexportable keys
+    //   6: a key or signature that does not parse, a key file that does not decrypt or
+    //      does not match itself, or a failed cryptographic operation
+    //   7: a file cannot be read or written
+    //   8: the audit log cannot be written or fails verification
+    //   9: anything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::VerificationFailed(_) => 1,
+            CliError::Usage(_) | CliError::Path(_) | CliError::Profile(_) | CliError::Input { .. } | CliError::OutputExists { .. }
+            | CliError::InvalidValue { .. } => 2,
+            CliError::KeyNotFound { .. } => 3,
+            CliError::SignatureFile { .. } | CliError::WrongSignatureScheme { .. } => 4,
+            CliError::Ssh(SshError::PublicKey(_)) => 6,
+            CliError::Storage(_) | CliError::KeyExpired { .. } | CliError::NotExportable { .. } | CliError::Migration { .. }
+            | CliError::Signer(_) | CliError::Ssh(_) => 5,
+            CliError::Signature(_) | CliError::InvalidPrivateKey { .. } | CliError::KeyMismatch(_) | CliError::Web3Keystore(_) => 6,
+            CliError::Io { .. } => 7,
+            CliError::Audit(_) => 8,
+            CliError::Json(_) | CliError::Completion(_) => 9,
+        }
+    }
+}
+
+impl From<StorageError> for CliError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::KeyNotFound(key) => CliError::KeyNotFound { key },
+            StorageError::KeyExpired(key, expires_at) => CliError::KeyExpired { key, expires_at, overridable: false },
+            StorageError::Signature(err) => CliError::Signature(err),
+            StorageError::IO(source) => CliError::Io { path: None, source },
+            err => CliError::Storage(err),
+        }
+    }
+}
+
+impl From<SignerError> for CliError {
+    fn from(err: SignerError) -> Self {
+        match err {
+            SignerError::Storage(err) => err.into(),
+            SignerError::Signature(err) => CliError::Signature(err),
+            err => CliError::Signer(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(source: std::io::Error) -> Self {
+        CliError::Io { path: None, source }
+    }
+}
+
+// An argument error, for commands to return
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+// fs::read, fs::File::open and fs::read_to_string, naming the file when it cannot be read
+fn read_file(path: impl AsRef<Path>) -> Result<Vec<u8>, CliError> {
+    let path = path.as_ref();
+    fs::read(path).map_err(|source| CliError::Io { path: Some(path.to_path_buf()), source })
+}
+
+// For the other reads of a path: directory listings, metadata and links
+fn read_error(path: &Path) -> impl FnOnce(std::io::Error) -> CliError + '_ {
+    move |source| CliError::Io { path: Some(path.to_path_buf()), source }
+}
+
+fn open_file(path: impl AsRef<Path>) -> Result<fs::File, CliError> {
+    let path = path.as_ref();
+    fs::File::open(path).map_err(|source| CliError::Io { path: Some(path.to_path_buf()), source })
+}
+
+fn read_text_file(path: impl AsRef<Path>) -> Result<String, CliError> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|source| CliError::Io { path: Some(path.to_path_buf()), source })
+}
+
+// load_signature_with_info, naming the signature file when it cannot be loaded
+fn read_signature_file(path: impl AsRef<Path>) -> Result<(String, Vec<u8>, SignatureInfo), CliError> {
+    let path = path.as_ref();
+    load_signature_with_info(path).map_err(|source| CliError::SignatureFile { path: path.to_path_buf(), source })
+}
+
+pub fn run_cli(cli: Cli) -> Result<Outcome, CliError> {
+    let out = Output { verbose: cli.verbose, quiet: cli.quiet, json: cli.json, text_encoding: &cli.text_encoding };
+    
+    if let Commands::Completions { shell } = &cli.command {
+        let script = completions::script(shell, Cli::command())?;
+        if out.json {
+            out.print_json(&serde_json::json!({ "shell": shell, "script": script }));
+        } else {
+            print!("{}", script);
+        }
+        return Ok(Outcome::Success);
+    }
+    if let Commands::InspectSig { file } = &cli.command {
+        return inspect_signature(&out, file).map(Outcome::verified);
+    }
+    if let Commands::InspectKey { path: Some(path), .. } = &cli.command {
+        let (key_entry, _) = KeyEntry::parse(&read_file(path)?)?;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        return inspect_key(&out, &key_entry, &stem, None).map(Outcome::verified);
+    }
+    
+    let profiles_path = paths::expand(PROFILES_FILE)?;
+    if let Commands::Profile { action } = &cli.command {
+        return run_profile_action(&out, action, &profiles_path).map(|_| Outcome::Success);
+    }
+    
+    let profiles = Profiles::load(&profiles_path)?;
+    let profile = match &cli.profile {
+        Some(name) => Some((name.as_str(), profiles.get(name)?)),
+        None => None,
+    };
+    let (keystore_path, source) = keystore_location(cli.keystore.as_deref(), profile, env_setting(KEYSTORE_ENV), &profiles, &profiles_path)?;
+    verboseln!(out, "Keystore: {} (from {})", keystore_path.display(), source);
+    let default_scheme = default_keygen_scheme(profile.map(|(_, profile)| profile), env_setting(DEFAULT_SCHEME_ENV), &profiles);
+    // Nothing is saved, so the keystore is not opened: it may be unwritable or missing
+    if let Commands::KeyGen { no_save: true, scheme, derive, seed_file, path, show_private, .. } = &cli.command {
+        if cli.backend == YUBIKEY_BACKEND {
+            return Err(usage("--no-save cannot be used with --backend yubikey; the key is generated on the device"));
+        }
+        let scheme = scheme.clone().or(default_scheme).unwrap_or_else(|| "ecdsa".to_string());
+        let keypair = unsaved_keypair(&out, &scheme, *derive, seed_file.as_ref(), path.as_deref())?;
+        print_unsaved_keypair(&out, keypair, *show_private);
+        return Ok(Outcome::Success);
+    }
+    
+    // The signature file carries the key, so the keystore is not opened: there may be none
+    if let Commands::Verify { standalone: true, signature, message, file, no_progress, .. } = &cli.command {
+        let progress = !no_progress && !out.quiet;
+        return verify_standalone(&out, signature, message.clone(), file.clone(), progress).map(Outcome::verified);
+    }
+    
+    let mut keystore = if cli.read_only { KeyStore::new_read_only(&keystore_path)? } else { KeyStore::new(&keystore_path)? };
+    match cli.backend.as_str() {
+        KEYCHAIN_BACKEND => keystore = keystore.with_backend(Box::new(KeychainBackend::new(&keystore_path)?)),
+        YUBIKEY_BACKEND => keystore = keystore.with_backend(Box::new(YubiKeyBackend)),
+        _ => {}
+    }
+    for path in keystore.check_permissions(cli.strict_permissions)? {
+        eprintln!("Warning: {} is accessible to other users; restrict it with chmod go-rwx", path.display());
+    }
+    
+    let audit_log = AuditLog::new(&keystore_path);
+    let hmac_key = cli.audit_hmac_key.as_ref().map(read_passphrase_file).transpose()?;
+    if let Commands::Audit { action } = &cli.command {
+        return run_audit_action(&out, action, &audit_log, hmac_key.as_deref()).map(|_| Outcome::Success);
+    }
+    
+    // Signing and key-management commands are logged whether they succeed or fail
+    let mut record = audit_record(&cli.command);
+    if let Some(record) = &mut record {
+        record.fingerprint = record.key.as_deref().and_then(|key| keystore_fingerprint(&keystore, key));
+    }
+    let mut details = AuditRecord::default();
+    let result = run_command(cli.command, &keystore, &cli.backend, &out, default_scheme, &mut details);
+    
+    if let Some(mut record) = record {
+        // Looked up again as the command may have created or replaced the key
+        record.fingerprint = details.fingerprint
+            .or_else(|| record.key.as_deref().and_then(|key| keystore_fingerprint(&keystore, key)))
+            .or(record.fingerprint);
+        record.message_sha256 = details.message_sha256;
+        record.success = result.is_ok();
+        record.error = result.as_ref().err().map(|e| e.to_string());
+        let logged = keystore.check_writable()
+            .map_err(|e| e.to_string())
+            .and_then(|_| audit_log.append(record, hmac_key.as_deref()).map_err(|e| e.to_string()));
+        if let Err(e) = logged {
+            eprintln!("Warning: the audit log was not written: {}", e);
+        }
+    }
+    result
+}
+
+// An error as --json reports it: {"error": {"kind", "message", "exit_code"}}, with the key
+// or file the error is about when it names one
+pub fn error_json(err: &CliError) -> serde_json::Value {
+    let mut error = serde_json::json!({ "kind": err.kind(), "message": err.to_string(), "exit_code": err.exit_code() });
+    match err {
+        CliError::KeyNotFound { key } | CliError::KeyExpired { key, .. } | CliError::NotExportable { key, .. } => {
+            error["key"] = key.as_str().into()
+        }
+        CliError::Storage(err) => {
+            if let Some(key) = err.key() {
+                error["key"] = key.into();
+            }
+        }
+        CliError::SignatureFile { path, .. } | CliError::Input { path, .. } | CliError::Io { path: Some(path), .. }
+        | CliError::OutputExists { path } => error["path"] = path.display().to_string().into(),
+        CliError::InvalidValue { argument, value, .. } => {
+            error["argument"] = (*argument).into();
+            error["value"] = value.as_str().into();
+        }
+        _ => {}
+    }
+    serde_json::json!({ "error": error })
+}
+
+// Run a command against an open keystore. Details only known while running it, such as
+// the digest of the message signed, are noted in `audit` for the audit log.
+fn run_command(
+    command: Commands,
+    keystore: &KeyStore,
+    backend: &str,
+    out: &Output,
+    default_scheme: Option<String>,
+    audit: &mut AuditRecord,
+) -> Result<Outcome, CliError> {
+    match command {
+        Commands::Profile { .. } | Commands::Audit { .. } | Commands::Completions { .. } | Commands::InspectSig { .. } => {
+            unreachable!("handled before running commands")
+        }
+        
+        Commands::CompleteKeys => {
+            let names: Vec<String> = keystore.list_keys()?.keys.into_iter().map(|key| key.metadata.name).collect();
+            for name in &names {
+                outln!(out, "{}", name);
+            }
+            if out.json {
+                out.print_json(&serde_json::json!({ "keys": names }));
             }
         }
         
-        Commands::Verify { key, signature, message, file } => {
-            let key_entry = keystore.load_key_entry(&key)?;
-            let msg = get_message(message, file)?;
-            let (scheme, sig_bytes) = load_signature(signature)?;
+        Commands::KeyGen { name, scheme, derive, seed_file, path, force, expires_in, slot, .. } => {
+            let name = name.expect("clap requires --name unless --no-save, which is handled before");
+            let on_yubikey = backend == YUBIKEY_BACKEND;
+            if on_yubikey && scheme.as_deref().is_some_and(|scheme| scheme != "p256") {
+                return Err(usage(format!("--backend yubikey only supports p256 keys, not {}", scheme.unwrap_or_default())));
+            }
+            if slot.is_some() && !on_yubikey {
+                return Err(usage("--slot is only used with --backend yubikey"));
+            }
+            let scheme = if on_yubikey { "p256".to_string() } else { scheme.or(default_scheme).unwrap_or_else(|| "ecdsa".to_string()) };
+            let expires_at = expires_in.as_deref().map(parse_expiry).transpose()?;
+            // Fail before anything is generated, on a device in particular
+            keystore.check_writable()?;
             
-            if scheme != key_entry.metadata.scheme {
-                return Err(format!("Signature scheme mismatch: {} vs {}", 
-                                  scheme, key_entry.metadata.scheme).into());
+            if keystore.key_exists(&name) {
+                if !force {
+                    return Err(StorageError::KeyExists(name).into());
+                }
+                match keystore.load_key_entry(&name).and_then(|replaced| Ok((replaced.fingerprint()?, replaced))) {
+                    Ok((fingerprint, replaced)) => 
+                        infoln!(out, "Replacing key {} ({}, fingerprint {})", name, replaced.metadata.scheme, fingerprint),
+                    Err(e @ StorageError::BackendUnavailable(..)) => return Err(e.into()),
+                    Err(e) => infoln!(out, "Replacing unreadable key {}: {}", name, e),
+                }
             }
             
-            match scheme.as_str() {
-                "ECDSA-secp256k1" => {
-                    let public_key_bytes = hex::decode(&key_entry.public_key)
-                        .map_err(|_| StorageError::InvalidFormat)?;
-                    let public_key = ECDSA::deserialize_public_key(&public_key_bytes)?;
-                    
-                    let signature = ECDSA::deserialize_signature(&sig_bytes)?;
-                    let is_valid = ECDSA::verify(&public_key, &msg, &signature)?;
-                    
-                    println!("Signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+            let entry = if on_yubikey {
+                let Some(slot) = slot else {
+                    return Err(usage("--backend yubikey generates the key on the device; pass --slot, e.g. --slot 9c"));
+                };
+                if seed_file.is_some() {
+                    return Err(usage("--seed-file cannot be used with --backend yubikey; the key is generated on the device"));
                 }
-                "BLS12-381-min-pk" => {
-                    let public_key_bytes = hex::decode(&key_entry.public_key)
-                        .map_err(|_| StorageError::InvalidFormat)?;
-                    let public_key = BLS::deserialize_public_key(&public_key_bytes)?;
-                    
-                    let signature = BLS::deserialize_signature(&sig_bytes)?;
-                    let is_valid = BLS::verify(&public_key, &msg, &signature)?;
-                    
-                    println!("Signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+                let public_key = yubikey::generate(&slot, force)?;
+                
+                let mut metadata = KeyMetadata::new(&name, EcdsaP256::name());
+                metadata.backend = Some(YUBIKEY_BACKEND.to_string());
+                metadata.piv_slot = Some(slot.clone());
+                metadata.expires_at = expires_at;
+                let entry = KeyEntry {
+                    version: CURRENT_VERSION,
+                    metadata,
+                    private_key: String::new(),
+                    public_key: hex::encode(&public_key),
+                };
+                keystore.save_key_entry(&entry)?;
+                
+                infoln!(out, "Generated {} key pair {} in YubiKey PIV slot {}", EcdsaP256::name(), name, slot);
+                infoln!(out, "Public key: {}", entry.public_key);
+                entry
+            } else if derive {
+                if scheme != "bls" {
+                    return Err(usage(format!("--derive is only supported for bls keys, not {}", scheme)));
                 }
-                _ => {
-                    return Err(format!("Unsupported signature scheme: {}", scheme).into());
+                
+                let (Some(seed_file), Some(path)) = (seed_file, path) else {
+                    return Err(usage("--derive needs both --seed-file and --path"));
+                };
+                let seed = read_seed_file(&seed_file)?;
+                
+                let (private_key, public_key) = BLS::derive_eip2333(&seed, &path)?;
+                
+                let mut metadata = KeyMetadata::new(&name, BLS::name());
+                metadata.derivation_path = Some(path.clone());
+                metadata.expires_at = expires_at;
+                let entry = KeyEntry {
+                    version: CURRENT_VERSION,
+                    metadata,
+                    private_key: hex::encode(BLS::serialize_private_key(&private_key)?),
+                    public_key: hex::encode(BLS::serialize_public_key(&public_key)?),
+                };
+                keystore.save_key_entry(&entry)?;
+                
+                infoln!(out, "Derived BLS key pair {} at {}", name, path);
+                infoln!(out, "Public key: {}", entry.public_key);
+                entry
+            } else if let Some(seed_file) = seed_file {
+                let (scheme_name, private_key, public_key) = seeded_keypair(&seed_file, &scheme)?;
+                
+                let mut metadata = KeyMetadata::new(&name, scheme_name);
+                metadata.seed_derived = true;
+                metadata.expires_at = expires_at;
+                let entry = KeyEntry {
+                    version: CURRENT_VERSION,
+                    metadata,
+                    private_key: hex::encode(private_key),
+                    public_key: hex::encode(public_key),
+                };
+                keystore.save_key_entry(&entry)?;
+                
+                infoln!(out, "Generated {} key pair {} from seed file {:?}", scheme_name, name, seed_file);
+                infoln!(out, "Public key: {}", entry.public_key);
+                entry
+            } else {
+                let Some(registered) = registry::by_keygen_name(&scheme) else {
+                    return Err(StorageError::UnsupportedScheme(scheme).into());
+                };
+                if registered.name() == RsaPss::name() {
+                    infoln!(out, "Generating {}-bit RSA key, this may take a while...", RSA_KEY_BITS);
                 }
+                let (private_key, public_key) = registered.generate_keypair()?;
+                let mut metadata = KeyMetadata::new(&name, registered.name());
+                metadata.expires_at = expires_at;
+                let entry = KeyEntry {
+                    version: CURRENT_VERSION,
+                    metadata,
+                    private_key: hex::encode(private_key),
+                    public_key: hex::encode(public_key),
+                };
+                keystore.save_key_entry(&entry)?;
+                infoln!(out, "Generated {} key pair: {}", registered.label(), name);
+                entry
+            };
+            if out.json {
+                out.print_json(&key_json(out, &entry)?);
             }
         }
         
-        Commands::Aggregate { signatures, output } => {
-            let mut bls_signatures = Vec::new();
-            
-            for sig_path in signatures {
-                let (scheme, sig_bytes) = load_signature(sig_path)?;
-                
-                if scheme != "BLS12-381-min-pk" {
-                    return Err(format!("Can only aggregate BLS signatures, found: {}", scheme).into());
+        Commands::Reindex => {
+            keystore.check_writable()?;
+            let listing = keystore.reindex()?;
+            for (file_name, error) in &listing.unreadable {
+                eprintln!("Warning: skipping unreadable key file {}: {}", file_name, error);
+            }
+            infoln!(out, "Indexed {} keys", listing.keys.len());
+            if out.json {
+                let unreadable: Vec<serde_json::Value> = listing.unreadable.iter()
+                    .map(|(file_name, error)| serde_json::json!({ "file": file_name, "error": error }))
+                    .collect();
+                out.print_json(&serde_json::json!({ "indexed": listing.keys.len(), "unreadable": unreadable }));
+            }
+        }
+        
+        Commands::Migrate => {
+            keystore.check_writable()?;
+            let (mut migrated, mut failed) = (Vec::new(), Vec::new());
+            for name in keystore.key_names()? {
+                match keystore.migrate_key(&name) {
+                    Ok(Some(from)) => {
+                        infoln!(out, "- {}: version {} -> {}", name, from, CURRENT_VERSION);
+                        migrated.push(name);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        outln!(out, "- {}: FAILED ({})", name, e);
+                        failed.push(serde_json::json!({ "key": name, "error": e.to_string() }));
+                    }
                 }
-                
-                let signature = BLS::deserialize_signature(&sig_bytes)?;
-                bls_signatures.push(signature);
             }
             
-            use crate::crypto::bls::BLSSignature;
-            let aggregated = BLSSignature::aggregate(&bls_signatures)?;
+            infoln!(out, "Migrated {} keys to version {}", migrated.len(), CURRENT_VERSION);
+            if out.json {
+                out.print_json(&serde_json::json!({ "version": CURRENT_VERSION, "migrated": migrated, "failed": failed }));
+            }
+            if !failed.is_empty() {
+                return Err(CliError::Migration { failed: failed.len() });
+            }
+        }
+        
+        Commands::ListKeys { addresses, tag, long, format } => {
+            if out.json && format.as_deref().is_some_and(|format| format != "json") {
+                return Err(usage(format!("--json lists keys as JSON; it cannot be used with --format {}", format.unwrap_or_default())));
+            }
+            let listing = keystore.list_keys()?;
+            for (file_name, error) in &listing.unreadable {
+                eprintln!("Warning: skipping unreadable key file {}: {}", file_name, error);
+            }
+            let mut keys = listing.keys;
+            if let Some(tag) = &tag {
+                keys.retain(|key| key.metadata.tags.contains(tag));
+            }
+            // Keys come sorted by name, and every format lists them in that order
+            match format.as_deref() {
+                Some("table") => {
+                    outln!(out, "{}", format_table(&key_table(keystore, &keys, addresses, long, format_local_timestamp)?).trim_end_matches('\n'));
+                    return Ok(Outcome::Success);
+                }
+                Some("csv") => {
+                    outln!(out, "{}", format_csv(&key_table(keystore, &keys, addresses, long, format_timestamp)?).trim_end_matches('\n'));
+                    return Ok(Outcome::Success);
+                }
+                _ => {}
+            }
+            if out.json || format.is_some() {
+                // Full metadata, with the fingerprint and, on request, the Ethereum address
+                let mut summaries = Vec::new();
+                for summary in &keys {
+                    let mut value = serde_json::to_value(summary)?;
+                    if addresses && summary.metadata.scheme == "ECDSA-secp256k1" {
+                        value["address"] = ethereum_address_of(&keystore.read_entry(&summary.metadata.name)?)?.into();
+                    }
+                    summaries.push(value);
+                }
+                // --format json is the bare list; --json wraps it like every other report
+                if out.json {
+                    out.print_json(&serde_json::json!({ "keys": summaries }));
+                } else {
+                    out.print_json(&summaries.into());
+                }
+                return Ok(Outcome::Success);
+            }
+            infoln!(out, "Found {} keys:", keys.len());
+            for KeySummary { metadata: key, fingerprint } in keys {
+                let mut details = format!("{}, fingerprint: {}, created: {}", key.scheme, fingerprint, key.created_at);
+                if !key.tags.is_empty() {
+                    details.push_str(&format!(", tags: {}", key.tags.join(",")));
+                }
+                if let Some(expires_at) = key.expires_at {
+                    let state = if key.is_expired() { "EXPIRED" } else { "expires" };
+                    details.push_str(&format!(", {}: {}", state, format_timestamp(expires_at)));
+                }
+                if let Some(path) = &key.derivation_path {
+                    details.push_str(&format!(", path: {}", path));
+                }
+                if key.seed_derived {
+                    details.push_str(", seed-derived");
+                }
+                if let Some(backend) = &key.backend {
+                    details.push_str(&format!(", backend: {}", backend));
+                }
+                if let Some(slot) = &key.piv_slot {
+                    details.push_str(&format!(", PIV slot: {}", slot));
+                }
+                if long {
+                    match key.last_used_at {
+                        Some(last_used_at) => details.push_str(&format!(", last used: {}, signatures: {}", 
+                                                                         format_timestamp(last_used_at), key.use_count)),
+                        None => details.push_str(", never used"),
+                    }
+                }
+                if addresses && key.scheme == "ECDSA-secp256k1" {
+                    details.push_str(&format!(", address: {}", ethereum_address_of(&keystore.read_entry(&key.name)?)?));
+                }
+                outln!(out, "- {} ({})", key.name, details);
+            }
+        }
+        
+        Commands::SetExpiry { key, expires_in, never } => {
+            let expires_at = if never { None } else { expires_in.as_deref().map(parse_expiry).transpose()? };
+            keystore.set_expiry(&key, expires_at)?;
+            match expires_at {
+                Some(expires_at) => infoln!(out, "Key {} expires at {}", key, format_timestamp(expires_at)),
+                None => infoln!(out, "Key {} no longer expires", key),
+            }
+            if out.json {
+                out.print_json(&serde_json::json!({ "key": key, "expires_at": expires_at }));
+            }
+        }
+        
+        Commands::Tag { key, add, remove } => {
+            if let Some(tag) = add.iter().chain(&remove).find(|tag| tag.trim().is_empty() || tag.contains(char::is_whitespace)) {
+                return Err(CliError::InvalidValue { argument: "tag", value: tag.clone(), expected: "a non-empty tag without whitespace" });
+            }
             
-            let agg_bytes = BLS::serialize_signature(&aggregated)?;
-            save_signature(&output, "BLS12-381-min-pk-aggregated", &agg_bytes)?;
-            println!("Aggregated signature saved to {:?}", output);
+            let tags = keystore.update_tags(&key, &add, &remove)?;
+            if out.json {
+                out.print_json(&serde_json::json!({ "key": key, "tags": tags }));
+            } else if tags.is_empty() {
+                outln!(out, "Key {} has no tags", key);
+            } else {
+                outln!(out, "Key {} tags: {}", key, tags.join(","));
+            }
         }
         
-        Commands::VerifyAggregate { keys, signature, message, file } => {
-            let _msg = get_message(message, file)?;
-            let (scheme, _sig_bytes) = load_signature(signature)?;
+        Commands::DeleteKey { key, yes, shred } => {
+            let key_entry = keystore.load_key_entry(&key)?;
+            infoln!(out, "Key: {}", key_entry.metadata.name);
+            infoln!(out, "Scheme: {}", key_entry.metadata.scheme);
+            infoln!(out, "Fingerprint: {}", key_entry.fingerprint()?);
             
-            if !scheme.starts_with("BLS12-381-min-pk") {
-                return Err(format!("Expected BLS signature, found: {}", scheme).into());
+            // The prompt goes to stderr, and is not made at all when stdout is for scripts
+            if !yes && out.json {
+                return Err(usage("delete-key --json does not ask for confirmation; pass --yes"));
+            }
+            if !yes {
+                eprint!("Delete key {}? This cannot be undone [y/N]: ", key);
+                std::io::stderr().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                    infoln!(out, "Aborted; key {} was not deleted", key);
+                    return Ok(Outcome::Success);
+                }
             }
             
-            let mut public_keys = Vec::new();
+            if shred {
+                keystore.shred_key(&key)?;
+                infoln!(out, "Shredded and deleted key {}", key);
+            } else {
+                keystore.delete_key(&key)?;
+                infoln!(out, "Deleted key {}", key);
+            }
+            if out.json {
+                out.print_json(&serde_json::json!({ "deleted": key, "fingerprint": key_entry.fingerprint()?, "shredded": shred }));
+            }
+        }
+        
+        Commands::RenameKey { from, to, force } => {
+            keystore.rename_key(&from, &to, force)?;
+            infoln!(out, "Renamed key {} to {}", from, to);
+            if out.json {
+                out.print_json(&serde_json::json!({ "from": from, "to": to }));
+            }
+        }
+        
+        Commands::ExportKey { key, output, passphrase_file, format, kdf, force } => {
+            check_overwrite(&output, force)?;
+            if let Some(slot) = keystore.read_entry(&key)?.metadata.piv_slot {
+                return Err(CliError::NotExportable { key, held: format!("hardware-backed (YubiKey PIV slot {})", slot) });
+            }
+            let key_entry = keystore.load_key_entry(&key)?;
+            if key_entry.private_key.is_empty() {
+                return Err(CliError::NotExportable { key, held: format!("a public-only {} key", key_entry.metadata.scheme) });
+            }
+            if kdf.is_some() && format == "sig-tool" {
+                return Err(usage("--kdf is only used with --format eip2335 or web3"));
+            }
+            let kdf = match kdf.as_deref() {
+                Some("pbkdf2") => eip2335::Kdf::Pbkdf2,
+                _ => eip2335::Kdf::Scrypt,
+            };
+            let passphrase = get_passphrase(passphrase_file.as_ref(), true)?;
+            let fingerprint = key_entry.fingerprint()?;
+            let report = serde_json::json!({
+                "key": key, "scheme": key_entry.metadata.scheme, "fingerprint": fingerprint, "format": format, "output": output,
+            });
             
-            for key_name in keys {
-                let key_entry = keystore.load_key_entry(&key_name)?;
-                
-                if key_entry.metadata.scheme != "BLS12-381-min-pk" {
-                    return Err(format!("Key {} is not a BLS key", key_name).into());
+            if format == "eip2335" {
+                if key_entry.metadata.scheme != BLS::name() {
+                    return Err(usage(format!("--format eip2335 is only supported for {} keys, not {}", 
+                                      BLS::name(), key_entry.metadata.scheme)));
                 }
+                let passphrase = String::from_utf8(passphrase).map_err(|_| usage("Passphrase is not valid UTF-8"))?;
+                let private_key = hex::decode(&key_entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?;
+                let public_key = hex::decode(&key_entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                let path = key_entry.metadata.derivation_path.as_deref().unwrap_or_default();
                 
-                let pk_bytes = hex::decode(&key_entry.public_key)
-                    .map_err(|_| StorageError::InvalidFormat)?;
-                let public_key = BLS::deserialize_public_key(&pk_bytes)?;
-                
-                public_keys.push(public_key);
+                let exported = Keystore::encrypt(&private_key, &public_key, path, &key, &passphrase, kdf)?;
+                write_private(&output, &serde_json::to_vec_pretty(&exported)?)?;
+                infoln!(out, "Exported key {} ({}, fingerprint {}) to EIP-2335 keystore {:?}", 
+                         key, key_entry.metadata.scheme, fingerprint, output);
+                if out.json {
+                    out.print_json(&report);
+                }
+                return Ok(Outcome::Success);
             }
             
-            // For aggregated signature verification, we'd normally need to implement a specialized function
-            // that verifies the aggregated signature against all public keys and messages
-            // This is a simplified version that assumes all signatures were made on the same message
+            if format == "web3" {
+                if key_entry.metadata.scheme != ECDSA::name() {
+                    return Err(usage(format!("--format web3 is only supported for {} keys, not {}", 
+                                      ECDSA::name(), key_entry.metadata.scheme)));
+                }
+                let private_key = hex::decode(&key_entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?;
+                let public_key = hex::decode(&key_entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                let address = ECDSA::ethereum_address(&ECDSA::deserialize_public_key(&public_key)?);
+                
+                let exported = Web3Keystore::encrypt(&private_key, &address, &passphrase, kdf)?;
+                write_private(&output, &serde_json::to_vec_pretty(&exported)?)?;
+                infoln!(out, "Exported key {} ({}, fingerprint {}) to web3 keystore {:?}", 
+                         key, key_entry.metadata.scheme, fingerprint, output);
+                infoln!(out, "Ethereum address: {}", address);
+                if out.json {
+                    let mut report = report;
+                    report["address"] = address.into();
+                    out.print_json(&report);
+                }
+                return Ok(Outcome::Success);
+            }
             
-            println!("Aggregated signature verification not fully implemented in this example.");
-            println!("For a complete implementation, you'd need a specialized verification function.");
+            let export = KeyExport::seal(key_entry, &passphrase)?;
+            save_key_export(&output, &export)?;
+            infoln!(out, "Exported key {} ({}, fingerprint {}) to {:?}", key, export.metadata.scheme, fingerprint, output);
+            if out.json {
+                out.print_json(&report);
+            }
         }
-    }
-    
-    Ok(())
+        
+        Commands::ImportKey { input, passphrase_file, name, format } if format == "eip2335" => {
+            let imported: Keystore = serde_json::from_slice(&read_file(&input)?)?;
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let stem = input.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                    parse_key_name(&stem).map_err(|e| usage(format!("{}; pass --name", e)))?
+                }
+            };
+            if keystore.key_exists(&name) {
+                return Err(StorageError::KeyExists(name).into());
+            }
+            let passphrase = get_passphrase(passphrase_file.as_ref(), false)?;
+            let passphrase = String::from_utf8(passphrase).map_err(|_| usage("Passphrase is not valid UTF-8"))?;
+            
+            // The checksum rejects a wrong passphrase before anything is written
+            let secret = imported.decrypt(&passphrase)?;
+            let private_key = BLS::deserialize_private_key(&secret)?;
+            let public_key = BLS::serialize_public_key(&BLS::public_key(&private_key))?;
+            if !imported.pubkey.is_empty() && imported.pubkey.to_lowercase() != hex::encode(&public_key) {
+                return Err(CliError::KeyMismatch(format!("Keystore pubkey {} does not match its secret key (public key {})", 
+                                                         imported.pubkey, hex::encode(&public_key))));
+            }
+            
+            let mut metadata = KeyMetadata::new(&name, BLS::name());
+            metadata.derivation_path = (!imported.path.is_empty()).then(|| imported.path.clone());
+            let key_entry = KeyEntry {
+                version: CURRENT_VERSION,
+                metadata,
+                private_key: hex::encode(&secret),
+                public_key: hex::encode(&public_key),
+            };
+            keystore.save_key_entry(&key_entry)?;
+            infoln!(out, "Imported key {} ({}, fingerprint {}) from EIP-2335 keystore", name, BLS::name(), key_entry.fingerprint()?);
+            infoln!(out, "Public key: {}", key_entry.public_key);
+            if out.json {
+                out.print_json(&key_json(out, &key_entry)?);
+            }
+        }
+        
+        Commands::ImportKey { input, passphrase_file, name, format } if format == "web3" => {
+            let imported: Web3Keystore = serde_json::from_slice(&read_file(&input)?)?;
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let stem = input.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                    parse_key_name(&stem).map_err(|e| usage(format!("{}; pass --name", e)))?
+                }
+            };
+            if keystore.key_exists(&name) {
+                return Err(StorageError::KeyExists(name).into());
+            }
+            let passphrase = get_passphrase(passphrase_file.as_ref(), false)?;
+            
+            // The MAC rejects a wrong passphrase before anything is written
+            let secret = imported.decrypt(&passphrase)?;
+            let private_key = ECDSA::deserialize_private_key(&secret)?;
+            let public_key = ECDSA::serialize_public_key(private_key.verifying_key())?;
+            let address = ECDSA::ethereum_address(private_key.verifying_key());
+            if !imported.address.is_empty() 
+                && imported.address.trim_start_matches("0x").to_lowercase() != address[2..].to_lowercase() {
+                return Err(CliError::KeyMismatch(format!("Keystore address {} does not match its secret key (address {})", 
+                                                         imported.address, address)));
+            }
+            
+            let mut metadata = KeyMetadata::new(&name, ECDSA::name());
+            metadata.address = Some(address.clone());
+            let key_entry = KeyEntry {
+                version: CURRENT_VERSION,
+                metadata,
+                private_key: hex::encode(&secret),
+                public_key: hex::encode(&public_key),
+            };
+            keystore.save_key_entry(&key_entry)?;
+            infoln!(out, "Imported key {} ({}, fingerprint {}) from web3 keystore", name, ECDSA::name(), key_entry.fingerprint()?);
+            infoln!(out, "Ethereum address: {}", address);
+            if out.json {
+                let mut imported = key_json(out, &key_entry)?;
+                imported["address"] = address.into();
+                out.print_json(&imported);
+            }
+        }
+        
+        Commands::ImportKey { input, passphrase_file, name, .. } => {
+            let export = load_key_export(&input)?;
+            let name = name.unwrap_or_else(|| export.metadata.name.clone());
+            if keystore.key_exists(&name) {
+                return Err(StorageError::KeyExists(name).into());
+            }
+            let passphrase = get_passphrase(passphrase_file.as_ref(), false)?;
+            
+            // Everything is decrypted and checked before the keystore is touched
+            let mut key_entry = export.open(&passphrase)?;
+            let private_key = hex::decode(&key_entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            let public_key = hex::decode(&key_entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+            check_key_pair(&key_entry.metadata.scheme, &private_key, &public_key)?;
+            
+            key_entry.metadata.name = name.clone();
+            keystore.save_key_entry(&key_entry)?;
+            infoln!(out, "Imported key {} ({}, fingerprint {})", name, key_entry.metadata.scheme, key_entry.fingerprint()?);
+            if out.json {
+                out.print_json(&key_json(out, &key_entry)?);
+            }
+        }
+        
+        Commands::ImportPublicKey { input, name } => {
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let stem = input.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                    parse_key_name(&stem).map_err(|e| usage(format!("{}; pass --name", e)))?
+                }
+            };
+            if keystore.key_exists(&name) {
+                return Err(StorageError::KeyExists(name).into());
+            }
+            let contents = read_text_file(&input)?;
+            let line = contents.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+            let (_, blob, comment) = ssh::parse_public_key(line)?;
+            let public_key = ssh::ed25519_public_key(&blob)?;
+            ED25519::deserialize_public_key(&public_key)?;
+            
+            let key_entry = KeyEntry {
+                version: CURRENT_VERSION,
+                metadata: KeyMetadata::new(&name, ED25519::name()),
+                private_key: String::new(),
+                public_key: hex::encode(&public_key),
+            };
+            keystore.save_key_entry(&key_entry)?;
+            infoln!(out, "Imported public key {} ({}, fingerprint {}) from {} {}", 
+                     name, ED25519::name(), key_entry.fingerprint()?, ssh::fingerprint(&blob), comment);
+            if out.json {
+                out.print_json(&key_json(out, &key_entry)?);
+            }
+        }
+        
+        Commands::Backup { output, passphrase_file, force } => {
+            check_overwrite(&output, force)?;
+            let passphrase = get_passphrase(passphrase_file.as_ref(), true)?;
+            let files = keystore.backup_files()?;
+            let key_count = files.iter().filter(|file| file.name.ends_with(".json")).count();
+            let in_keychain = keystore.list_keys()?.keys.iter()
+                .filter(|key| key.metadata.backend.as_deref() == Some(KEYCHAIN_BACKEND))
+                .count();
+            if in_keychain > 0 {
+                eprintln!("Warning: the private keys of {} keys are kept in the OS keychain and are not in the backup", in_keychain);
+            }
+            
+            save_backup(&output, &BackupArchive::seal(&files, &passphrase)?)?;
+            infoln!(out, "Backed up {} keys ({} files) to {:?}", key_count, files.len(), output);
+            if out.json {
+                out.print_json(&serde_json::json!({ "output": output, "keys": key_count, "files": files.len() }));
+            }
+        }
+        
+        Commands::Restore { input, passphrase_file, overwrite } => {
+            let passphrase = get_passphrase(passphrase_file.as_ref(), false)?;
+            // Nothing is written unless the whole archive authenticates and parses
+            let files = load_backup(&input)?.open(&passphrase)?;
+            
+            let (mut restored, mut skipped) = (Vec::new(), Vec::new());
+            infoln!(out, "Restoring {} files from {:?}:", files.len(), input);
+            for file in &files {
+                let name = file.name.strip_suffix(".json").unwrap_or(&file.name);
+                let exists = keystore.file_exists(&file.name);
+                if exists && !overwrite {
+                    infoln!(out, "- {}: skipped (already exists)", name);
+                    skipped.push(name);
+                    continue;
+                }
+                
+                keystore.restore_file(file)?;
+                infoln!(out, "- {}: {}", name, if exists { "overwritten" } else { "restored" });
+                restored.push(name);
+            }
+            infoln!(out, "Restored {}, skipped {}", restored.len(), skipped.len());
+            if out.json {
+                out.print_json(&serde_json::json!({ "input": input, "restored": restored, "skipped": skipped }));
+            }
+        }
+        
+        Commands::InspectKey { key, .. } => {
+            let key = key.expect("inspect-key needs --key or --path");
+            let key_entry = keystore.read_entry(&key)?;
+            return inspect_key(out, &key_entry, &key, Some(keystore)).map(Outcome::verified);
+        }
+        
+        Commands::ShowKey { key, encoding, format, out: out_path, force } => {
+            if let Some(out_path) = &out_path {
+                check_overwrite(out_path, force)?;
+            }
+            let key_entry = keystore.read_entry(&key)?;
+            let scheme = key_entry.metadata.scheme.as_str();
+            let encoding = point_encoding(scheme, encoding.as_deref())?;
+            let pk_bytes = hex::decode(&key_entry.public_key)
+                .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+            
+            // The keystore holds compressed BLS points; re-encode on request
+            let public_key = match scheme {
+                "BLS12-381-min-pk" | "BLS12-381-min-pk-aggregated" => {
+                    BLS::encode_public_key(&BLS::deserialize_public_key(&pk_bytes)?, encoding)
+                }
+                "BLS12-381-min-sig" | "BLS12-381-min-sig-aggregated" => {
+                    BLSMinSig::encode_public_key(&BLSMinSig::deserialize_public_key(&pk_bytes)?, encoding)
+                }
+                _ => pk_bytes,
+            };
+            let pem = match format.as_deref() {
+                Some("pem") => Some(pem::public_key_pem(scheme, &public_key)?),
+                _ => None,
+            };
+            
+            outln!(out, "Key: {}", key_entry.metadata.name);
+            outln!(out, "Scheme: {}", scheme);
+            outln!(out, "Fingerprint: {}", key_entry.fingerprint()?);
+            outln!(out, "Created: {}", key_entry.metadata.created_at);
+            match (&key_entry.metadata.piv_slot, &key_entry.metadata.backend) {
+                (Some(slot), _) => outln!(out, "Private key: on a YubiKey, PIV slot {} (hardware-backed)", slot),
+                (None, Some(backend)) => outln!(out, "Private key: kept in the {} backend", backend),
+                (None, None) => {}
+            }
+            if let Some(expires_at) = key_entry.metadata.expires_at {
+                let state = if key_entry.metadata.is_expired() { " (expired)" } else { "" };
+                outln!(out, "Expires: {}{}", format_timestamp(expires_at), state);
+            }
+            match key_entry.metadata.last_used_at {
+                Some(last_used_at) => outln!(out, "Last used: {} ({} signatures)", format_timestamp(last_used_at), key_entry.metadata.use_count),
+                None => outln!(out, "Last used: never"),
+            }
+            let public_key_text = match (&pem, format.as_deref()) {
+                (Some(pem), _) => pem.clone(),
+                (None, Some(format)) => encode_text(format, &public_key),
+                (None, None) => encode_text(out.text_encoding, &public_key),
+            };
+            if pem.is_some() {
+                outln!(out, "Public key:\n{}", public_key_text.trim_end());
+            } else {
+                outln!(out, "Public key: {}", public_key_text);
+            }
+            if is_bls_scheme(scheme.trim_end_matches("-aggregated")) {
+                let form = if encoding == PointEncoding::Compressed { "compressed" } else { "uncompressed" };
+                outln!(out, "Public key length: {} bytes ({})", public_key.len(), form);
+            }
+            
+            if scheme == "ECDSA-secp256k1" {
+                let public_key_bytes = hex::decode(&key_entry.public_key)
+                    .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                let public_key = ECDSA::deserialize_public_key(&public_key_bytes)?;
+                outln!(out, "Uncompressed public key: {}", hex::encode(public_key.to_encoded_point(false).as_bytes()));
+                outln!(out, "Ethereum address: {}", ECDSA::ethereum_address(&public_key));
+            }
+            
+            if let Some(out_path) = &out_path {
+                match &pem {
+                    Some(pem) => write_atomic(out_path, pem.as_bytes())?,
+                    None => write_atomic(out_path, &public_key)?,
+                }
+                infoln!(out, "Public key written to {:?}", out_path);
+            }
+            if out.json {
+                let mut value = serde_json::to_value(&key_entry.metadata)?;
+                value["fingerprint"] = key_entry.fingerprint()?.into();
+                value["public_key"] = public_key_text.into();
+                if scheme == "ECDSA-secp256k1" {
+                    value["ethereum_address"] = ethereum_address_of(&key_entry)?.into();
+                }
+                out.print_json(&value);
+            }
+        }
+        
+        Commands::Sign { key, private_key, scheme, message, file, digest, output, prehash, recoverable, eip191, bitcoin_message, bip322, address_type, dst, ciphersuite, aug, encoding, extra_entropy, sig_encoding, allow_expired, ssh_agent, ssh_key, no_track, raw, embed, max_embed_size, signed_timestamp, no_progress, force } => {
+            if let Some(output) = &output {
+                check_overwrite(output, force)?;
+            }
+            // Raw bytes on stdout are the only thing printed there
+            let raw_stdout = raw && output.is_none();
+            let progress = !no_progress && !out.quiet;
+            if raw_stdout && out.json {
+                return Err(usage("--raw without --output writes the signature to stdout, where --json reports"));
+            }
+            if ssh_agent {
+                if digest.is_some() || prehash.is_some() {
+                    return Err(usage("--digest and --prehash cannot be used with --ssh-agent"));
+                }
+                let msg = read_message(message, file, progress)?;
+                let embedded = embedded_message(embed, &msg, max_embed_size)?;
+                verboseln!(out, "Signing {} bytes with ssh-agent", msg.len());
+                let query = ssh_key.unwrap_or_default();
+                let mut agent = Agent::connect()?;
+                let identity = agent.find(&query)?;
+                if identity.key_type() != SSH_ED25519 {
+                    return Err(usage(format!("Agent key {} is an {} key; only {} agent keys can sign", 
+                                            query, identity.key_type(), SSH_ED25519)));
+                }
+                let public_key = ssh::ed25519_public_key(&identity.blob)?;
+                let (_, sig_bytes) = agent.sign(&identity, &msg)?;
+                
+                // The agent signs plain Ed25519; check it before recording the signature
+                let verifying_key = ED25519::deserialize_public_key(&public_key)?;
+                if !ED25519::verify(&verifying_key, &msg, &ED25519::deserialize_signature(&sig_bytes)?)? {
+                    return Err(SshError::Protocol(format!("invalid signature for {}", identity.fingerprint())).into());
+                }
+                
+                if !raw_stdout {
+                    infoln!(out, "Signed with ssh-agent key {} ({})", identity.fingerprint(), identity.comment);
+                }
+                let key_fingerprint = Some(fingerprint_of(ED25519::name(), &public_key));
+                let message_sha256 = Some(hex::encode(Sha256::digest(&msg)));
+                audit.fingerprint = key_fingerprint.clone();
+                audit.message_sha256 = message_sha256.clone();
+                let info = SignatureInfo {
+                    key_fingerprint, public_key: Some(hex::encode(&public_key)), message_sha256, message: embedded,
+                    ..Default::default()
+                };
+                emit_signature(output, raw, out, SSH_AGENT_SCHEME, &sig_bytes, &info)?;
+                return Ok(Outcome::Success);
+            }
+            // Keys given on the command line or in the environment are not in the keystore
+            let in_keystore = private_key.is_none() && !key.as_deref().unwrap_or_default().starts_with(ENV_KEY_PREFIX);
+            let key_entry = match (key, private_key) {
+                (_, Some(private_key)) => {
+                    let Some(scheme) = scheme else {
+                        return Err(usage("--scheme is required to sign with --private-key"));
+                    };
+                    private_key_entry(&private_key, &scheme)?
+                }
+                (Some(key), None) => match key.strip_prefix(ENV_KEY_PREFIX) {
+                    Some(var) => {
+                        let Some(scheme) = scheme else {
+                            return Err(usage(format!("--scheme is required to sign with {}", key)));
+                        };
+                        env_key_entry(var, &scheme)?
+                    }
+                    None if scheme.is_some() => {
+                        return Err(usage(format!("--scheme is only used with {}VAR keys and --private-key; keystore keys record their scheme", ENV_KEY_PREFIX)));
+                    }
+                    None => keystore.load_key_entry(&key)?,
+                },
+                (None, None) => return Err(usage("--key or --private-key is required")),
+            };
+            let key = key_entry.metadata.name.clone();
+            if key_entry.metadata.is_expired() && !allow_expired {
+                let expires_at = key_entry.metadata.expires_at.unwrap_or_default();
+                return Err(CliError::KeyExpired { key, expires_at, overridable: true });
+            }
+            let prehashed = digest.is_some();
+            if prehashed && key_entry.metadata.scheme != "ECDSA-secp256k1" && !is_bls_scheme(&key_entry.metadata.scheme) {
+                return Err(usage(format!("--digest is only supported for ECDSA-secp256k1 and BLS keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            // BLS and schemes without a prehash signing API sign the digest bytes as the message
+            let (msg, message_sha256) = match (digest, &prehash) {
+                // A --digest is the SHA-256 of the message already
+                (Some(digest), _) => {
+                    let digest = parse_digest(&digest)?;
+                    (digest.clone(), digest)
+                }
+                (None, Some(algorithm)) => {
+                    let ([digest, message_sha256], len) = hash_message(message, file, [algorithm.as_str(), "sha256"], progress)?;
+                    verboseln!(out, "Hashed {} bytes with {} as they were read", len, algorithm);
+                    (digest, message_sha256)
+                }
+                (None, None) => {
+                    let msg = read_message(message, file, progress)?;
+                    let message_sha256 = Sha256::digest(&msg).to_vec();
+                    (msg, message_sha256)
+                }
+            };
+            
+            if key_entry.private_key.is_empty() && key_entry.metadata.piv_slot.is_none() {
+                return Err(StorageError::PublicOnly(key).into());
+            }
+            if recoverable && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--recoverable is only supported for ECDSA-secp256k1 keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            if sig_encoding.is_some() && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--sig-encoding is only supported for ECDSA-secp256k1 keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            if eip191 && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--eip191 is only supported for ECDSA-secp256k1 keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            if extra_entropy.is_some() && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--extra-entropy is only supported for ECDSA-secp256k1 keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            let extra_entropy = extra_entropy.as_deref().map(parse_extra_entropy).transpose()?;
+            if bitcoin_message && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--bitcoin-message is only supported for ECDSA-secp256k1 keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            // BIP-322 signs with ECDSA for P2WPKH and with Schnorr for P2TR
+            let address_type = match (key_entry.metadata.scheme.as_str(), address_type.as_deref()) {
+                ("ECDSA-secp256k1", None | Some("p2wpkh")) => AddressType::P2wpkh,
+                ("Schnorr-secp256k1-BIP340" | TWEAKED_SCHEME, None | Some("p2tr")) => AddressType::P2tr,
+                (scheme, _) if bip322 => {
+                    return Err(usage(format!("--bip322 signs P2WPKH with ECDSA-secp256k1 keys and P2TR with Schnorr keys, not {} with {} key", 
+                                      address_type.as_deref().unwrap_or("an address"), scheme)));
+                }
+                _ => AddressType::P2wpkh,
+            };
+            if (dst.is_some() || ciphersuite.is_some()) && !is_bls_scheme(&key_entry.metadata.scheme) {
+                return Err(usage(format!("--dst and --ciphersuite are only supported for BLS keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            if aug && key_entry.metadata.scheme != "BLS12-381-min-pk" {
+                return Err(usage(format!("--aug is only supported for BLS12-381-min-pk keys, not {}", 
+                                  key_entry.metadata.scheme)));
+            }
+            let encoding = point_encoding(&key_entry.metadata.scheme, encoding.as_deref())?;
+            let dst = preset_dst(&key_entry.metadata.scheme, dst, ciphersuite.as_deref())?;
+            let embedded = embedded_message(embed, &msg, max_embed_size)?;
+            audit.fingerprint = Some(key_entry.fingerprint()?);
+            let message_sha256 = Some(hex::encode(message_sha256));
+            audit.message_sha256 = message_sha256.clone();
+            verboseln!(out, "Signing {} bytes with key {} ({})", msg.len(), key, key_entry.metadata.scheme);
+            let started = Instant::now();
+            // The file records a signed timestamp for verify to rebuild the payload from
+            let timestamp = if signed_timestamp {
+                Some(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+            } else {
+                None
+            };
+            let msg = match timestamp {
+                Some(timestamp) => timestamped_message(timestamp, &msg),
+                None => msg,
+            };
+            
+            let params = SignParams {
+                prehash: prehash.clone(), prehashed, recoverable, eip191, bitcoin_message, bip322, address_type,
+                dst, ciphersuite, aug, encoding, extra_entropy, sig_encoding,
+            };
+            let (sig_scheme, sig_bytes, info) = sign_message(&key_entry, msg, params)?;
+            // The public key and digest let a verifier holding only the file know what to fetch
+            let info = SignatureInfo {
+                message: embedded, prehash, timestamp, signed_timestamp,
+                public_key: Some(key_entry.public_key.clone()), message_sha256,
+                ..info
+            };
+            let wallet_format = sig_scheme == BITCOIN_MESSAGE_SCHEME || sig_scheme == BIP322_SCHEME;
+            if output.is_some() || out.json || !wallet_format {
+                emit_signature(output, raw, out, &sig_scheme, &sig_bytes, &info)?;
+            }
+            // Bitcoin wallets exchange message signatures as base64
+            if wallet_format {
+                let address = match &info.address {
+                    Some(address) => address.clone(),
+                    None => bitcoin_address_of(&key_entry, false)?,
+                };
+                out.result("Signature (base64)", &BASE64_STANDARD.encode(&sig_bytes));
+                infoln!(out, "Address: {}", address);
+            }
+            if eip191 && !raw_stdout {
+                infoln!(out, "Signer address: {}", ethereum_address_of(&key_entry)?);
+            }
+            
+            verboseln!(out, "Signed with {} in {:.2?}", key_entry.metadata.scheme, started.elapsed());
+            // The signature is made either way, so failing to record it only warns
+            if !no_track && in_keystore {
+                if let Err(e) = keystore.record_use(&key) {
+                    eprintln!("Warning: the use of key {} was not recorded: {}; pass --no-track to skip recording", key, e);
+                }
+            }
+        }
+        
+        Commands::Verify { key, public_key, signature, message, file, digest, prehash, eip191, bitcoin_message, bip322, address, dst, ciphersuite, aug, raw, allow_high_s, scheme, max_age, min_timestamp, no_progress, .. } => {
+            let progress = !no_progress && !out.quiet;
+            if scheme.is_some() && !raw && public_key.is_none() {
+                return Err(usage("--scheme is only used with --raw or --public-key; signature files record their scheme"));
+            }
+            let max_age = max_age.as_deref()
+                .map(|spec| parse_duration(spec).ok_or_else(|| CliError::InvalidValue {
+                    argument: "--max-age",
+                    value: spec.to_string(),
+                    expected: "a duration such as 30s, 15m, 1h or 7d",
+                }))
+                .transpose()?;
+            let min_timestamp = min_timestamp.as_deref().map(parse_rfc3339).transpose()?;
+            // Bitcoin message signatures are checked against an address rather than a key
+            let stored = if signature.is_file() && !raw { Some(load_json_signature(&signature)?) } else { None };
+            let stored_scheme = stored.as_ref().map(|(sig_scheme, _, _)| sig_scheme.as_str());
+            let bitcoin_message = bitcoin_message || stored_scheme == Some(BITCOIN_MESSAGE_SCHEME);
+            let bip322 = bip322 || stored_scheme == Some(BIP322_SCHEME);
+            if address.is_some() && !bitcoin_message && !bip322 {
+                return Err(usage("--address is only used with --bitcoin-message or --bip322"));
+            }
+            
+            if bitcoin_message || bip322 {
+                let expected_scheme = if bip322 { BIP322_SCHEME } else { BITCOIN_MESSAGE_SCHEME };
+                if max_age.is_some() || min_timestamp.is_some() {
+                    return Err(usage("--max-age and --min-timestamp are not supported for Bitcoin message and BIP-322 signatures"));
+                }
+                // Wallets hand out the signature as base64 rather than a file
+                let (sig_bytes, recorded_address) = match stored {
+                    Some((sig_scheme, sig_bytes, info)) => {
+                        if sig_scheme != expected_scheme {
+                            return Err(CliError::WrongSignatureScheme { expected: expected_scheme.to_string(), found: sig_scheme });
+                        }
+                        (sig_bytes, info.address)
+                    }
+                    None => {
+                        let sig_bytes = BASE64_STANDARD.decode(signature.to_string_lossy().as_bytes())
+                            .map_err(|_| usage("Signature is neither a file nor base64"))?;
+                        (sig_bytes, None)
+                    }
+                };
+                
+                let expected = match (address, &key, recorded_address) {
+                    (Some(address), _, _) => address,
+                    (None, Some(key), _) => bitcoin_address_of(&keystore.read_entry(key)?, bip322)?,
+                    (None, None, Some(recorded)) => recorded,
+                    (None, None, None) => return Err(usage("Verifying a Bitcoin message needs --address or --key")),
+                };
+                infoln!(out, "Address: {}", expected);
+                
+                let msg = read_message(message, file, progress)?;
+                let is_valid = if bip322 {
+                    bip322::verify(&expected, &msg, &sig_bytes)?
+                } else {
+                    let (public_key, compressed) = bitcoin::recover_message_signer(&msg, &sig_bytes)?;
+                    let recovered = bitcoin::p2pkh_address(&public_key, compressed);
+                    if recovered != expected {
+                        infoln!(out, "Signature was made by {}, not {}", recovered, expected);
+                    }
+                    recovered == expected
+                };
+                
+                report_verification(out, is_valid, expected_scheme, key.as_deref());
+                return Ok(Outcome::verified(is_valid));
+            }
+            
+            let scheme = scheme.map(|scheme| scheme_name_of(&scheme).map_or(scheme, str::to_string));
+            // A recorded scheme is used, so a --scheme naming another would be ignored
+            if let (Some(scheme), Some((sig_scheme, _, _))) = (&scheme, &stored) {
+                if key_scheme_of(scheme) != key_scheme_of(sig_scheme) {
+                    return Err(usage(format!("--scheme {} does not match the {} signature in {:?}", scheme, sig_scheme, signature)));
+                }
+            }
+            let key_entry = match (&key, public_key) {
+                (Some(key), _) => {
+                    let key_entry = keystore.read_entry(key)?;
+                    if key_entry.metadata.is_expired() {
+                        let expires_at = key_entry.metadata.expires_at.unwrap_or_default();
+                        eprintln!("Warning: key {} expired at {}", key, format_timestamp(expires_at));
+                    }
+                    key_entry
+                }
+                (None, Some(public_key)) => {
+                    // Without key metadata the scheme has to be given or recorded
+                    let key_scheme = match (&scheme, &stored) {
+                        (Some(scheme), _) => key_scheme_of(scheme).to_string(),
+                        (None, Some((sig_scheme, _, _))) => key_scheme_of(sig_scheme).to_string(),
+                        (None, None) => return Err(usage("--public-key needs --scheme unless the signature file records it")),
+                    };
+                    public_key_entry(PUBLIC_KEY_NAME, &public_key, &key_scheme)?
+                }
+                (None, None) => return Err(usage("--key or --public-key is required")),
+            };
+            // Messages name a --public-key by the flag; reports leave its key out
+            let key_label = key_entry.metadata.name.clone();
+            let (sig_scheme, sig_bytes, info) = match (stored, scheme) {
+                (Some(stored), _) => stored,
+                (None, Some(scheme)) if raw => {
+                    if load_signature_with_info(&signature).is_ok() {
+                        return Err(usage(format!("{:?} is a JSON signature file; verify it without --raw", signature)));
+                    }
+                    (scheme, read_file(&signature)?, SignatureInfo { prehashed: digest.is_some(), ..Default::default() })
+                }
+                (None, _) => load_json_signature(&signature)?,
+            };
+            // Files from before public keys were embedded only record the fingerprint
+            match (&info.public_key, &info.key_fingerprint) {
+                (Some(embedded), _) => {
+                    if !embedded.eq_ignore_ascii_case(&key_entry.public_key) {
+                        eprintln!("Warning: signature file embeds public key {}, but key {} is {}", 
+                                 embedded, key_label, key_entry.public_key);
+                    }
+                }
+                (None, Some(recorded)) => {
+                    let fingerprint = key_entry.fingerprint()?;
+                    if *recorded != fingerprint {
+                        eprintln!("Warning: signature was made by the key with fingerprint {}, but key {} has fingerprint {}", 
+                                 recorded, key_label, fingerprint);
+                    }
+                }
+                (None, None) => {}
+            }
+            
+            // A digest signature only verifies against the digest, and a message signature only against the message
+            let prehashed = digest.is_some();
+            if info.prehashed && !prehashed {
+                return Err(usage("Signature was made over a precomputed digest; verify it with --digest"));
+            }
+            if prehashed && !info.prehashed {
+                return Err(usage("Signature was made over a message, not a precomputed digest; verify it with --message or --file"));
+            }
+            // The hash a signature file records is applied without asking, but never replaced
+            let prehash = match (prehash, info.prehash.clone()) {
+                (Some(given), Some(recorded)) if given != recorded => {
+                    return Err(usage(format!("Signature was made over the {} digest of the message, not {}; verify it without --prehash", 
+                                      recorded, given)));
+                }
+                (given, recorded) => given.or(recorded),
+            };
+            let embedded = info.message.as_deref()
+                .map(|message| BASE64_STANDARD.decode(message).map_err(|_| StorageError::InvalidFormat("message")))
+                .transpose()?;
+            if prehash.is_some() && embedded.is_some() {
+                return Err(usage("--prehash cannot be used with a signature file that embeds its message"));
+            }
+            if prehash.is_some() && info.signed_timestamp {
+                return Err(usage("Signature covers its timestamp and the whole message; verify it without --prehash"));
+            }
+            // Checked after the signature, but a file without a timestamp fails before reading the message
+            let freshness = if max_age.is_some() || min_timestamp.is_some() {
+                let signed_at = info.timestamp
+                    .ok_or_else(|| usage("The signature file records no timestamp to check --max-age or --min-timestamp against"))?;
+                let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+                Some(Freshness { signed_at, now, max_age, min_timestamp, advisory: !info.signed_timestamp })
+            } else {
+                None
+            };
+            // The SHA-256 of a message given on the command line, to check against the recorded one
+            let (msg, message_sha256) = match (digest, embedded) {
+                (Some(digest), _) => {
+                    let digest = parse_digest(&digest)?;
+                    (digest.clone(), Some(digest))
+                }
+                (None, Some(embedded)) if message.is_none() && file.is_none() => (embedded, None),
+                // A message given as well must be the one that was embedded
+                (None, Some(embedded)) => {
+                    if read_message(message, file, progress)? != embedded {
+                        return Err(StorageError::EmbeddedMessageMismatch(format!("{:?}", signature)).into());
+                    }
+                    (embedded, None)
+                }
+                (None, None) => match &prehash {
+                    Some(algorithm) => {
+                        let ([digest, message_sha256], len) = hash_message(message, file, [algorithm.as_str(), "sha256"], progress)?;
+                        verboseln!(out, "Hashed {} bytes with {} as they were read", len, algorithm);
+                        (digest, Some(message_sha256))
+                    }
+                    None => {
+                        let msg = read_message(message, file, progress)?;
+                        let message_sha256 = info.message_sha256.is_some().then(|| Sha256::digest(&msg).to_vec());
+                        (msg, message_sha256)
+                    }
+                },
+            };
+            // A message that does not hash to the recorded digest will not verify with any key
+            if let (Some(recorded), Some(message_sha256)) = (&info.message_sha256, message_sha256) {
+                let message_sha256 = hex::encode(message_sha256);
+                if !recorded.eq_ignore_ascii_case(&message_sha256) {
+                    eprintln!("Warning: the message has SHA-256 {}, but the signature file records {}; it is not the message that was signed", 
+                             message_sha256, recorded);
+                }
+            }
+            let msg = if info.signed_timestamp {
+                timestamped_message(info.timestamp.ok_or(StorageError::InvalidFormat("timestamp"))?, &msg)
+            } else {
+                msg
+            };
+            let stored_dst = info.dst;
+            let scheme = key_scheme_of(&sig_scheme).to_string();
+            
+            // A signature of another scheme cannot have been made by this key
+            if scheme != key_entry.metadata.scheme {
+                infoln!(out, "Signature scheme mismatch: {} vs {}", scheme, key_entry.metadata.scheme);
+                report_verification(out, false, &scheme, key.as_deref());
+                return Ok(Outcome::Invalid);
+            }
+            
+            if eip191 && scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--eip191 is only supported for ECDSA-secp256k1 keys, not {}", scheme)));
+            }
+            let eip191 = eip191 || sig_scheme == EIP191_SCHEME;
+            if (dst.is_some() || ciphersuite.is_some()) && !is_bls_scheme(&scheme) {
+                return Err(usage(format!("--dst and --ciphersuite are only supported for BLS keys, not {}", scheme)));
+            }
+            if aug && scheme != "BLS12-381-min-pk" {
+                return Err(usage(format!("--aug is only supported for BLS12-381-min-pk keys, not {}", scheme)));
+            }
+            let aug = aug || sig_scheme == bls::AUG_SCHEME;
+            let dst = preset_dst(&scheme, dst, ciphersuite.as_deref())?;
+            if allow_high_s && scheme != "ECDSA-secp256k1" {
+                return Err(usage(format!("--allow-high-s is only supported for ECDSA-secp256k1 keys, not {}", scheme)));
+            }
+            
+            verboseln!(out, "Verifying a {}-byte {} signature over {} bytes with key {}", sig_bytes.len(), sig_scheme, msg.len(), key_label);
+            let started = Instant::now();
+            // Either way the message is a digest by now
+            let params = VerifyParams { eip191, prehashed: prehashed || prehash.is_some(), dst, stored_dst, aug, allow_high_s };
+            let is_valid = verify_signature(out, &key_entry, &sig_scheme, &sig_bytes, msg, params)?;
+            
+            // Only a valid signature's timestamp is worth checking
+            match &freshness {
+                Some(freshness) if is_valid => report_freshness(out, freshness, &scheme, key.as_deref()),
+                _ => report_verification(out, is_valid, &scheme, key.as_deref()),
+            }
+            verboseln!(out, "Verified with {} in {:.2?}", scheme, started.elapsed());
+            if !is_valid || !freshness.as_ref().is_none_or(Freshness::is_fresh) {
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::VerifyManifest { dir, key, pattern, allow_unsigned } => {
+            let key_entry = keystore.read_entry(&key)?;
+            if key_entry.metadata.is_expired() {
+                let expires_at = key_entry.metadata.expires_at.unwrap_or_default();
+                eprintln!("Warning: key {} expired at {}", key, format_timestamp(expires_at));
+            }
+            let pattern = glob::Pattern::new(&pattern).map_err(|e| usage(format!("Invalid --pattern: {}", e)))?;
+            
+            // Only files directly in the directory, in name order
+            let mut names = Vec::new();
+            for entry in fs::read_dir(&dir).map_err(read_error(&dir))? {
+                let entry = entry.map_err(read_error(&dir))?;
+                if entry.file_type().map_err(read_error(&entry.path()))?.is_file() {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+            names.sort();
+            let (signatures, artifacts): (Vec<&String>, Vec<&String>) = names.iter().partition(|name| pattern.matches(name));
+            if signatures.is_empty() {
+                return Err(usage(format!("No files in {:?} match {}", dir, pattern)));
+            }
+            
+            let mut rows = Vec::new();
+            let mut signed = Vec::new();
+            let (mut passed, mut failed) = (0, 0);
+            for sig_name in signatures {
+                let artifact = Path::new(sig_name).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                let result = match verify_manifest_entry(out, &key_entry, &dir.join(sig_name), &dir.join(&artifact)) {
+                    Ok(true) => {
+                        passed += 1;
+                        "PASS".to_string()
+                    }
+                    Ok(false) => {
+                        failed += 1;
+                        "FAIL (invalid signature)".to_string()
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        format!("FAIL ({})", e)
+                    }
+                };
+                rows.push((artifact.clone(), result));
+                signed.push(artifact);
+            }
+            let unsigned: Vec<&String> = artifacts.into_iter().filter(|name| !signed.contains(name)).collect();
+            for name in &unsigned {
+                let result = if allow_unsigned { "UNSIGNED (allowed)" } else { "UNSIGNED" };
+                rows.push((name.to_string(), result.to_string()));
+            }
+            rows.sort();
+            
+            let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("ARTIFACT".len());
+            infoln!(out, "{:<width$}  RESULT", "ARTIFACT");
+            for (name, result) in &rows {
+                infoln!(out, "{:<width$}  {}", name, result);
+            }
+            infoln!(out, "{} passed, {} failed, {} unsigned", passed, failed, unsigned.len());
+            
+            let is_valid = failed == 0 && (unsigned.is_empty() || allow_unsigned);
+            if out.json {
+                let artifacts: Vec<serde_json::Value> = rows.iter()
+                    .map(|(name, result)| serde_json::json!({ "artifact": name, "result": result }))
+                    .collect();
+                out.print_json(&serde_json::json!({
+                    "valid": is_valid, "key": key, "artifacts": artifacts, "passed": passed, "failed": failed, "unsigned": unsigned.len(),
+                }));
+            }
+            if !is_valid {
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::SignTree { dir, key, output, force } => {
+            check_overwrite(&output, force)?;
+            let key_entry = keystore.load_key_entry(&key)?;
+            if key_entry.metadata.is_expired() {
+                let expires_at = key_entry.metadata.expires_at.unwrap_or_default();
+                return Err(CliError::KeyExpired { key, expires_at, overridable: false });
+            }
+            if key_entry.private_key.is_empty() && key_entry.metadata.piv_slot.is_none() {
+                return Err(StorageError::PublicOnly(key).into());
+            }
+            
+            let manifest = tree_manifest(&dir, &output)?;
+            if manifest.entries.is_empty() {
+                return Err(usage(format!("No files to sign in {:?}", dir)));
+            }
+            let manifest_bytes = serde_json::to_vec(&manifest)?;
+            audit.message_sha256 = Some(hex::encode(Sha256::digest(&manifest_bytes)));
+            
+            let (sig_scheme, sig_bytes, info) = sign_message(&key_entry, manifest_bytes, SignParams::default())?;
+            let info = SignatureInfo {
+                manifest: Some(manifest.clone()),
+                signature_encoding: text_encoding_field(out.text_encoding),
+                ..info
+            };
+            save_signature_with_info(&output, &sig_scheme, &sig_bytes, &info)?;
+            infoln!(out, "Signed {} entries under {:?} with {}; manifest and signature saved to {:?}", 
+                     manifest.entries.len(), dir, key, output);
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "key": key, "scheme": sig_scheme, "entries": manifest.entries.len(), "output": output,
+                }));
+            }
+            if let Err(e) = keystore.record_use(&key) {
+                eprintln!("Warning: the use of key {} was not recorded: {}", key, e);
+            }
+        }
+        
+        Commands::VerifyTree { dir, key, signature } => {
+            let key_entry = keystore.read_entry(&key)?;
+            let (sig_scheme, sig_bytes, info) = read_signature_file(&signature)?;
+            let Some(manifest) = info.manifest else {
+                return Err(CliError::SignatureFile { path: signature, source: StorageError::InvalidFormat("manifest") });
+            };
+            if key_scheme_of(&sig_scheme) != key_entry.metadata.scheme {
+                infoln!(out, "Signature scheme ({}) does not match key scheme ({})", sig_scheme, key_entry.metadata.scheme);
+                report_verification(out, false, &sig_scheme, Some(&key));
+                return Ok(Outcome::Invalid);
+            }
+            
+            let params = VerifyParams { stored_dst: info.dst, ..Default::default() };
+            let is_valid = verify_signature(out, &key_entry, &sig_scheme, &sig_bytes, serde_json::to_vec(&manifest)?, params)?;
+            
+            // Compare the directory with the manifest whether or not the signature holds
+            let current = tree_manifest(&dir, &signature)?;
+            let (added, removed, modified) = diff_tree(&manifest, &current);
+            let unchanged = added.is_empty() && removed.is_empty() && modified.is_empty();
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "valid": is_valid, "scheme": sig_scheme, "key": key,
+                    "unchanged": unchanged, "added": added, "removed": removed, "modified": modified,
+                }));
+                return Ok(Outcome::verified(is_valid && unchanged));
+            }
+            report_verification(out, is_valid, &sig_scheme, Some(&key));
+            for path in &added {
+                infoln!(out, "added:    {}", path);
+            }
+            for path in &removed {
+                infoln!(out, "removed:  {}", path);
+            }
+            for path in &modified {
+                infoln!(out, "modified: {}", path);
+            }
+            if unchanged {
+                infoln!(out, "Directory matches the manifest ({} entries)", manifest.entries.len());
+            } else {
+                infoln!(out, "Directory differs from the manifest: {} added, {} removed, {} modified", 
+                         added.len(), removed.len(), modified.len());
+            }
+            
+            if !is_valid || !unchanged {
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::Tweak { key, merkle_root, save_as } => {
+            let key_entry = keystore.load_key_entry(&key)?;
+            
+            if key_entry.metadata.scheme != "Schnorr-secp256k1-BIP340" && key_entry.metadata.scheme != "ECDSA-secp256k1" {
+                return Err(StorageError::WrongScheme(key, key_entry.metadata.scheme, "ECDSA-secp256k1 or Schnorr-secp256k1-BIP340".into()).into());
+            }
+            
+            let merkle_root = match merkle_root {
+                Some(root_hex) => {
+                    let root: [u8; 32] = hex::decode(&root_hex)
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .ok_or_else(|| usage("Merkle root must be 32 bytes of hex"))?;
+                    Some(root)
+                }
+                None => None,
+            };
+            
+            // The secret scalar is shared between the ECDSA and Schnorr encodings
+            let private_key_bytes = hex::decode(&key_entry.private_key)
+                .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            let private_key = Schnorr::deserialize_private_key(&private_key_bytes)?;
+            
+            let tweaked = Schnorr::taproot_tweak(&private_key, merkle_root.as_ref())?;
+            let output_key = Schnorr::serialize_public_key(tweaked.verifying_key())?;
+            
+            let name = save_as.unwrap_or_else(|| format!("{}-tweaked", key));
+            let entry = KeyEntry {
+                version: CURRENT_VERSION,
+                metadata: KeyMetadata::new(&name, TWEAKED_SCHEME),
+                private_key: hex::encode(Schnorr::serialize_private_key(&tweaked)?),
+                public_key: hex::encode(&output_key),
+            };
+            keystore.save_key_entry(&entry)?;
+            
+            infoln!(out, "Tweaked key saved as: {}", name);
+            infoln!(out, "Taproot output key (x-only): {}", hex::encode(&output_key));
+            if out.json {
+                out.print_json(&key_json(out, &entry)?);
+            }
+        }
+        
+        Commands::Recover { signature, message, file, hash, eip191 } => {
+            // The signature is either a signature file or inline hex
+            let (sig_scheme, sig_bytes) = if PathBuf::from(&signature).is_file() {
+                let (sig_scheme, sig_bytes, _) = read_signature_file(&signature)?;
+                if ![RECOVERABLE_SCHEME, KECCAK256_RECOVERABLE_SCHEME, EIP191_SCHEME].contains(&sig_scheme.as_str()) {
+                    return Err(CliError::WrongSignatureScheme { expected: "recoverable ECDSA".into(), found: sig_scheme });
+                }
+                (Some(sig_scheme), sig_bytes)
+            } else {
+                let sig_bytes = hex::decode(signature.trim_start_matches("0x"))
+                    .map_err(|_| usage("Signature is neither a file nor hex"))?;
+                (None, sig_bytes)
+            };
+            
+            let eip191 = eip191 || sig_scheme.as_deref() == Some(EIP191_SCHEME);
+            let keccak = match hash.as_deref() {
+                Some(digest) => digest == "keccak256",
+                None => eip191 || sig_scheme.as_deref() == Some(KECCAK256_RECOVERABLE_SCHEME),
+            };
+            
+            let msg = get_message(message, file)?;
+            let msg = if eip191 { ECDSA::eip191_message(&msg) } else { msg };
+            let digest = ECDSA::message_digest(&msg, keccak);
+            let public_key = ECDSA::recover_public_key(&digest, &sig_bytes)?;
+            
+            let public_key_hex = hex::encode(ECDSA::serialize_public_key(&public_key)?);
+            let address = keccak.then(|| ECDSA::ethereum_address(&public_key));
+            outln!(out, "Recovered public key: {}", public_key_hex);
+            if let Some(address) = &address {
+                outln!(out, "Ethereum address: {}", address);
+            }
+            
+            // Name any keystore keys the signer matches
+            let mut matches = Vec::new();
+            for metadata in keystore.list_keys()?.keys.into_iter().map(|key| key.metadata) {
+                if metadata.scheme != "ECDSA-secp256k1" {
+                    continue;
+                }
+                let key_entry = keystore.read_entry(&metadata.name)?;
+                let stored = hex::decode(&key_entry.public_key)
+                    .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                if ECDSA::deserialize_public_key(&stored)? == public_key {
+                    matches.push(metadata.name);
+                }
+            }
+            
+            if out.json {
+                out.print_json(&serde_json::json!({ "public_key": public_key_hex, "address": address, "matches": matches }));
+            } else if matches.is_empty() {
+                outln!(out, "No matching key in the keystore");
+            } else {
+                outln!(out, "Matches keystore key: {}", matches.join(", "));
+            }
+        }
+        
+        Commands::Aggregate { signatures, output, dst, ciphersuite, encoding, committee, message, file, mapping, force } => {
+            check_overwrite(&output, force)?;
+            let signatures = expand_signature_paths(&signatures)?;
+            let mapping = mapping.map(|path| load_mapping(&path, &signatures)).transpose()?;
+            let signatures = match &mapping {
+                Some(entries) if signatures.is_empty() => entries.iter().map(|(path, _)| path.clone()).collect(),
+                _ => signatures,
+            };
+            infoln!(out, "Aggregating {} signature files", signatures.len());
+            let (scheme, agg_dst, loaded) = load_aggregation_inputs(&signatures)?;
+            
+            if let Some(dst) = preset_dst(&scheme, dst, ciphersuite.as_deref())? {
+                if dst != agg_dst {
+                    return Err(usage(format!("DST mismatch: signatures were made under {:?} but --dst is {:?}", agg_dst, dst)));
+                }
+            }
+            
+            let encoding = point_encoding(&scheme, encoding.as_deref())?;
+            let agg_bytes = aggregate_bls_signatures(&scheme, &signatures, &loaded, encoding)?;
+            
+            // Attribute every signature to the committee member whose key verifies it
+            let committee = if committee.is_empty() {
+                None
+            } else {
+                if message.is_none() && file.is_none() {
+                    return Err(usage("--committee needs the signed --message or --file to attribute signatures"));
+                }
+                let msg = get_message(message, file)?;
+                audit.message_sha256 = Some(hex::encode(Sha256::digest(&msg)));
+                let key_scheme = key_scheme_of(&scheme);
+                let aug = scheme == bls::AUG_SCHEME;
+                
+                let mut members = Vec::new();
+                for key_name in &committee {
+                    if members.iter().any(|member: &CommitteeMember| member.key == *key_name) {
+                        return Err(usage(format!("Duplicate key in committee: {}", key_name)));
+                    }
+                    
+                    let key_entry = keystore.read_entry(key_name)?;
+                    if key_entry.metadata.scheme != key_scheme {
+                        return Err(StorageError::WrongScheme(key_name.clone(), key_entry.metadata.scheme, key_scheme.to_string()).into());
+                    }
+                    members.push(CommitteeMember { key: key_name.clone(), public_key: key_entry.public_key });
+                }
+                
+                let mut bitmap = vec![0u8; committee.len().div_ceil(8)];
+                for (sig_path, sig_bytes) in signatures.iter().zip(&loaded) {
+                    let mut signer = None;
+                    for (i, member) in members.iter().enumerate() {
+                        let pk_bytes = hex::decode(&member.public_key)
+                            .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                        if verify_bls_signature(key_scheme, &pk_bytes, &msg, sig_bytes, agg_dst.as_bytes(), aug)? {
+                            signer = Some(i);
+                            break;
+                        }
+                    }
+                    
+                    let Some(i) = signer else {
+                        return Err(CliError::VerificationFailed(format!("Signature {:?} is not from any committee member", sig_path)));
+                    };
+                    if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                        return Err(CliError::VerificationFailed(format!("Committee member {} signed more than once", members[i].key)));
+                    }
+                    bitmap[i / 8] |= 1 << (i % 8);
+                }
+                
+                Some(Committee { id: committee.join(","), members, bitmap: hex::encode(bitmap) })
+            };
+            
+            // Check every signature against its mapped message, so a mapping out of order fails here
+            // rather than leaving an aggregate that never verifies
+            let signers = match mapping {
+                None => None,
+                Some(entries) => {
+                    let key_scheme = key_scheme_of(&scheme);
+                    let aug = scheme == bls::AUG_SCHEME;
+                    let mut signers = Vec::new();
+                    let mut mismatched = Vec::new();
+                    for (i, ((sig_path, spec), sig_bytes)) in entries.into_iter().zip(&loaded).enumerate() {
+                        let (fingerprint, public_key) = signer_of(keystore, &sig_path)?;
+                        let pk_bytes = hex::decode(&public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                        let msg = message_from_spec(&spec)?;
+                        if !verify_bls_signature(key_scheme, &pk_bytes, &msg, sig_bytes, agg_dst.as_bytes(), aug)? {
+                            mismatched.push(format!("{} ({:?})", i, sig_path));
+                        }
+                        signers.push(AggregateSigner { fingerprint, public_key, message_sha256: hex::encode(Sha256::digest(&msg)), message: spec });
+                    }
+                    if !mismatched.is_empty() {
+                        return Err(CliError::VerificationFailed(format!(
+                            "Signatures {} do not verify over the messages the mapping gives them; check the mapping order",
+                            mismatched.join(", ")
+                        )));
+                    }
+                    Some(signers)
+                }
+            };
+            
+            let info = SignatureInfo { dst: Some(agg_dst), ciphersuite, committee, signers, ..Default::default() };
+            let agg_scheme = format!("{}-aggregated", scheme);
+            save_signature_with_info(&output, &agg_scheme, &agg_bytes, &info)?;
+            infoln!(out, "Aggregated signature saved to {:?}", output);
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "scheme": agg_scheme,
+                    "signature": encode_text(out.text_encoding, &agg_bytes),
+                    "output": output,
+                    "signatures": signatures.len(),
+                }));
+            }
+        }
+        
+        Commands::VerifyAggregate { keys, key, signature, message, file, messages_file, require_pop, dst, ciphersuite, aug } => {
+            let (sig_scheme, sig_bytes, info) = read_signature_file(signature)?;
+            let stored_dst = info.dst;
+            
+            // Aggregates carry an "-aggregated" suffix; a single signature is also accepted
+            let aug = aug || sig_scheme.trim_end_matches("-aggregated") == bls::AUG_SCHEME;
+            let scheme = key_scheme_of(sig_scheme.trim_end_matches("-aggregated"));
+            if !is_bls_scheme(scheme) {
+                return Err(CliError::WrongSignatureScheme { expected: "BLS".into(), found: sig_scheme });
+            }
+            if aug && scheme != "BLS12-381-min-pk" {
+                return Err(usage(format!("--aug is only supported for BLS12-381-min-pk signatures, not {}", scheme)));
+            }
+            if aug && key.is_some() {
+                return Err(usage("AUG aggregates bind each member's key into its message; verify with --keys instead of an aggregated --key"));
+            }
+            
+            // A committee aggregate names its participants itself when no keys are given; their
+            // keys come from the keystore
+            let committee = match info.committee {
+                Some(committee) if keys.is_empty() && key.is_none() && messages_file.is_none() => {
+                    Some(committee_participants(keystore, &committee, scheme)?)
+                }
+                _ => None,
+            };
+            
+            // A mapped aggregate names its signers and their messages itself when nothing else is given
+            let mapped = match info.signers {
+                Some(signers) if keys.is_empty() && key.is_none() && messages_file.is_none() && committee.is_none() => {
+                    if require_pop.is_some() {
+                        return Err(usage("--require-pop looks proofs up by key name; verify this aggregate with --messages-file to use it"));
+                    }
+                    Some(signers)
+                }
+                _ => None,
+            };
+            
+            // Pair every signer with the message it signed
+            let signers = match (messages_file, &mapped) {
+                (None, Some(mapped)) => mapped_messages(mapped)?,
+                (Some(path), _) => {
+                    let entries = load_message_entries(&path)?;
+                    
+                    if !keys.is_empty() {
+                        if keys.len() != entries.len() {
+                            return Err(usage(format!("--keys lists {} keys but {:?} has {} entries", 
+                                              keys.len(), path, entries.len())));
+                        }
+                        for (i, (key_name, (entry_key, _))) in keys.iter().zip(&entries).enumerate() {
+                            if key_name != entry_key {
+                                return Err(usage(format!("Entry {} names key {} but --keys lists {} at that position", 
+                                                        i, entry_key, key_name)));
+                            }
+                        }
+                    }
+                    
+                    entries
+                }
+                (None, None) => {
+                    let msg = get_message(message, file)?;
+                    match (&key, &committee) {
+                        (Some(key_name), _) => vec![(key_name.clone(), msg)],
+                        (None, Some(participants)) => {
+                            participants.iter().map(|(key_name, _)| (key_name.clone(), msg.clone())).collect()
+                        }
+                        (None, None) => keys.into_iter().map(|key_name| (key_name, msg.clone())).collect(),
+                    }
+                }
+            };
+            
+            if signers.is_empty() {
+                return Err(usage("At least one key must be supplied with --keys, --key or --messages-file"));
+            }
+            if let Some(participants) = &committee {
+                infoln!(out, "Committee signers: {}", 
+                         participants.iter().map(|(key_name, _)| key_name.as_str()).collect::<Vec<_>>().join(", "));
+            }
+            
+            // A pre-aggregated key stands in for all of its members
+            let key_scheme = match key {
+                Some(_) => format!("{}-aggregated", scheme),
+                None => scheme.to_string(),
+            };
+            
+            // A mapped signer may sign more than one of the messages
+            let mut seen = std::collections::HashSet::new();
+            for (key_name, _) in &signers {
+                if mapped.is_none() && !seen.insert(key_name.as_str()) {
+                    return Err(usage(format!("Duplicate key in signer list: {}", key_name)));
+                }
+            }
+            
+            let mut public_key_bytes = Vec::new();
+            
+            for (i, (key_name, _)) in signers.iter().enumerate() {
+                let pk_bytes = match (&committee, &mapped) {
+                    // Committee members were looked up in the keystore and checked against the file
+                    (Some(participants), _) => participants[i].1.clone(),
+                    // Mapped signers are the keystore keys with the fingerprints the file records
+                    (None, Some(mapped)) => {
+                        let (name, pk_bytes) = mapped_signer_key(keystore, &mapped[i], scheme)?;
+                        infoln!(out, "Signer {} ({}) signed {} (SHA-256 {})", name, mapped[i].fingerprint, mapped[i].message, mapped[i].message_sha256);
+                        pk_bytes
+                    }
+                    (None, None) => {
+                        let key_entry = keystore.read_entry(key_name)?;
+                        
+                        if key_entry.metadata.scheme != key_scheme {
+                            return Err(StorageError::WrongScheme(key_name.clone(), key_entry.metadata.scheme, key_scheme.to_string()).into());
+                        }
+                        
+                        hex::decode(&key_entry.public_key)
+                            .map_err(|_| StorageError::InvalidFormat("public_key"))?
+                    }
+                };
+                
+                // Without proofs of possession a rogue key could cancel out honest signers
+                if let Some(pop_dir) = &require_pop {
+                    check_pop_file(pop_dir, key_name, scheme, &pk_bytes)?;
+                }
+                
+                public_key_bytes.push(pk_bytes);
+            }
+            
+            let messages: Vec<&[u8]> = signers.iter().map(|(_, msg)| msg.as_slice()).collect();
+            let dst = resolve_dst(scheme, preset_dst(scheme, dst, ciphersuite.as_deref())?, stored_dst);
+            
+            let is_valid = verify_bls_aggregate(scheme, &public_key_bytes, &messages, &sig_bytes, dst.as_bytes(), aug)?;
+            
+            infoln!(out, "Aggregated signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+            if out.json {
+                let signers: Vec<&String> = signers.iter().map(|(key_name, _)| key_name).collect();
+                out.print_json(&serde_json::json!({ "valid": is_valid, "scheme": sig_scheme, "signers": signers }));
+            }
+            
+            if !is_valid {
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::AggregateVerify { signatures, keys, message, file, paired, output, force } => {
+            if let Some(output) = &output {
+                check_overwrite(output, force)?;
+            }
+            if keys.is_empty() {
+                return Err(usage("At least one key must be supplied with --keys"));
+            }
+            if paired && keys.len() != signatures.len() {
+                return Err(usage(format!("--paired needs one key per signature, got {} keys and {} signatures", 
+                                  keys.len(), signatures.len())));
+            }
+            
+            let (sig_scheme, agg_dst, loaded) = load_aggregation_inputs(&signatures)?;
+            let agg_bytes = aggregate_bls_signatures(&sig_scheme, &signatures, &loaded, PointEncoding::Compressed)?;
+            let aug = sig_scheme == bls::AUG_SCHEME;
+            let scheme = key_scheme_of(&sig_scheme);
+            
+            let mut public_key_bytes = Vec::new();
+            for key_name in &keys {
+                if keys.iter().filter(|other| *other == key_name).count() > 1 {
+                    return Err(usage(format!("Duplicate key in signer list: {}", key_name)));
+                }
+                
+                let key_entry = keystore.read_entry(key_name)?;
+                if key_entry.metadata.scheme != scheme {
+                    return Err(StorageError::WrongScheme(key_name.clone(), key_entry.metadata.scheme, scheme.to_string()).into());
+                }
+                
+                public_key_bytes.push(hex::decode(&key_entry.public_key)
+                    .map_err(|_| StorageError::InvalidFormat("public_key"))?);
+            }
+            
+            let msg = get_message(message, file)?;
+            let messages = vec![msg.as_slice(); keys.len()];
+            let is_valid = verify_bls_aggregate(scheme, &public_key_bytes, &messages, &agg_bytes, agg_dst.as_bytes(), aug)?;
+            
+            infoln!(out, "Aggregated signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+            
+            // Only an aggregate that verifies is saved
+            let saved = match output {
+                Some(output) if is_valid => {
+                    let info = SignatureInfo { dst: Some(agg_dst), ..Default::default() };
+                    save_signature_with_info(&output, &format!("{}-aggregated", sig_scheme), &agg_bytes, &info)?;
+                    infoln!(out, "Aggregated signature saved to {:?}", output);
+                    Some(output)
+                }
+                _ => None,
+            };
+            if out.json {
+                out.print_json(&serde_json::json!({ "valid": is_valid, "scheme": sig_scheme, "keys": keys, "output": saved }));
+            }
+            if !is_valid {
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::VerifyBatch { input } => {
+            #[derive(serde::Deserialize)]
+            struct BatchEntry {
+                key: String,
+                message_hex: String,
+                signature: String,
+                #[serde(default)]
+                dst: Option<String>,
+            }
+            
+            let contents = read_text_file(&input)?;
+            let mut items = Vec::new();
+            
+            for (line_no, line) in contents.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: BatchEntry = serde_json::from_str(line)
+                    .map_err(|e| CliError::Input { path: input.clone(), line: line_no + 1, message: e.to_string() })?;
+                
+                let key_entry = keystore.read_entry(&entry.key)?;
+                let scheme = key_entry.metadata.scheme;
+                if !is_bls_scheme(&scheme) {
+                    let message = format!("key {} is not a BLS key (found {})", entry.key, scheme);
+                    return Err(CliError::Input { path: input, line: line_no + 1, message });
+                }
+                
+                let pk_bytes = hex::decode(&key_entry.public_key)
+                    .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                let msg = hex::decode(&entry.message_hex)
+                    .map_err(|e| CliError::Input { path: input.clone(), line: line_no + 1, message: format!("invalid message_hex: {}", e) })?;
+                
+                // The signature is either a signature file, which records its DST, or inline hex
+                let (sig_bytes, stored_dst) = if PathBuf::from(&entry.signature).is_file() {
+                    let (sig_scheme, sig_bytes, info) = read_signature_file(&entry.signature)?;
+                    if sig_scheme != scheme {
+                        let message = format!("{} signature does not match {} key {}", sig_scheme, scheme, entry.key);
+                        return Err(CliError::Input { path: input, line: line_no + 1, message });
+                    }
+                    (sig_bytes, info.dst)
+                } else {
+                    let sig_bytes = hex::decode(&entry.signature)
+                        .map_err(|_| CliError::Input { path: input.clone(), line: line_no + 1, message: "signature is neither a file nor hex".into() })?;
+                    (sig_bytes, None)
+                };
+                let dst = resolve_dst(&scheme, entry.dst, stored_dst);
+                
+                items.push(BatchItem { scheme, dst, public_key: pk_bytes, message: msg, signature: sig_bytes });
+            }
+            
+            if items.is_empty() {
+                return Err(usage(format!("No entries found in {:?}", input)));
+            }
+            
+            let started = Instant::now();
+            
+            // Each ciphersuite and DST is checked as its own batch
+            let mut batches: BTreeMap<(&str, &str), Vec<usize>> = BTreeMap::new();
+            for (i, item) in items.iter().enumerate() {
+                batches.entry((item.scheme.as_str(), item.dst.as_str())).or_default().push(i);
+            }
+            let mut invalid = Vec::new();
+            for ((scheme, dst), indices) in &batches {
+                invalid.extend(verify_bls_batch(scheme, dst.as_bytes(), &items, indices)?);
+            }
+            invalid.sort_unstable();
+            
+            verboseln!(out, "Verified {} signatures in {:.2?}", items.len(), started.elapsed());
+            
+            infoln!(out, "{} valid, {} invalid", items.len() - invalid.len(), invalid.len());
+            if out.json {
+                out.print_json(&serde_json::json!({ "valid": invalid.is_empty(), "entries": items.len(), "invalid": invalid }));
+            }
+            
+            if !invalid.is_empty() {
+                let listed: Vec<String> = invalid.iter().map(|i| i.to_string()).collect();
+                infoln!(out, "Invalid entries: {}", listed.join(", "));
+                return Ok(Outcome::Invalid);
+            }
+        }
+        
+        Commands::AggregateKeys { keys, save_as, require_pop } => {
+            let mut seen = std::collections::HashSet::new();
+            let mut loaded = Vec::new();
+            
+            for key_name in &keys {
+                if !seen.insert(key_name.as_str()) {
+                    return Err(usage(format!("Duplicate key in key list: {}", key_name)));
+                }
+                
+                let key_entry = keystore.read_entry(key_name)?;
+                let pk_bytes = hex::decode(&key_entry.public_key)
+                    .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                
+                if let Some(pop_dir) = &require_pop {
+                    check_pop_file(pop_dir, key_name, &key_entry.metadata.scheme, &pk_bytes)?;
+                }
+                
+                loaded.push((key_name, key_entry.metadata.scheme, pk_bytes));
+            }
+            
+            // All keys must share the scheme of the first one
+            let scheme = match loaded.first() {
+                Some((_, scheme, _)) => scheme.clone(),
+                None => return Err(usage("No keys to aggregate")),
+            };
+            
+            for (key_name, other, _) in &loaded {
+                if *other != scheme {
+                    return Err(StorageError::WrongScheme(key_name.to_string(), other.clone(), scheme).into());
+                }
+            }
+            
+            let Some(aggregatable) = registry::lookup(&scheme).and_then(|registered| registered.as_aggregatable()) else {
+                return Err(usage(format!("Can only aggregate BLS keys, found: {}", scheme)));
+            };
+            let public_keys: Vec<Vec<u8>> = loaded.iter().map(|(_, _, pk_bytes)| pk_bytes.clone()).collect();
+            let agg_bytes = aggregatable.aggregate_public_keys(&public_keys)?;
+            
+            // Public-only entry: there is no private key for an aggregate
+            let entry = KeyEntry {
+                version: CURRENT_VERSION,
+                metadata: KeyMetadata::new(&save_as, &format!("{}-aggregated", scheme)),
+                private_key: String::new(),
+                public_key: hex::encode(&agg_bytes),
+            };
+            keystore.save_key_entry(&entry)?;
+            
+            infoln!(out, "Aggregated {} keys saved as: {}", loaded.len(), save_as);
+            if out.json {
+                let mut aggregated = key_json(out, &entry)?;
+                aggregated["keys"] = keys.into();
+                out.print_json(&aggregated);
+            }
+        }
+        
+        Commands::SplitKey { key, threshold, shares, output_dir } => {
+            let key_entry = keystore.load_key_entry(&key)?;
+            
+            if key_entry.metadata.scheme != "BLS12-381-min-pk" {
+                return Err(StorageError::WrongScheme(key, key_entry.metadata.scheme, "BLS12-381-min-pk".into()).into());
+            }
+            
+            let private_key_bytes = hex::decode(&key_entry.private_key)
+                .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            let private_key = BLS::deserialize_private_key(&private_key_bytes)?;
+            
+            fs::create_dir_all(&output_dir)?;
+            let mut files = Vec::new();
+            for (index, share) in BLS::split_key(&private_key, threshold, shares, &mut OsRng)? {
+                let key_share = KeyShare {
+                    scheme: SHARE_SCHEME.to_string(),
+                    key: key.clone(),
+                    index,
+                    threshold,
+                    shares,
+                    public_key: key_entry.public_key.clone(),
+                    share: hex::encode(BLS::serialize_private_key(&share)?),
+                };
+                
+                let path = output_dir.join(format!("{}-share-{}.json", key, index));
+                save_key_share(&path, &key_share)?;
+                infoln!(out, "Share {} of {} saved to {:?}", index, shares, path);
+                files.push(path);
+            }
+            infoln!(out, "Any {} shares can sign for {}", threshold, key);
+            if out.json {
+                out.print_json(&serde_json::json!({ "key": key, "threshold": threshold, "shares": shares, "files": files }));
+            }
+        }
+        
+        Commands::SignShare { share, message, file, output, force } => {
+            check_overwrite(&output, force)?;
+            let key_share = load_key_share(&share)?;
+            let msg = get_message(message, file)?;
+            audit.message_sha256 = Some(hex::encode(Sha256::digest(&msg)));
+            
+            if key_share.scheme != SHARE_SCHEME {
+                return Err(CliError::WrongSignatureScheme { expected: SHARE_SCHEME.into(), found: key_share.scheme });
+            }
+            
+            let share_bytes = hex::decode(&key_share.share)
+                .map_err(|_| StorageError::InvalidFormat("share"))?;
+            let share_key = BLS::deserialize_private_key(&share_bytes)?;
+            let partial = BLS::sign(&share_key, &msg)?;
+            
+            let info = SignatureInfo {
+                dst: Some(default_dst(BLS::name())),
+                share: Some(ShareInfo { index: key_share.index, threshold: key_share.threshold, public_key: key_share.public_key }),
+                ..Default::default()
+            };
+            let sig_bytes = BLS::serialize_signature(&partial)?;
+            save_signature_with_info(&output, PARTIAL_SCHEME, &sig_bytes, &info)?;
+            infoln!(out, "Partial signature {} saved to {:?}", key_share.index, output);
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "scheme": PARTIAL_SCHEME,
+                    "signature": encode_text(out.text_encoding, &sig_bytes),
+                    "output": output,
+                    "index": key_share.index,
+                }));
+            }
+        }
+        
+        Commands::Combine { partials, output, force } => {
+            check_overwrite(&output, force)?;
+            let mut loaded = Vec::new();
+            let mut group: Option<ShareInfo> = None;
+            
+            for path in &partials {
+                let (scheme, sig_bytes, info) = read_signature_file(path)?;
+                let share = match (scheme.as_str(), info.share) {
+                    (PARTIAL_SCHEME, Some(share)) => share,
+                    (PARTIAL_SCHEME, None) => {
+                        return Err(CliError::SignatureFile { path: path.clone(), source: StorageError::InvalidFormat("share") });
+                    }
+                    (found, _) => return Err(CliError::WrongSignatureScheme { expected: PARTIAL_SCHEME.into(), found: found.into() }),
+                };
+                
+                // Every partial must come from a share of the same key
+                if let Some(first) = &group {
+                    if first.public_key != share.public_key || first.threshold != share.threshold {
+                        return Err(usage(format!("{:?} was made with a share of a different key", path)));
+                    }
+                }
+                
+                loaded.push((share.index, BLS::deserialize_signature(&sig_bytes)?));
+                group.get_or_insert(share);
+            }
+            
+            let Some(group) = group else {
+                return Err(usage("No partial signatures to combine"));
+            };
+            if loaded.len() < group.threshold as usize {
+                return Err(usage(format!("Need at least {} partial signatures to combine, got {}", group.threshold, loaded.len())));
+            }
+            
+            let combined = BLS::combine_partials(&loaded)?;
+            let info = SignatureInfo { dst: Some(default_dst(BLS::name())), ..Default::default() };
+            let sig_bytes = BLS::serialize_signature(&combined)?;
+            save_signature_with_info(&output, BLS::name(), &sig_bytes, &info)?;
+            infoln!(out, "Combined {} partial signatures into {:?}", loaded.len(), output);
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "scheme": BLS::name(),
+                    "signature": encode_text(out.text_encoding, &sig_bytes),
+                    "output": output,
+                    "partials": loaded.len(),
+                }));
+            }
+        }
+        
+        Commands::Pop { key, output, encoding, force } => {
+            if let Some(output) = &output {
+                check_overwrite(output, force)?;
+            }
+            let key_entry = keystore.load_key_entry(&key)?;
+            
+            let private_key_bytes = hex::decode(&key_entry.private_key)
+                .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            
+            let (pop_scheme, pop_bytes) = match key_entry.metadata.scheme.as_str() {
+                "BLS12-381-min-pk" => {
+                    let private_key = BLS::deserialize_private_key(&private_key_bytes)?;
+                    let pop = BLS::prove_possession(&private_key)?;
+                    (bls::POP_SCHEME, BLS::encode_signature(&pop, point_encoding(&key_entry.metadata.scheme, encoding.as_deref())?))
+                }
+                "BLS12-381-min-sig" => {
+                    let private_key = BLSMinSig::deserialize_private_key(&private_key_bytes)?;
+                    let pop = BLSMinSig::prove_possession(&private_key)?;
+                    (bls_min_sig::POP_SCHEME, BLSMinSig::encode_signature(&pop, point_encoding(&key_entry.metadata.scheme, encoding.as_deref())?))
+                }
+                _ => {
+                    return Err(StorageError::WrongScheme(key, key_entry.metadata.scheme, "BLS12-381-min-pk or BLS12-381-min-sig".into()).into());
+                }
+            };
+            
+            let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.pop", key)));
+            save_signature(&output, pop_scheme, &pop_bytes)?;
+            infoln!(out, "Proof of possession saved to {:?}", output);
+            if out.json {
+                out.print_json(&serde_json::json!({
+                    "key": key,
+                    "scheme": pop_scheme,
+                    "signature": encode_text(out.text_encoding, &pop_bytes),
+                    "output": output,
+                }));
+            }
+        }
+        
+        Commands::VerifyPop { key, pop } => {
+            let key_entry = keystore.read_entry(&key)?;
+            let (pop_scheme, pop_bytes, _) = read_signature_file(pop)?;
+            
+            let pk_bytes = hex::decode(&key_entry.public_key)
+                .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+            
+            let is_valid = verify_pop(&key_entry.metadata.scheme, &pk_bytes, &pop_scheme, &pop_bytes)?;
+            
+            infoln!(out, "Proof of possession verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+            if out.json {
+                out.print_json(&serde_json::json!({ "valid": is_valid, "scheme": pop_scheme, "key": key }));
+            }
+            
+            if !is_valid {
+                return Ok(Outcome::Invalid);
+            }
+        }
+    }
+    
+    Ok(Outcome::Success)
+}
+
+// How sign makes a signature beyond the key and message: the flags it was given, already
+// checked against the key's scheme
+#[derive(Default)]
+struct SignParams {
+    prehash: Option<String>,
+    prehashed: bool,
+    recoverable: bool,
+    eip191: bool,
+    bitcoin_message: bool,
+    bip322: bool,
+    address_type: AddressType,
+    dst: Option<String>,
+    ciphersuite: Option<String>,
+    aug: bool,
+    encoding: PointEncoding,
+    extra_entropy: Option<[u8; 32]>,
+    sig_encoding: Option<String>,
+}
+
+// Sign with a keystore key, returning the scheme to record, the signature and the details
+// stored alongside it
+fn sign_message(key_entry: &KeyEntry, msg: Vec<u8>, params: SignParams) -> Result<(String, Vec<u8>, SignatureInfo), CliError> {
+    let SignParams { prehash, prehashed, recoverable, eip191, bitcoin_message, bip322, address_type, dst, ciphersuite, aug, encoding, extra_entropy, sig_encoding } = params;
+    // Recorded in signature files so verify can tell when a different key is used
+    let key_fingerprint = Some(key_entry.fingerprint()?);
+    let plain = |sig_scheme: &str, sig_bytes: Vec<u8>| {
+        (sig_scheme.to_string(), sig_bytes, SignatureInfo { key_fingerprint: key_fingerprint.clone(), ..Default::default() })
+    };
+    
+    // The BLS ciphersuites sign under a DST
+    let registered = registered_scheme(&key_entry.metadata.scheme)?;
+    if let Some(bls) = registered.as_aggregatable() {
+        let private_key_bytes = hex::decode(&key_entry.private_key)
+            .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+        
+        let dst = dst.unwrap_or_else(|| String::from_utf8_lossy(bls.default_dst()).into_owned());
+        let sig_bytes = bls.sign_with_dst(&private_key_bytes, &msg, dst.as_bytes(), aug, encoding)?;
+        let sig_scheme = if aug { bls::AUG_SCHEME } else { registered.name() };
+        let info = SignatureInfo { dst: Some(dst), ciphersuite, prehashed, key_fingerprint, ..Default::default() };
+        return Ok((sig_scheme.to_string(), sig_bytes, info));
+    }
+    
+    Ok(match key_entry.metadata.scheme.as_str() {
+        "ECDSA-secp256k1" => {
+            let private_key_bytes = hex::decode(&key_entry.private_key)
+                .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            let private_key = ECDSA::deserialize_private_key(&private_key_bytes)?;
+            
+            // EIP-191 is a recoverable Keccak-256 signature over the framed message
+            let msg = if eip191 { ECDSA::eip191_message(&msg) } else { msg };
+            let recoverable = recoverable || eip191;
+            let keccak = eip191 || prehash.as_deref() == Some("keccak256");
+            // The message is a digest already with --digest, or once --prehash has hashed it
+            let signs_digest = prehashed || prehash.is_some();
+            let sig_encoding_name = sig_encoding.as_deref().unwrap_or("der");
+            let sig_encoding = match sig_encoding_name {
+                "compact" => SignatureEncoding::Compact,
+                _ => SignatureEncoding::Der,
+            };
+            let (sig_scheme, sig_bytes) = if bitcoin_message {
+                (BITCOIN_MESSAGE_SCHEME, bitcoin::sign_message(&private_key, &msg)?)
+            } else if bip322 {
+                (BIP322_SCHEME, bip322::sign_p2wpkh(&private_key, &msg)?)
+            } else if recoverable {
+                let digest = if signs_digest { msg.clone() } else { ECDSA::message_digest(&msg, keccak).to_vec() };
+                let sig_scheme = if eip191 {
+                    EIP191_SCHEME
+                } else if keccak {
+                    KECCAK256_RECOVERABLE_SCHEME
+                } else {
+                    RECOVERABLE_SCHEME
+                };
+                let sig_bytes = match &extra_entropy {
+                    Some(extra_entropy) => ECDSA::sign_recoverable_hedged(&private_key, &digest, extra_entropy)?,
+                    None => ECDSA::sign_recoverable(&private_key, &digest)?,
+                };
+                (sig_scheme, sig_bytes)
+            } else if let Some(extra_entropy) = &extra_entropy {
+                // Hedged signing works on the digest, whichever way it was computed
+                let digest = if signs_digest { msg.clone() } else { ECDSA::message_digest(&msg, keccak).to_vec() };
+                let (signature, _) = ECDSA::sign_prehash_hedged(&private_key, &digest, extra_entropy)?;
+                let sig_scheme = if keccak { KECCAK256_SCHEME } else { "ECDSA-secp256k1" };
+                (sig_scheme, ECDSA::encode_signature(&signature, sig_encoding))
+            } else if signs_digest {
+                let signature = ECDSA::sign_prehash(&private_key, &msg)?;
+                let sig_scheme = if keccak { KECCAK256_SCHEME } else { "ECDSA-secp256k1" };
+                (sig_scheme, ECDSA::encode_signature(&signature, sig_encoding))
+            } else if keccak {
+                let signature = ECDSA::sign_keccak256(&private_key, &msg)?;
+                (KECCAK256_SCHEME, ECDSA::encode_signature(&signature, sig_encoding))
+            } else {
+                let signature = ECDSA::sign(&private_key, &msg)?;
+                ("ECDSA-secp256k1", ECDSA::encode_signature(&signature, sig_encoding))
+            };
+            
+            // Recoverable signatures have a single r||s||v layout
+            let info = SignatureInfo {
+                encoding: (!recoverable && !bitcoin_message && !bip322).then(|| sig_encoding_name.to_string()),
+                prehashed,
+                hedged: extra_entropy.is_some(),
+                address: bip322.then(|| bitcoin::p2wpkh_address(private_key.verifying_key())),
+                key_fingerprint,
+                ..Default::default()
+            };
+            (sig_scheme.to_string(), sig_bytes, info)
+        }
+        "Schnorr-secp256k1-BIP340" | TWEAKED_SCHEME => {
+            let private_key_bytes = hex::decode(&key_entry.private_key)
+                .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+            let private_key = Schnorr::deserialize_private_key(&private_key_bytes)?;
+            
+            if bip322 && address_type == AddressType::P2tr {
+                // Untweaked keys sign for their BIP-86 key-path-only output key
+                let output_key = if key_entry.metadata.scheme == TWEAKED_SCHEME {
+                    private_key
+                } else {
+                    Schnorr::taproot_tweak(&private_key, None)?
+                };
+                let sig_bytes = bip322::sign_p2tr(&output_key, &msg)?;
+                let address = bitcoin::p2tr_address(output_key.verifying_key());
+                let info = SignatureInfo { address: Some(address), key_fingerprint, ..Default::default() };
+                (BIP322_SCHEME.to_string(), sig_bytes, info)
+            } else {
+                let signature = Schnorr::sign(&private_key, &msg)?;
+                plain(&key_entry.metadata.scheme, Schnorr::serialize_signature(&signature)?)
+            }
+        }
+        "ECDSA-P256" => {
+            let sig_bytes = match &key_entry.metadata.piv_slot {
+                Some(slot) => {
+                    let sig_bytes = yubikey::sign(slot, &msg)?;
+                    // Catch a slot that was regenerated since the key was recorded
+                    let public_key_bytes = hex::decode(&key_entry.public_key)
+                        .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+                    let public_key = EcdsaP256::deserialize_public_key(&public_key_bytes)?;
+                    if !EcdsaP256::verify(&public_key, &msg, &EcdsaP256::deserialize_signature(&sig_bytes)?)? {
+                        return Err(CliError::VerificationFailed(format!(
+                            "YubiKey PIV slot {} no longer holds key {}; its signature does not verify", slot, key_entry.metadata.name
+                        )));
+                    }
+                    sig_bytes
+                }
+                None => {
+                    let private_key_bytes = hex::decode(&key_entry.private_key)
+                        .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+                    let private_key = EcdsaP256::deserialize_private_key(&private_key_bytes)?;
+                    EcdsaP256::serialize_signature(&EcdsaP256::sign(&private_key, &msg)?)?
+                }
+            };
+            plain("ECDSA-P256", sig_bytes)
+        }
+        // Every other scheme signs the message as it is, through the signer a remote key
+        // would sign with too
+        scheme => {
+            // An expired key only gets this far with --allow-expired
+            let signer = BlockingSigner::new(KeyStoreSigner::new_allowing_expired(key_entry)?)?;
+            plain(scheme, signer.sign(scheme, &msg)?)
+        }
+    })
+}
+
+// What sign --signed-timestamp signs: a fixed prefix, the Unix time as 8 big-endian bytes,
+// then the message
+fn timestamped_message(timestamp: u64, msg: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(SIGNED_TIMESTAMP_PREFIX.len() + 8 + msg.len());
+    payload.extend_from_slice(SIGNED_TIMESTAMP_PREFIX);
+    payload.extend_from_slice(&timestamp.to_be_bytes());
+    payload.extend_from_slice(msg);
+    payload
+}
+
+// The message as sign --embed stores it, refusing messages over the size limit
+fn embedded_message(embed: bool, msg: &[u8], max_embed_size: usize) -> Result<Option<String>, CliError> {
+    if !embed {
+        return Ok(None);
+    }
+    if msg.len() > max_embed_size {
+        return Err(usage(format!("The message is {} bytes, more than the {} bytes --embed stores; raise --max-embed-size or ship the message beside the signature", 
+                                msg.len(), max_embed_size)));
+    }
+    Ok(Some(BASE64_STANDARD.encode(msg)))
+}
+
+// Refuse to replace an existing output file unless --force (or --yes) says to, asking
+// first when there is a terminal to ask on. Commands check before doing any work, so
+// nothing is signed or prompted for only to be thrown away.
+fn check_overwrite(path: &Path, force: bool) -> Result<(), CliError> {
+    if force || !path.exists() {
+        return Ok(());
+    }
+    // Stdin may be carrying the message, so only a terminal is asked
+    if std::io::stderr().is_terminal() && std::io::stdin().is_terminal() {
+        eprint!("{} already exists; overwrite it? [y/N]: ", path.display());
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Ok(());
+        }
+    }
+    Err(CliError::OutputExists { path: path.to_path_buf() })
+}
+
+// Save a signature to --output as a JSON signature file, or print it in the text encoding.
+// With --raw only the signature bytes are written, to the file or to stdout. With --json
+// the signature is reported as {scheme, signature, output} whichever way it was saved.
+fn emit_signature(
+    output: Option<PathBuf>,
+    raw: bool,
+    out: &Output,
+    scheme: &str,
+    sig_bytes: &[u8],
+    info: &SignatureInfo,
+) -> Result<(), CliError> {
+    match (&output, raw) {
+        (Some(output_path), false) => {
+            let info = SignatureInfo { signature_encoding: text_encoding_field(out.text_encoding), ..info.clone() };
+            save_signature_with_info(output_path, scheme, sig_bytes, &info)?;
+            infoln!(out, "Signature saved to {:?}", output_path);
+        }
+        (Some(output_path), true) => {
+            write_atomic(output_path, sig_bytes)?;
+            infoln!(out, "Raw {} signature saved to {:?}", scheme, output_path);
+        }
+        (None, false) => out.result("Signature", &encode_text(out.text_encoding, sig_bytes)),
+        (None, true) => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(sig_bytes)?;
+            stdout.flush()?;
+        }
+    }
+    if out.json {
+        out.print_json(&serde_json::json!({
+            "scheme": scheme,
+            "signature": encode_text(out.text_encoding, sig_bytes),
+            "output": output,
+        }));
+    }
+    Ok(())
+}
+
+// How verify checks a signature beyond the key, scheme and message: the flags it was
+// given, resolved against what the signature file recorded
+#[derive(Default)]
+struct VerifyParams {
+    eip191: bool,
+    prehashed: bool,
+    dst: Option<String>,
+    stored_dst: Option<String>,
+    aug: bool,
+    allow_high_s: bool,
+}
+
+// Check a signature against a keystore key of its scheme
+fn verify_signature(
+    out: &Output,
+    key_entry: &KeyEntry,
+    sig_scheme: &str,
+    sig_bytes: &[u8],
+    msg: Vec<u8>,
+    params: VerifyParams,
+) -> Result<bool, CliError> {
+    let scheme = key_scheme_of(sig_scheme);
+    let registered = registered_scheme(scheme)?;
+    let public_key_bytes = hex::decode(&key_entry.public_key)
+        .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    
+    // The BLS ciphersuites verify under a DST
+    if let Some(bls) = registered.as_aggregatable() {
+        let dst = resolve_dst(scheme, params.dst, params.stored_dst);
+        return Ok(bls.verify_with_dst(&public_key_bytes, &msg, sig_bytes, dst.as_bytes(), params.aug)?);
+    }
+    
+    Ok(match scheme {
+        "ECDSA-secp256k1" => {
+            let public_key = ECDSA::deserialize_public_key(&public_key_bytes)?;
+            
+            let msg = if params.eip191 { ECDSA::eip191_message(&msg) } else { msg };
+            let keccak = params.eip191 || sig_scheme == KECCAK256_SCHEME || sig_scheme == KECCAK256_RECOVERABLE_SCHEME;
+            
+            if sig_bytes.len() == RECOVERABLE_SIGNATURE_LEN {
+                // Recover the signer and require it to be the keystore key
+                let digest = if params.prehashed { msg.clone() } else { ECDSA::message_digest(&msg, keccak).to_vec() };
+                let recovered_matches = match ECDSA::recover_public_key(&digest, sig_bytes) {
+                    Ok(recovered) => recovered == public_key,
+                    Err(_) => false,
+                };
+                if !recovered_matches {
+                    infoln!(out, "Recovered public key does not match key {}", key_entry.metadata.name);
+                }
+                
+                recovered_matches && ECDSA::verify_prehash(&public_key, &digest, &sig_bytes[..64])?
+            } else {
+                let normalized = ECDSA::normalize_high_s(sig_bytes)?;
+                let sig_bytes = match normalized.as_deref() {
+                    Some(low_s) if params.allow_high_s => {
+                        verboseln!(out, "Signature has a high S; verifying its low-S form as --allow-high-s asks");
+                        low_s
+                    }
+                    Some(_) => {
+                        verboseln!(out, "Signature has a high S, which ECDSA-secp256k1 verification refuses; --allow-high-s accepts it");
+                        sig_bytes
+                    }
+                    None => sig_bytes,
+                };
+                if params.prehashed {
+                    return Ok(ECDSA::verify_prehash(&public_key, &msg, sig_bytes)?);
+                }
+                let signature = ECDSA::deserialize_signature(sig_bytes)?;
+                if keccak {
+                    ECDSA::verify_keccak256(&public_key, &msg, &signature)?
+                } else {
+                    ECDSA::verify(&public_key, &msg, &signature)?
+                }
+            }
+        }
+        // Every other scheme verifies the message as it is
+        _ => registered.verify_bytes(&public_key_bytes, &msg, sig_bytes)?,
+    })
+}
+
+// Why verify-manifest could not check a signature file against its artifact, for its
+// table of results
+#[derive(Error, Debug)]
+enum ManifestEntryError {
+    #[error("unreadable signature file: {0}")]
+    Unreadable(StorageError),
+
+    #[error("no artifact for the signature")]
+    NoArtifact,
+
+    #[error("signed over a digest, not the file")]
+    Prehashed,
+
+    #[error("{signature} signature, {key} key")]
+    WrongScheme { signature: String, key: String },
+
+    #[error(transparent)]
+    Cli(#[from] CliError),
+}
+
+// Check one verify-manifest signature file against its artifact with what the file
+// recorded
+fn verify_manifest_entry(out: &Output, key_entry: &KeyEntry, signature: &Path, artifact: &Path) -> Result<bool, ManifestEntryError> {
+    let (sig_scheme, sig_bytes, info) = load_signature_with_info(signature).map_err(ManifestEntryError::Unreadable)?;
+    if !artifact.is_file() {
+        return Err(ManifestEntryError::NoArtifact);
+    }
+    if info.prehashed {
+        return Err(ManifestEntryError::Prehashed);
+    }
+    if key_scheme_of(&sig_scheme) != key_entry.metadata.scheme {
+        return Err(ManifestEntryError::WrongScheme { signature: sig_scheme, key: key_entry.metadata.scheme.clone() });
+    }
+    
+    let msg = recorded_message(&info, signature, None, Some(artifact.to_path_buf()), false)?;
+    Ok(verify_signature(out, key_entry, &sig_scheme, &sig_bytes, msg, recorded_params(&sig_scheme, info))?)
+}
+
+// verify --standalone: check a signature file against the public key embedded in it, and
+// report that key's fingerprint for the caller to pin
+fn verify_standalone(
+    out: &Output,
+    signature: &Path,
+    message: Option<String>,
+    file: Option<PathBuf>,
+    progress: bool,
+) -> Result<bool, CliError> {
+    let (sig_scheme, sig_bytes, info) = load_json_signature(signature)?;
+    if sig_scheme == BITCOIN_MESSAGE_SCHEME || sig_scheme == BIP322_SCHEME {
+        return Err(usage(format!("{} signatures are verified against an address; pass --address instead of --standalone", sig_scheme)));
+    }
+    if info.prehashed {
+        return Err(usage("Signature was made over a precomputed digest; verify it with --key or --public-key and --digest"));
+    }
+    let Some(public_key) = &info.public_key else {
+        return Err(usage(format!("{:?} has no embedded public key; re-sign the message with sign --output, which embeds the signer's public key since signature file version {}, or verify it with --key or --public-key", 
+                          signature, SIGNATURE_FILE_VERSION)));
+    };
+    let scheme = key_scheme_of(&sig_scheme);
+    let key_entry = public_key_entry(&format!("The public key embedded in {:?}", signature), public_key, scheme)?;
+    let fingerprint = key_entry.fingerprint()?;
+    if info.key_fingerprint.as_ref().is_some_and(|recorded| *recorded != fingerprint) {
+        eprintln!("Warning: signature file records fingerprint {}, but its embedded public key has fingerprint {}", 
+                 info.key_fingerprint.as_deref().unwrap_or_default(), fingerprint);
+    }
+    
+    let msg = recorded_message(&info, signature, message, file, progress)?;
+    verboseln!(out, "Verifying a {}-byte {} signature over {} bytes with the embedded public key", sig_bytes.len(), sig_scheme, msg.len());
+    let is_valid = verify_signature(out, &key_entry, &sig_scheme, &sig_bytes, msg, recorded_params(&sig_scheme, info))?;
+    
+    if out.json {
+        out.print_json(&serde_json::json!({
+            "valid": is_valid,
+            "scheme": scheme,
+            "key": null,
+            "public_key": key_entry.public_key,
+            "fingerprint": fingerprint,
+        }));
+    } else {
+        infoln!(out, "Embedded public key: {} (fingerprint {})", key_entry.public_key, fingerprint);
+        infoln!(out, "Signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+        infoln!(out, "Note: the key comes from the signature file itself; check the fingerprint against one you trust");
+    }
+    Ok(is_valid)
+}
+
+// inspect-sig: print what a signature file holds and any problems with it, returning
+// whether there were none. With --json the file's contents are included as they are.
+fn inspect_signature(out: &Output, path: &Path) -> Result<bool, CliError> {
+    let bytes = read_file(path)?;
+    let raw: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| {
+        usage(format!("{:?} is not JSON ({}); if it is a raw signature from sign --raw, its {} bytes are all there is to inspect", path, e, bytes.len()))
+    })?;
+    let contents: SignatureFileContents = serde_json::from_value(raw.clone())
+        .map_err(|e| CliError::SignatureFile { path: path.to_path_buf(), source: StorageError::Json(e) })?;
+    let info = &contents.info;
+    
+    let mut problems = Vec::new();
+    if contents.version == 0 || contents.version > SIGNATURE_FILE_VERSION {
+        problems.push(format!("format version {} is not one this sig-tool reads (1 to {})", contents.version, SIGNATURE_FILE_VERSION));
+    }
+    let signature = decode_signature(&contents.signature, info.signature_encoding.as_deref());
+    if signature.is_none() {
+        problems.push(format!("the signature is not valid {}", info.signature_encoding.as_deref().unwrap_or("hex or base64")));
+    }
+    if info.signed_timestamp && info.timestamp.is_none() {
+        problems.push("signed_timestamp is set but there is no timestamp".to_string());
+    }
+    let embedded_fingerprint = match &info.public_key {
+        Some(public_key) => match public_key_entry("the embedded public key", public_key, key_scheme_of(&contents.scheme)) {
+            Ok(key_entry) => Some(key_entry.fingerprint()?),
+            Err(e) => {
+                problems.push(e.to_string());
+                None
+            }
+        },
+        None => None,
+    };
+    if let (Some(recorded), Some(fingerprint)) = (&info.key_fingerprint, &embedded_fingerprint) {
+        if recorded != fingerprint {
+            problems.push(format!("key_fingerprint is {} but the embedded public key has fingerprint {}", recorded, fingerprint));
+        }
+    }
+    let embedded_message = info.message.as_deref().map(|message| BASE64_STANDARD.decode(message));
+    match (&embedded_message, &info.message_sha256) {
+        (Some(Err(_)), _) => problems.push("the embedded message is not valid base64".to_string()),
+        (Some(Ok(message)), Some(recorded)) if !recorded.eq_ignore_ascii_case(&hex::encode(Sha256::digest(message))) => {
+            problems.push("message_sha256 does not match the embedded message".to_string());
+        }
+        _ => {}
+    }
+    let unknown: Vec<&str> = contents.unknown.keys().map(String::as_str).collect();
+    
+    if out.json {
+        out.print_json(&serde_json::json!({
+            "file": path,
+            "clean": problems.is_empty(),
+            "problems": problems,
+            "version": contents.version,
+            "scheme": contents.scheme,
+            "signature_length": signature.as_ref().map(Vec::len),
+            "timestamp": info.timestamp.map(format_timestamp),
+            "public_key_fingerprint": embedded_fingerprint,
+            "unknown_fields": unknown,
+            "contents": raw,
+        }));
+        return Ok(problems.is_empty());
+    }
+    
+    let versioned = raw.get("version").is_some();
+    infoln!(out, "Format version: {}{}", contents.version, if versioned { "" } else { " (no version field)" });
+    infoln!(out, "Scheme: {}", contents.scheme);
+    if let Some(signature) = &signature {
+        let prefix = hex::encode(&signature[..signature.len().min(16)]);
+        infoln!(out, "Signature: {} bytes, {}{}", signature.len(), prefix, if signature.len() > 16 { "…" } else { "" });
+    }
+    if let Some(timestamp) = info.timestamp {
+        infoln!(out, "Timestamp: {}{}", format_timestamp(timestamp), if info.signed_timestamp { " (signed)" } else { "" });
+    }
+    if let Some(public_key) = &info.public_key {
+        infoln!(out, "Public key: {}", public_key);
+    }
+    if let Some(fingerprint) = &info.key_fingerprint {
+        infoln!(out, "Key fingerprint: {}", fingerprint);
+    }
+    if let Some(message_sha256) = &info.message_sha256 {
+        infoln!(out, "Message SHA-256: {}", message_sha256);
+    }
+    if let Some(Ok(message)) = &embedded_message {
+        infoln!(out, "Embedded message: {} bytes", message.len());
+    }
+    for (name, value) in [("DST", &info.dst), ("Ciphersuite", &info.ciphersuite), ("Encoding", &info.encoding), 
+                          ("Address", &info.address), ("Prehash", &info.prehash), ("Signature encoding", &info.signature_encoding)] {
+        if let Some(value) = value {
+            infoln!(out, "{}: {}", name, value);
+        }
+    }
+    if info.prehashed {
+        infoln!(out, "Signed over a precomputed digest");
+    }
+    if info.hedged {
+        infoln!(out, "Hedged nonce");
+    }
+    if let Some(share) = &info.share {
+        infoln!(out, "Partial signature: share {} of a {}-of-n key", share.index, share.threshold);
+    }
+    if let Some(committee) = &info.committee {
+        infoln!(out, "Committee: {} ({} members)", committee.id, committee.members.len());
+    }
+    if let Some(signers) = &info.signers {
+        infoln!(out, "Mapped signers: {}", signers.len());
+    }
+    if let Some(manifest) = &info.manifest {
+        infoln!(out, "Manifest: {} entries", manifest.entries.len());
+    }
+    if !unknown.is_empty() {
+        infoln!(out, "Unknown fields: {}", unknown.join(", "));
+    }
+    if problems.is_empty() {
+        infoln!(out, "Structure: OK");
+    } else {
+        infoln!(out, "Structure: {} problems", problems.len());
+        for problem in &problems {
+            infoln!(out, "- {}", problem);
+        }
+    }
+    Ok(problems.is_empty())
+}
+
+// Show a key entry and check that it hangs together: the public key parses, and signs and
+// verifies with the private key when that can be read. With a keystore, private keys kept
+// in a backend are loaded for the check. `stored_as` is the name of the key's file.
+// Returns whether no problems were found.
+fn inspect_key(out: &Output, key_entry: &KeyEntry, stored_as: &str, keystore: Option<&KeyStore>) -> Result<bool, CliError> {
+    let metadata = &key_entry.metadata;
+    // Shares and aggregated keys are BLS keys under another name
+    let scheme = match metadata.scheme.trim_end_matches("-aggregated") {
+        SHARE_SCHEME => "BLS12-381-min-pk",
+        scheme => scheme,
+    };
+    
+    let mut problems = Vec::new();
+    if metadata.name != stored_as {
+        problems.push(format!("the metadata names the key {} but it is stored as {}", metadata.name, stored_as));
+    }
+    let public_key = match public_key_entry("the public key", &key_entry.public_key, scheme) {
+        Ok(public_key) => Some(public_key),
+        Err(e) => {
+            problems.push(e.to_string());
+            None
+        }
+    };
+    // Where the private key is, and its hex when it can be read for the check
+    let (location, private_key) = match (&metadata.piv_slot, &metadata.backend, keystore) {
+        (Some(slot), _, _) => (format!("on a YubiKey, PIV slot {}; it never leaves the device", slot), None),
+        (None, Some(backend), Some(keystore)) => match keystore.load_key_entry(&metadata.name) {
+            Ok(loaded) => (format!("kept in the {} backend", backend), Some(loaded.private_key)),
+            Err(e) => (format!("kept in the {} backend, which could not load it: {}", backend, e), None),
+        },
+        (None, Some(backend), None) => (format!("kept in the {} backend; inspect the key with --key to load it", backend), None),
+        (None, None, _) if key_entry.private_key.is_empty() => ("none (public key only)".to_string(), None),
+        (None, None, _) => ("in the key file".to_string(), Some(key_entry.private_key.clone())),
+    };
+    let key_pair = match (&private_key, &public_key) {
+        (Some(private_key), Some(public_key)) => {
+            let private_key = hex::decode(private_key).map_err(|_| "the private key is not valid hex".to_string());
+            let public_key = hex::decode(&public_key.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+            match private_key.and_then(|private_key| check_key_pair(scheme, &private_key, &public_key).map_err(|e| e.to_string())) {
+                Ok(()) => Some(true),
+                Err(e) => {
+                    problems.push(e);
+                    Some(false)
+                }
+            }
+        }
+        _ => None,
+    };
+    let fingerprint = key_entry.fingerprint().ok();
+    
+    if out.json {
+        out.print_json(&serde_json::json!({
+            "key": metadata.name,
+            "clean": problems.is_empty(),
+            "problems": problems,
+            "scheme": metadata.scheme,
+            "created_at": format_timestamp(metadata.created_at),
+            "fingerprint": fingerprint,
+            "public_key": key_entry.public_key,
+            "private_key": location,
+            // The keystore keeps private keys in the clear; only exports are encrypted
+            "encrypted": false,
+            "key_pair_matches": key_pair,
+            "expires_at": metadata.expires_at.map(format_timestamp),
+            "expired": metadata.is_expired(),
+            "tags": metadata.tags,
+            "derivation_path": metadata.derivation_path,
+            "seed_derived": metadata.seed_derived,
+            "address": metadata.address,
+        }));
+        return Ok(problems.is_empty());
+    }
+    
+    infoln!(out, "Key: {}", metadata.name);
+    infoln!(out, "Scheme: {}", metadata.scheme);
+    infoln!(out, "Created: {}", format_timestamp(metadata.created_at));
+    if let Some(fingerprint) = &fingerprint {
+        infoln!(out, "Fingerprint: {}", fingerprint);
+    }
+    infoln!(out, "Public key: {}", key_entry.public_key);
+    infoln!(out, "Private key: {}", location);
+    if private_key.is_some() {
+        infoln!(out, "Encrypted: no");
+    }
+    if let Some(derivation_path) = &metadata.derivation_path {
+        infoln!(out, "Derivation path: {}", derivation_path);
+    }
+    if metadata.seed_derived {
+        infoln!(out, "Derived from a seed file");
+    }
+    if let Some(address) = &metadata.address {
+        infoln!(out, "Ethereum address: {}", address);
+    }
+    if !metadata.tags.is_empty() {
+        infoln!(out, "Tags: {}", metadata.tags.join(", "));
+    }
+    if let Some(expires_at) = metadata.expires_at {
+        infoln!(out, "Expires: {}{}", format_timestamp(expires_at), if metadata.is_expired() { " (expired)" } else { "" });
+    }
+    match key_pair {
+        Some(true) => infoln!(out, "Key pair: the public key matches the private key"),
+        Some(false) => infoln!(out, "Key pair: MISMATCH"),
+        None => infoln!(out, "Key pair: not checked"),
+    }
+    if problems.is_empty() {
+        infoln!(out, "Consistency: OK");
+    } else {
+        infoln!(out, "Consistency: {} problems", problems.len());
+        for problem in &problems {
+            infoln!(out, "- {}", problem);
+        }
+    }
+    Ok(problems.is_empty())
+}
+
+// The bytes a signature file's signature covers: the message given, or the embedded one,
+// hashed with the recorded --prehash and behind a signed timestamp
+fn recorded_message(
+    info: &SignatureInfo,
+    signature: &Path,
+    message: Option<String>,
+    file: Option<PathBuf>,
+    progress: bool,
+) -> Result<Vec<u8>, CliError> {
+    let embedded = info.message.as_deref()
+        .map(|message| BASE64_STANDARD.decode(message).map_err(|_| StorageError::InvalidFormat("message")))
+        .transpose()?;
+    let msg = match (embedded, &info.prehash) {
+        (Some(embedded), _) if message.is_none() && file.is_none() => embedded,
+        // A message given as well must be the one that was embedded
+        (Some(embedded), _) => {
+            if read_message(message, file, progress)? != embedded {
+                return Err(StorageError::EmbeddedMessageMismatch(format!("{:?}", signature)).into());
+            }
+            embedded
+        }
+        (None, Some(algorithm)) => {
+            let ([digest], _) = hash_message(message, file, [algorithm.as_str()], progress)?;
+            digest
+        }
+        (None, None) => read_message(message, file, progress)?,
+    };
+    Ok(match (info.signed_timestamp, info.timestamp) {
+        (true, Some(timestamp)) => timestamped_message(timestamp, &msg),
+        (true, None) => return Err(StorageError::InvalidFormat("timestamp").into()),
+        (false, _) => msg,
+    })
+}
+
+// VerifyParams as a signature file records them, for verifying without flags
+fn recorded_params(sig_scheme: &str, info: SignatureInfo) -> VerifyParams {
+    VerifyParams {
+        eip191: sig_scheme == EIP191_SCHEME,
+        prehashed: info.prehash.is_some(),
+        stored_dst: info.dst,
+        aug: sig_scheme == bls::AUG_SCHEME,
+        ..Default::default()
+    }
+}
+
+// Record every file and symlink under a directory, sorted by path. The signature file
+// itself is left out so it can be written inside the directory it signs.
+fn tree_manifest(dir: &Path, signature: &Path) -> Result<TreeManifest, CliError> {
+    fn walk(root: &Path, dir: &Path, skip: Option<&Path>, entries: &mut Vec<TreeEntry>) -> Result<(), CliError> {
+        for entry in fs::read_dir(dir).map_err(read_error(dir))? {
+            let entry = entry.map_err(read_error(dir))?;
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(read_error(&path))?;
+            if file_type.is_dir() {
+                walk(root, &path, skip, entries)?;
+                continue;
+            }
+            
+            let relative = path.strip_prefix(root).expect("walked paths are under the root")
+                .components()
+                .map(|component| component.as_os_str().to_str().ok_or_else(|| usage(format!("{:?} is not a UTF-8 path", path))))
+                .collect::<Result<Vec<_>, _>>()?
+                .join("/");
+            if file_type.is_symlink() {
+                let target = fs::read_link(&path).map_err(read_error(&path))?;
+                let target = target.to_str().ok_or_else(|| usage(format!("{:?} links to a path that is not UTF-8", path)))?;
+                entries.push(TreeEntry { path: relative, size: None, sha256: None, symlink: Some(target.to_string()) });
+            } else if file_type.is_file() {
+                if skip.is_some_and(|skip| fs::canonicalize(&path).is_ok_and(|path| path == skip)) {
+                    continue;
+                }
+                let contents = read_file(&path)?;
+                entries.push(TreeEntry {
+                    path: relative,
+                    size: Some(contents.len() as u64),
+                    sha256: Some(hex::encode(Sha256::digest(&contents))),
+                    symlink: None,
+                });
+            } else {
+                return Err(usage(format!("{:?} is not a file, directory or symlink", path)));
+            }
+        }
+        Ok(())
+    }
+    
+    if !dir.is_dir() {
+        return Err(usage(format!("{:?} is not a directory", dir)));
+    }
+    // A signature file that does not exist yet cannot be in the tree
+    let skip = fs::canonicalize(signature).ok();
+    let mut entries = Vec::new();
+    walk(dir, dir, skip.as_deref(), &mut entries)?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(TreeManifest { entries })
+}
+
+// Paths added to, removed from and modified in a tree since its manifest was signed, each
+// in path order
+fn diff_tree(signed: &TreeManifest, current: &TreeManifest) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let signed: BTreeMap<&str, &TreeEntry> = signed.entries.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    let current: BTreeMap<&str, &TreeEntry> = current.entries.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    
+    let added = current.keys().filter(|path| !signed.contains_key(*path)).map(|path| path.to_string()).collect();
+    let removed = signed.keys().filter(|path| !current.contains_key(*path)).map(|path| path.to_string()).collect();
+    let modified = signed.iter()
+        .filter(|(path, entry)| current.get(*path).is_some_and(|current| current != *entry))
+        .map(|(path, _)| path.to_string())
+        .collect();
+    (added, removed, modified)
+}
+
+// Print the outcome of verify, or with --json report it as {valid, scheme, key}
+fn report_verification(out: &Output, is_valid: bool, scheme: &str, key: Option<&str>) {
+    if out.json {
+        out.print_json(&serde_json::json!({ "valid": is_valid, "scheme": scheme, "key": key }));
+    } else {
+        infoln!(out, "Signature verification: {}", if is_valid { "VALID ✓" } else { "INVALID ✗" });
+    }
+}
+
+// How a signature's timestamp compares with verify --max-age and --min-timestamp
+struct Freshness {
+    signed_at: u64,
+    now: u64,
+    max_age: Option<u64>,
+    min_timestamp: Option<u64>,
+    // The timestamp was recorded beside the signature rather than signed, so the check
+    // only holds if the file was not edited
+    advisory: bool,
+}
+
+impl Freshness {
+    fn age(&self) -> u64 {
+        self.now.saturating_sub(self.signed_at)
+    }
+    
+    fn is_fresh(&self) -> bool {
+        self.max_age.is_none_or(|max_age| self.age() <= max_age)
+            && self.min_timestamp.is_none_or(|min_timestamp| self.signed_at >= min_timestamp)
+    }
+}
+
+// report_verification for a valid signature checked for freshness: VALID, or INVALID-STALE
+// with the time it was made and the current time
+fn report_freshness(out: &Output, freshness: &Freshness, scheme: &str, key: Option<&str>) {
+    let fresh = freshness.is_fresh();
+    if out.json {
+        out.print_json(&serde_json::json!({
+            "valid": fresh,
+            "status": if fresh { "VALID" } else { "INVALID-STALE" },
+            "scheme": scheme,
+            "key": key,
+            "freshness": {
+                "advisory": freshness.advisory,
+                "signed_at": format_timestamp(freshness.signed_at),
+                "now": format_timestamp(freshness.now),
+                "max_age": freshness.max_age,
+                "min_timestamp": freshness.min_timestamp.map(format_timestamp),
+            },
+        }));
+        return;
+    }
+    infoln!(out, "Signature verification: {}", if fresh { "VALID ✓" } else { "INVALID-STALE ✗" });
+    infoln!(out, "Signed at: {}", format_timestamp(freshness.signed_at));
+    infoln!(out, "Now: {}", format_timestamp(freshness.now));
+    if let Some(max_age) = freshness.max_age.filter(|max_age| freshness.age() > *max_age) {
+        infoln!(out, "The signature is {}s old, more than the {}s --max-age allows", freshness.age(), max_age);
+    }
+    if let Some(min_timestamp) = freshness.min_timestamp.filter(|min_timestamp| freshness.signed_at < *min_timestamp) {
+        infoln!(out, "The signature was made before --min-timestamp {}", format_timestamp(min_timestamp));
+    }
+    if freshness.advisory {
+        infoln!(out, "Note: the timestamp is not signed, so this check is advisory; sign with --signed-timestamp to make it tamper-evident");
+    }
+}
+
+// Encode bytes as --text-encoding (or show-key --format) asks
+fn encode_text(text_encoding: &str, bytes: &[u8]) -> String {
+    match text_encoding {
+        BASE64_ENCODING => BASE64_STANDARD.encode(bytes),
+        _ => hex::encode(bytes),
+    }
+}
+
+// The signature_encoding recorded in a signature file; hex, the default, is left out so
+// those files read as they always have
+fn text_encoding_field(text_encoding: &str) -> Option<String> {
+    (text_encoding != HEX_ENCODING).then(|| text_encoding.to_string())
+}
+
+// Load a JSON signature file, pointing at --raw when it holds something else
+fn load_json_signature(path: &Path) -> Result<(String, Vec<u8>, SignatureInfo), CliError> {
+    match load_signature_with_info(path) {
+        Err(StorageError::Json(_)) => Err(usage(format!(
+            "{:?} is not a JSON signature file; pass --raw --scheme <scheme> to verify a raw signature", path
+        ))),
+        result => Ok(result.map_err(|source| CliError::SignatureFile { path: path.to_path_buf(), source })?),
+    }
+}
+
+// Helper to get message from either a string or a file
+fn get_message(message_str: Option<String>, message_file: Option<PathBuf>) -> Result<Vec<u8>, CliError> {
+    read_message(message_str, message_file, false)
+}
+
+// get_message for sign and verify, which show a progress bar on stderr while reading a
+// file over PROGRESS_THRESHOLD, when stderr is a terminal
+fn read_message(message_str: Option<String>, message_file: Option<PathBuf>, progress: bool) -> Result<Vec<u8>, CliError> {
+    match (message_str, message_file) {
+        (Some(msg), None) => Ok(msg.into_bytes()),
+        // A file of "-" is stdin, read byte for byte to the end
+        (None, Some(file)) if file.as_os_str() == "-" => read_stdin(),
+        (None, Some(file)) if progress && std::io::stderr().is_terminal() => read_with_progress(&file),
+        (None, Some(file)) => Ok(read_file(file)?),
+        (None, None) => Err(usage("Either message or file must be specified")),
+        (Some(_), Some(_)) => Err(usage("Cannot specify both message and file")),
+    }
+}
+
+// All of stdin, warning once it grows past STDIN_WARNING_BYTES as there is no telling how
+// much more a pipe holds
+fn read_stdin() -> Result<Vec<u8>, CliError> {
+    let mut stdin = std::io::stdin().lock();
+    let mut msg = Vec::new();
+    let mut chunk = vec![0; 1024 * 1024];
+    let mut warned = false;
+    loop {
+        let read = stdin.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        msg.extend_from_slice(&chunk[..read]);
+        if !warned && msg.len() > STDIN_WARNING_BYTES {
+            eprintln!("Warning: holding over {} MiB of stdin in memory; sign and verify --prehash hash it as it is read instead", 
+                      STDIN_WARNING_BYTES / (1024 * 1024));
+            warned = true;
+        }
+    }
+    
+    Ok(msg)
+}
+
+fn read_with_progress(path: &Path) -> Result<Vec<u8>, CliError> {
+    let mut file = open_file(path)?;
+    let len = file.metadata().map_err(read_error(path))?.len();
+    if len <= PROGRESS_THRESHOLD {
+        return read_file(path);
+    }
+    
+    let bar = progress_bar(path, len);
+    let mut msg = Vec::with_capacity(len as usize);
+    let mut chunk = vec![0; 1024 * 1024];
+    loop {
+        let read = file.read(&mut chunk).map_err(read_error(path))?;
+        if read == 0 {
+            break;
+        }
+        msg.extend_from_slice(&chunk[..read]);
+        bar.inc(read as u64);
+    }
+    bar.finish_and_clear();
+    Ok(msg)
+}
+
+fn progress_bar(path: &Path, len: u64) -> ProgressBar {
+    ProgressBar::new(len).with_style(
+        ProgressStyle::with_template("{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+            .expect("the progress bar template is valid")
+            .progress_chars("=> "),
+    ).with_message(format!("Reading {}", path.display()))
+}
+
+// A message's digest under each algorithm asked for, and its length
+type MessageDigests<const N: usize> = ([Vec<u8>; N], u64);
+
+// Hash the message with each of the --prehash algorithms as it is read, through one
+// fixed-size buffer, so a large file or an endless pipe on stdin is never held in memory.
+// The progress bar is shown as read_message shows it.
+fn hash_message<const N: usize>(
+    message_str: Option<String>,
+    message_file: Option<PathBuf>,
+    algorithms: [&str; N],
+    progress: bool,
+) -> Result<MessageDigests<N>, CliError> {
+    let mut hashers = Vec::with_capacity(N);
+    for algorithm in algorithms {
+        hashers.push(Prehasher::new(algorithm).ok_or_else(|| usage(format!("Unsupported --prehash {}", algorithm)))?);
+    }
+    let (mut reader, bar): (Box<dyn Read>, Option<ProgressBar>) = match (message_str, message_file) {
+        (Some(msg), None) => (Box::new(std::io::Cursor::new(msg.into_bytes())), None),
+        (None, Some(file)) if file.as_os_str() == "-" => (Box::new(std::io::stdin().lock()), None),
+        (None, Some(file)) => {
+            let opened = open_file(&file)?;
+            let len = opened.metadata().map_err(read_error(&file))?.len();
+            let bar = if progress && len > PROGRESS_THRESHOLD && std::io::stderr().is_terminal() {
+                Some(progress_bar(&file, len))
+            } else {
+                None
+            };
+            (Box::new(opened), bar)
+        }
+        (None, None) => return Err(usage("Either message or file must be specified")),
+        (Some(_), Some(_)) => return Err(usage("Cannot specify both message and file")),
+    };
+    
+    let len = hash_stream(&mut reader, &mut hashers, bar.as_ref())?;
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
+    
+    let digests: Vec<Vec<u8>> = hashers.into_iter().map(Prehasher::finalize).collect();
+    Ok((digests.try_into().expect("one digest per algorithm"), len))
+}
+
+// Feed everything a reader holds to the hashers through a single 1 MiB buffer, returning
+// how many bytes there were
+fn hash_stream(reader: &mut dyn Read, hashers: &mut [Prehasher], bar: Option<&ProgressBar>) -> std::io::Result<u64> {
+    let mut chunk = vec![0; HASH_CHUNK_BYTES];
+    let mut len = 0;
+    loop {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        len += read as u64;
+        for hasher in hashers.iter_mut() {
+            hasher.update(&chunk[..read]);
+        }
+        if let Some(bar) = bar {
+            bar.inc(read as u64);
+        }
+    }
+    
+    Ok(len)
+}
+
+// Helper to parse a --digest argument: exactly 32 bytes of hex, as produced by SHA-256
+fn parse_digest(digest: &str) -> Result<Vec<u8>, CliError> {
+    let bytes = hex::decode(digest.trim_start_matches("0x"))
+        .map_err(|e| usage(format!("Invalid --digest hex: {}", e)))?;
+    if bytes.len() != 32 {
+        return Err(usage(format!("--digest must be a 32-byte SHA-256 digest, got {} bytes", bytes.len())));
+    }
+    
+    Ok(bytes)
+}
+
+// Parse --extra-entropy: 32 bytes of hex, or "random" to draw them from the OS
+fn parse_extra_entropy(extra_entropy: &str) -> Result<[u8; 32], CliError> {
+    let mut bytes = [0u8; 32];
+    if extra_entropy == "random" {
+        OsRng.fill_bytes(&mut bytes);
+        return Ok(bytes);
+    }
+    
+    let decoded = hex::decode(extra_entropy.trim_start_matches("0x"))
+        .map_err(|e| usage(format!("Invalid --extra-entropy hex: {}", e)))?;
+    if decoded.len() != 32 {
+        return Err(usage(format!("--extra-entropy must be 32 bytes or \"random\", got {} bytes", decoded.len())));
+    }
+    bytes.copy_from_slice(&decoded);
+    Ok(bytes)
+}
+
+// A signer's key name paired with the message it signed
+type SignerMessage = (String, Vec<u8>);
+
+// Helper to load the (key, message) pairs of a --messages-file, keeping file order
+fn load_message_entries(path: &PathBuf) -> Result<Vec<SignerMessage>, CliError> {
+    #[derive(serde::Deserialize)]
+    struct MessageEntry {
+        key: String,
+        message: String,
+    }
+    
+    let entries: Vec<MessageEntry> = serde_json::from_reader(open_file(path)?)?;
+    
+    let mut result = Vec::new();
+    for entry in entries {
+        let msg = message_from_spec(&entry.message)?;
+        result.push((entry.key, msg));
+    }
+    
+    Ok(result)
+}
+
+// A message as --messages-file and --mapping entries give it: "@path" reads it from a file,
+// "hex:" prefixes its bytes in hex, and anything else is the literal message
+fn message_from_spec(spec: &str) -> Result<Vec<u8>, CliError> {
+    if let Some(message_path) = spec.strip_prefix('@') {
+        return read_file(message_path);
+    }
+    match spec.strip_prefix("hex:") {
+        Some(message_hex) => hex::decode(message_hex).map_err(|e| usage(format!("Invalid hex message {:?}: {}", spec, e))),
+        None => Ok(spec.as_bytes().to_vec()),
+    }
+}
+
+// A signature file paired with the message spec an aggregate --mapping gives it
+type MappedSignature = (PathBuf, String);
+
+// Load an aggregate --mapping in file order. Signatures given with --signatures as well
+// must be the mapped ones in the same order.
+fn load_mapping(path: &Path, signatures: &[PathBuf]) -> Result<Vec<MappedSignature>, CliError> {
+    #[derive(serde::Deserialize)]
+    struct MappingEntry {
+        signature: PathBuf,
+        message: String,
+    }
+    
+    let entries: Vec<MappingEntry> = serde_json::from_reader(open_file(path)?)
+        .map_err(|e| usage(format!("Invalid mapping {:?}: {}", path, e)))?;
+    if !signatures.is_empty() {
+        if signatures.len() != entries.len() {
+            return Err(usage(format!("--signatures lists {} files but {:?} maps {}", signatures.len(), path, entries.len())));
+        }
+        for (i, (sig_path, entry)) in signatures.iter().zip(&entries).enumerate() {
+            if !same_file(sig_path, &entry.signature) {
+                return Err(usage(format!("Entry {} of {:?} maps {:?} but --signatures lists {:?} at that position", 
+                                        i, path, entry.signature, sig_path)));
+            }
+        }
+    }
+    Ok(entries.into_iter().map(|entry| (entry.signature, entry.message)).collect())
+}
+
+// Whether two paths name the same file, however they are spelled
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+// The fingerprint and hex public key of the key that made a signature file, which must be
+// a keystore key: the file records the fingerprint, and any public key it embeds must be
+// that key's
+fn signer_of(keystore: &KeyStore, sig_path: &Path) -> Result<(String, String), CliError> {
+    let (_, _, info) = read_signature_file(sig_path)?;
+    let Some(fingerprint) = info.key_fingerprint else {
+        return Err(usage(format!("Signature {:?} does not record the key that made it; re-sign it to aggregate it with --mapping", sig_path)));
+    };
+    let Some(key_entry) = key_with_fingerprint(keystore, &fingerprint)? else {
+        return Err(CliError::KeyNotFound { key: fingerprint });
+    };
+    if let Some(embedded) = &info.public_key {
+        if !embedded.eq_ignore_ascii_case(&key_entry.public_key) {
+            return Err(CliError::VerificationFailed(format!(
+                "Signature {:?} embeds a public key other than that of key {} ({}), whose fingerprint it records", 
+                sig_path, key_entry.metadata.name, fingerprint
+            )));
+        }
+    }
+    Ok((fingerprint, key_entry.public_key))
+}
+
+// The keystore key of a mapped aggregate's signer, found by the fingerprint it records and
+// checked against the public key it embeds, which is never used itself
+fn mapped_signer_key(keystore: &KeyStore, signer: &AggregateSigner, key_scheme: &str) -> Result<Participant, CliError> {
+    let Some(key_entry) = key_with_fingerprint(keystore, &signer.fingerprint)? else {
+        return Err(CliError::KeyNotFound { key: signer.fingerprint.clone() });
+    };
+    if key_entry.metadata.scheme != key_scheme {
+        return Err(StorageError::WrongScheme(key_entry.metadata.name, key_entry.metadata.scheme, key_scheme.to_string()).into());
+    }
+    if !signer.public_key.eq_ignore_ascii_case(&key_entry.public_key) {
+        return Err(CliError::VerificationFailed(format!(
+            "The aggregate records a different public key for signer {} than key {} in the keystore", 
+            signer.fingerprint, key_entry.metadata.name
+        )));
+    }
+    let pk_bytes = hex::decode(&key_entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    Ok((key_entry.metadata.name, pk_bytes))
+}
+
+// The messages of a mapped aggregate's signers, each checked against its recorded SHA-256
+fn mapped_messages(signers: &[AggregateSigner]) -> Result<Vec<SignerMessage>, CliError> {
+    let mut result = Vec::new();
+    for (i, signer) in signers.iter().enumerate() {
+        let msg = message_from_spec(&signer.message)?;
+        let message_sha256 = hex::encode(Sha256::digest(&msg));
+        if !message_sha256.eq_ignore_ascii_case(&signer.message_sha256) {
+            return Err(CliError::VerificationFailed(format!(
+                "Message {} ({}) has SHA-256 {} but the aggregate records {}; it changed since aggregation", 
+                i, signer.message, message_sha256, signer.message_sha256
+            )));
+        }
+        result.push((signer.fingerprint.clone(), msg));
+    }
+    Ok(result)
+}
+
+// A verify-batch entry, with the DST its signature was made under
+struct BatchItem {
+    scheme: String,
+    dst: String,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+// Helper to batch-verify the BLS entries at `indices`, all of one scheme and DST, returning
+// the indices of invalid ones. A single pairing check covers the whole batch; only when it
+// fails is each entry checked on its own to find the culprits.
+fn verify_bls_batch(scheme: &str, dst: &[u8], items: &[BatchItem], indices: &[usize]) -> Result<Vec<usize>, CliError> {
+    let bls = bls_scheme(scheme)?;
+    let public_keys: Vec<Vec<u8>> = indices.iter().map(|&i| items[i].public_key.clone()).collect();
+    let messages: Vec<&[u8]> = indices.iter().map(|&i| items[i].message.as_slice()).collect();
+    let signatures: Vec<Vec<u8>> = indices.iter().map(|&i| items[i].signature.clone()).collect();
+    
+    let mut invalid = Vec::new();
+    if !bls.verify_batch(&public_keys, &messages, &signatures, dst, &mut OsRng)? {
+        for (n, &i) in indices.iter().enumerate() {
+            if !bls.verify_with_dst(&public_keys[n], messages[n], &signatures[n], dst, false)? {
+                invalid.push(i);
+            }
+        }
+    }
+    
+    Ok(invalid)
+}
+
+// Helper to verify a single BLS signature of either ciphersuite
+fn verify_bls_signature(scheme: &str, pk_bytes: &[u8], msg: &[u8], sig_bytes: &[u8], dst: &[u8], aug: bool) -> Result<bool, CliError> {
+    Ok(bls_scheme(scheme)?.verify_with_dst(pk_bytes, msg, sig_bytes, dst, aug)?)
+}
+
+// The aggregation and DST operations of a BLS scheme, refusing every other scheme
+fn bls_scheme(scheme: &str) -> Result<&'static dyn Aggregatable, CliError> {
+    registered_scheme(scheme)?
+        .as_aggregatable()
+        .ok_or_else(|| usage(format!("Only BLS signatures can be used here, not {}", scheme)))
+}
+
+// The signature files aggregate --signatures names: each directory stands for the *.sig
+// files in it and each glob pattern for its matches, both in path order, so the
+// result does not depend on the shell. A pattern or directory matching nothing is an error.
+fn expand_signature_paths(args: &[PathBuf]) -> Result<Vec<PathBuf>, CliError> {
+    let mut paths = Vec::new();
+    for arg in args {
+        let arg_str = arg.to_string_lossy();
+        let matched = if arg.is_dir() {
+            let mut matched = Vec::new();
+            for entry in fs::read_dir(arg).map_err(read_error(arg))? {
+                let entry = entry.map_err(read_error(arg))?;
+                if entry.file_type().map_err(read_error(&entry.path()))?.is_file() && entry.path().extension().is_some_and(|extension| extension == "sig") {
+                    matched.push(entry.path());
+                }
+            }
+            matched.sort();
+            matched
+        } else if !arg.exists() && arg_str.contains(['*', '?', '[']) {
+            let mut matched = glob::glob(&arg_str)
+                .map_err(|e| usage(format!("Invalid pattern {}: {}", arg_str, e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| CliError::Io { path: Some(e.path().to_path_buf()), source: e.into_error() })?;
+            matched.retain(|path| path.is_file());
+            matched.sort();
+            matched
+        } else {
+            vec![arg.clone()]
+        };
+        if matched.is_empty() {
+            return Err(usage(format!("No signature files match {}", arg_str)));
+        }
+        paths.extend(matched);
+    }
+    Ok(paths)
+}
+
+// Signatures ready for aggregation: their shared scheme and DST, and the raw signature bytes
+type AggregationInputs = (String, String, Vec<Vec<u8>>);
+
+// Helper to load signature files for aggregation. All of them must share the scheme and
+// DST of the first one.
+fn load_aggregation_inputs(paths: &[PathBuf]) -> Result<AggregationInputs, CliError> {
+    let mut loaded = Vec::new();
+    
+    for sig_path in paths {
+        let (scheme, sig_bytes, info) = read_signature_file(sig_path)?;
+        // Files without a recorded DST were made under the scheme default
+        let stored_dst = info.dst.unwrap_or_else(|| default_dst(&scheme));
+        loaded.push((scheme, sig_bytes, stored_dst));
+    }
+    
+    let (scheme, agg_dst) = match loaded.first() {
+        Some((scheme, _, stored_dst)) => (scheme.clone(), stored_dst.clone()),
+        None => return Err(usage("No signatures to aggregate")),
+    };
+    
+    let mut signatures = Vec::new();
+    for (sig_path, (other, sig_bytes, other_dst)) in paths.iter().zip(loaded) {
+        if other != scheme {
+            return Err(usage(format!("Cannot aggregate {} signatures with {} signatures ({:?})", scheme, other, sig_path)));
+        }
+        if other_dst != agg_dst {
+            return Err(usage(format!("DST mismatch: cannot aggregate signatures made under {:?} and {:?} ({:?})", 
+                                    agg_dst, other_dst, sig_path)));
+        }
+        signatures.push(sig_bytes);
+    }
+    
+    Ok((scheme, agg_dst, signatures))
+}
+
+// Helper to aggregate BLS signatures of either ciphersuite, naming the file of any
+// signature that does not decode
+fn aggregate_bls_signatures(scheme: &str, paths: &[PathBuf], signatures: &[Vec<u8>], encoding: PointEncoding) -> Result<Vec<u8>, CliError> {
+    let unsupported = || CliError::WrongSignatureScheme { expected: "BLS".to_string(), found: scheme.to_string() };
+    let registered = registry::lookup(key_scheme_of(scheme)).ok_or_else(unsupported)?;
+    let aggregatable = registered.as_aggregatable().ok_or_else(unsupported)?;
+    for (sig_path, sig_bytes) in paths.iter().zip(signatures) {
+        registered.check_signature(sig_bytes)
+            .map_err(|e| CliError::SignatureFile { path: sig_path.clone(), source: e.into() })?;
+    }
+    
+    Ok(aggregatable.aggregate_signatures(signatures, encoding)?)
+}
+
+// Helper to verify an aggregated BLS signature against its signers' keys and messages.
+// When everyone signed the same message the public keys can be aggregated, otherwise
+// each (key, message) pair goes into the pairing check.
+fn verify_bls_aggregate(scheme: &str, public_key_bytes: &[Vec<u8>], messages: &[&[u8]], sig_bytes: &[u8], dst: &[u8], aug: bool) -> Result<bool, CliError> {
+    Ok(bls_scheme(scheme)?.aggregate_verify(public_key_bytes, messages, sig_bytes, dst, aug)?)
+}
+
+// A committee member's key name and public key bytes
+type Participant = (String, Vec<u8>);
+
+// Helper to read the participating members out of a committee bitmap. The file only names
+// the members: each one's key comes from the keystore, by name or else by the fingerprint
+// of the key recorded for it, and the file is rejected if it records any other key, so a
+// file cannot pass off keys of its own as the committee's.
+fn committee_participants(keystore: &KeyStore, committee: &Committee, key_scheme: &str) -> Result<Vec<Participant>, CliError> {
+    let bitmap = hex::decode(&committee.bitmap).map_err(|_| StorageError::InvalidFormat("bitmap"))?;
+    if bitmap.len() != committee.members.len().div_ceil(8) {
+        return Err(CliError::VerificationFailed(format!("Committee bitmap has {} bytes but {} members need {}", 
+                                                        bitmap.len(), committee.members.len(), committee.members.len().div_ceil(8))));
+    }
+    
+    let mut members = Vec::new();
+    for member in &committee.members {
+        let recorded = hex::decode(&member.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+        let key_entry = match keystore.read_entry(&member.key) {
+            Ok(key_entry) => key_entry,
+            Err(StorageError::KeyNotFound(_)) => key_with_fingerprint(keystore, &fingerprint_of(key_scheme, &recorded))?
+                .ok_or_else(|| CliError::KeyNotFound { key: member.key.clone() })?,
+            Err(err) => return Err(err.into()),
+        };
+        if key_entry.metadata.scheme != key_scheme {
+            return Err(StorageError::WrongScheme(key_entry.metadata.name, key_entry.metadata.scheme, key_scheme.to_string()).into());
+        }
+        let pk_bytes = hex::decode(&key_entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+        if pk_bytes != recorded {
+            return Err(CliError::VerificationFailed(format!(
+                "The aggregate records a different public key for committee member {} than the keystore holds", member.key
+            )));
+        }
+        members.push((key_entry.metadata.name, pk_bytes));
+    }
+    
+    let mut participants = Vec::new();
+    for i in 0..bitmap.len() * 8 {
+        if bitmap[i / 8] & (1 << (i % 8)) == 0 {
+            continue;
+        }
+        let Some(member) = members.get(i) else {
+            return Err(CliError::VerificationFailed(format!("Committee bitmap marks member {} but the committee has only {} members", 
+                                                            i, members.len())));
+        };
+        participants.push(member.clone());
+    }
+    
+    Ok(participants)
+}
+
+// The keystore key with a fingerprint, if there is one
+fn key_with_fingerprint(keystore: &KeyStore, fingerprint: &str) -> Result<Option<KeyEntry>, StorageError> {
+    match keystore.list_keys()?.keys.into_iter().find(|summary| summary.fingerprint.eq_ignore_ascii_case(fingerprint)) {
+        Some(summary) => Ok(Some(keystore.read_entry(&summary.metadata.name)?)),
+        None => Ok(None),
+    }
+}
+
+// Helper to require a valid <key>.pop file in a proof-of-possession directory
+fn check_pop_file(pop_dir: &std::path::Path, key_name: &str, key_scheme: &str, pk_bytes: &[u8]) -> Result<(), CliError> {
+    let pop_path = pop_dir.join(format!("{}.pop", key_name));
+    if !pop_path.exists() {
+        return Err(CliError::VerificationFailed(format!("No proof of possession for key {} in {:?}", key_name, pop_dir)));
+    }
+    
+    let (pop_scheme, pop_bytes, _) = read_signature_file(pop_path)?;
+    if !verify_pop(key_scheme, pk_bytes, &pop_scheme, &pop_bytes)? {
+        return Err(CliError::VerificationFailed(format!("Invalid proof of possession for key {}", key_name)));
+    }
+    
+    Ok(())
 }
 
-// Helper to get message from either a string or a file
-fn get_message(message_str: Option<String>, message_file: Option<PathBuf>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    match (message_str, message_file) {
-        (Some(msg), None) => Ok(msg.into_bytes()),
-        (None, Some(file)) => Ok(fs::read(file)?),
-        (None, None) => Err("Either message or file must be specified".into()),
-        (Some(_), Some(_)) => Err("Cannot specify both message and file".into()),
+// Helper to check a proof of possession against a BLS public key
+fn verify_pop(key_scheme: &str, pk_bytes: &[u8], pop_scheme: &str, pop_bytes: &[u8]) -> Result<bool, CliError> {
+    let expected = match key_scheme {
+        "BLS12-381-min-pk" => bls::POP_SCHEME,
+        "BLS12-381-min-sig" => bls_min_sig::POP_SCHEME,
+        _ => return Err(usage(format!("Proofs of possession require a BLS key, found: {}", key_scheme))),
+    };
+    
+    if pop_scheme != expected {
+        return Err(CliError::WrongSignatureScheme { expected: expected.into(), found: pop_scheme.into() });
+    }
+    
+    let is_valid = match key_scheme {
+        "BLS12-381-min-pk" => {
+            let public_key = BLS::deserialize_public_key(pk_bytes)?;
+            let pop = BLS::deserialize_signature(pop_bytes)?;
+            BLS::verify_possession(&public_key, &pop)?
+        }
+        _ => {
+            let public_key = BLSMinSig::deserialize_public_key(pk_bytes)?;
+            let pop = BLSMinSig::deserialize_signature(pop_bytes)?;
+            BLSMinSig::verify_possession(&public_key, &pop)?
+        }
+    };
+    
+    Ok(is_valid)
+}
+
+// Ethereum address of a secp256k1 ECDSA keystore key
+fn ethereum_address_of(key_entry: &KeyEntry) -> Result<String, CliError> {
+    let public_key_bytes = hex::decode(&key_entry.public_key)
+        .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    Ok(ECDSA::ethereum_address(&ECDSA::deserialize_public_key(&public_key_bytes)?))
+}
+
+// Bitcoin address a keystore key signs messages for: P2PKH for legacy messages, and
+// P2WPKH (ECDSA) or BIP-86 P2TR (Schnorr) for BIP-322
+fn bitcoin_address_of(key_entry: &KeyEntry, bip322: bool) -> Result<String, CliError> {
+    let public_key_bytes = hex::decode(&key_entry.public_key)
+        .map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    
+    let address = match key_entry.metadata.scheme.as_str() {
+        "ECDSA-secp256k1" => {
+            let public_key = ECDSA::deserialize_public_key(&public_key_bytes)?;
+            if bip322 { bitcoin::p2wpkh_address(&public_key) } else { bitcoin::p2pkh_address(&public_key, true) }
+        }
+        "Schnorr-secp256k1-BIP340" if bip322 => {
+            let internal_key = Schnorr::deserialize_public_key(&public_key_bytes)?;
+            bitcoin::p2tr_address(&Schnorr::taproot_output_key(&internal_key, None)?)
+        }
+        TWEAKED_SCHEME if bip322 => bitcoin::p2tr_address(&Schnorr::deserialize_public_key(&public_key_bytes)?),
+        scheme => return Err(usage(format!("Key {} has no {} address ({} key)", key_entry.metadata.name, 
+                                           if bip322 { "BIP-322" } else { "P2PKH" }, scheme))),
+    };
+    
+    Ok(address)
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// Add a profile, or replace one with the same name
+    Add {
+        /// Name of the profile
+        #[clap(value_parser = parse_profile_name)]
+        name: String,
+        
+        /// Keystore directory of the profile
+        #[clap(long)]
+        path: String,
+        
+        /// Scheme keygen uses in this profile when --scheme is not given
+        #[clap(long, value_parser = keygen_schemes())]
+        scheme: Option<String>,
+    },
+    
+    /// List the configured profiles
+    List,
+    
+    /// Remove a profile (its keystore is left untouched)
+    Remove {
+        /// Name of the profile
+        name: String,
+    },
+}
+
+// The audit log record of a command, naming the key and output it was given. Commands that
+// neither sign nor change keys are not logged.
+fn audit_record(command: &Commands) -> Option<AuditRecord> {
+    let (name, key, output): (&str, Option<String>, Option<&Path>) = match command {
+        Commands::KeyGen { no_save: true, .. } => return None,
+        Commands::KeyGen { name, .. } => ("keygen", name.clone(), None),
+        Commands::SetExpiry { key, .. } => ("set-expiry", Some(key.clone()), None),
+        Commands::Tag { key, .. } => ("tag", Some(key.clone()), None),
+        Commands::DeleteKey { key, .. } => ("delete-key", Some(key.clone()), None),
+        Commands::RenameKey { from, .. } => ("rename-key", Some(from.clone()), None),
+        Commands::ExportKey { key, output, .. } => ("export-key", Some(key.clone()), Some(output)),
+        Commands::ImportKey { name, .. } => ("import-key", name.clone(), None),
+        Commands::ImportPublicKey { name, .. } => ("import-public-key", name.clone(), None),
+        Commands::Backup { output, .. } => ("backup", None, Some(output)),
+        Commands::Restore { .. } => ("restore", None, None),
+        Commands::SplitKey { key, output_dir, .. } => ("split-key", Some(key.clone()), Some(output_dir)),
+        Commands::AggregateKeys { save_as, .. } => ("aggregate-keys", Some(save_as.clone()), None),
+        Commands::Tweak { save_as: Some(save_as), .. } => ("tweak", Some(save_as.clone()), None),
+        Commands::Sign { key, private_key, output, ssh_key, .. } => {
+            let key = key.clone()
+                .or_else(|| private_key.as_ref().map(|_| PRIVATE_KEY_NAME.to_string()))
+                .or_else(|| ssh_key.as_ref().map(|query| format!("ssh-agent:{}", query)));
+            ("sign", key, output.as_deref())
+        }
+        Commands::SignShare { output, .. } => ("sign-share", None, Some(output)),
+        Commands::Pop { key, output, .. } => ("pop", Some(key.clone()), output.as_deref()),
+        Commands::Aggregate { output, .. } => ("aggregate", None, Some(output)),
+        Commands::Combine { output, .. } => ("combine", None, Some(output)),
+        Commands::SignTree { key, output, .. } => ("sign-tree", Some(key.clone()), Some(output)),
+        _ => return None,
+    };
+    
+    let mut record = AuditRecord::new(name);
+    record.key = key;
+    record.output = output.map(|output| output.display().to_string());
+    Some(record)
+}
+
+// A header row and one row per key, for list-keys --format table and csv; `created`
+// renders the creation time
+type KeyTable = (Vec<&'static str>, Vec<Vec<String>>);
+
+fn key_table(keystore: &KeyStore, keys: &[KeySummary], addresses: bool, long: bool, created: fn(u64) -> String) -> Result<KeyTable, CliError> {
+    let mut header = vec!["name", "scheme", "created", "fingerprint", "tags"];
+    if long {
+        header.extend(["last_used", "signatures"]);
+    }
+    if addresses {
+        header.push("address");
+    }
+    
+    let mut rows = Vec::new();
+    for KeySummary { metadata: key, fingerprint } in keys {
+        let mut row = vec![key.name.clone(), key.scheme.clone(), created(key.created_at), fingerprint.clone(), key.tags.join(";")];
+        if long {
+            row.push(key.last_used_at.map(created).unwrap_or_default());
+            row.push(key.use_count.to_string());
+        }
+        if addresses {
+            row.push(match key.scheme.as_str() {
+                "ECDSA-secp256k1" => ethereum_address_of(&keystore.read_entry(&key.name)?)?,
+                _ => String::new(),
+            });
+        }
+        rows.push(row);
+    }
+    Ok((header, rows))
+}
+
+// Columns padded to their widest cell, with an upper-case header
+fn format_table((header, rows): &KeyTable) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|name| name.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    
+    let mut table = String::new();
+    let header = header.iter().map(|name| name.to_uppercase().replace('_', " ")).collect::<Vec<_>>();
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+// RFC 4180 CSV: fields holding a comma, quote or line break are quoted, quotes doubled
+fn format_csv((header, rows): &KeyTable) -> String {
+    fn field(cell: &str) -> String {
+        if cell.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    }
+    
+    let header = header.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+    std::iter::once(&header).chain(rows)
+        .map(|row| row.iter().map(|cell| field(cell)).collect::<Vec<_>>().join(",") + "\r\n")
+        .collect()
+}
+
+fn keystore_fingerprint(keystore: &KeyStore, key: &str) -> Option<String> {
+    keystore.read_entry(key).and_then(|entry| entry.fingerprint()).ok()
+}
+
+#[derive(Subcommand)]
+pub enum AuditAction {
+    /// Show logged operations, oldest first
+    Show {
+        /// Only show operations on this key
+        #[clap(long)]
+        key: Option<String>,
+        
+        /// Only show operations since a time: a duration ago (24h, 7d), a date (YYYY-MM-DD, UTC)
+        /// or a Unix time
+        #[clap(long)]
+        since: Option<String>,
+    },
+    
+    /// Check the HMAC chain of the log against --audit-hmac-key
+    Verify,
+}
+
+fn run_audit_action(out: &Output, action: &AuditAction, log: &AuditLog, hmac_key: Option<&[u8]>) -> Result<(), CliError> {
+    match action {
+        AuditAction::Show { key, since } => {
+            let since = since.as_deref().map(parse_since).transpose()?;
+            let records: Vec<AuditRecord> = log.records()?
+                .into_iter()
+                .filter(|record| key.is_none() || record.key == *key)
+                .filter(|record| since.is_none_or(|since| record.timestamp >= since))
+                .collect();
+            infoln!(out, "Found {} audit records:", records.len());
+            if out.json {
+                out.print_json(&serde_json::json!({ "records": serde_json::to_value(&records)? }));
+            }
+            for record in records {
+                let mut details = Vec::new();
+                if let Some(key) = &record.key {
+                    details.push(format!("key: {}", key));
+                }
+                if let Some(fingerprint) = &record.fingerprint {
+                    details.push(format!("fingerprint: {}", fingerprint));
+                }
+                if let Some(digest) = &record.message_sha256 {
+                    details.push(format!("message sha256: {}", digest));
+                }
+                if let Some(output) = &record.output {
+                    details.push(format!("output: {}", output));
+                }
+                let outcome = match &record.error {
+                    _ if record.success => "ok".to_string(),
+                    Some(error) => format!("FAILED: {}", error),
+                    None => "FAILED".to_string(),
+                };
+                let details = if details.is_empty() { String::new() } else { format!(" ({})", details.join(", ")) };
+                outln!(out, "- {} {}{}: {}", format_timestamp(record.timestamp), record.command, details, outcome);
+            }
+        }
+        AuditAction::Verify => {
+            let Some(hmac_key) = hmac_key else {
+                return Err(usage("audit verify needs the --audit-hmac-key the log was written with"));
+            };
+            let count = log.verify(hmac_key)?;
+            infoln!(out, "Audit log verified: the HMAC chain covers all {} records", count);
+            if out.json {
+                out.print_json(&serde_json::json!({ "verified": true, "records": count }));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_profile_action(out: &Output, action: &ProfileAction, profiles_path: &PathBuf) -> Result<(), CliError> {
+    let mut profiles = Profiles::load(profiles_path)?;
+    match action {
+        ProfileAction::Add { name, path, scheme } => {
+            // Relative paths are pinned to where the profile was added; ~ and variables are
+            // kept as written and expanded when the profile is used
+            let path = if paths::expand(path)?.is_absolute() {
+                path.clone()
+            } else {
+                std::env::current_dir()?.join(path).to_string_lossy().into_owned()
+            };
+            let replaced = profiles.profiles.insert(name.clone(), Profile { path: path.clone(), scheme: scheme.clone() });
+            profiles.save(profiles_path)?;
+            infoln!(out, "{} profile {}: {}", if replaced.is_some() { "Updated" } else { "Added" }, name, path);
+            if out.json {
+                out.print_json(&serde_json::json!({ "profile": name, "path": path, "scheme": scheme, "replaced": replaced.is_some() }));
+            }
+        }
+        ProfileAction::List => {
+            infoln!(out, "Found {} profiles:", profiles.profiles.len());
+            if out.json {
+                let listed: serde_json::Map<String, serde_json::Value> = profiles.profiles.iter()
+                    .map(|(name, profile)| (name.clone(), serde_json::json!({ "path": profile.path, "scheme": profile.scheme })))
+                    .collect();
+                out.print_json(&listed.into());
+            }
+            for (name, profile) in &profiles.profiles {
+                match &profile.scheme {
+                    Some(scheme) => outln!(out, "- {} ({}, scheme: {})", name, profile.path, scheme),
+                    None => outln!(out, "- {} ({})", name, profile.path),
+                }
+            }
+        }
+        ProfileAction::Remove { name } => {
+            profiles.get(name)?;
+            profiles.profiles.remove(name);
+            profiles.save(profiles_path)?;
+            infoln!(out, "Removed profile {}", name);
+            if out.json {
+                out.print_json(&serde_json::json!({ "removed": name }));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_profile_name(name: &str) -> Result<String, String> {
+    validate_profile_name(name).map_err(|e| e.to_string())?;
+    Ok(name.to_string())
+}
+
+
+// The keystore to open and what chose it. The command line wins over the environment,
+// which wins over the profiles file.
+fn keystore_location(
+    keystore: Option<&str>,
+    profile: Option<(&str, &Profile)>,
+    env_keystore: Option<String>,
+    profiles: &Profiles,
+    profiles_path: &Path,
+) -> Result<(PathBuf, String), PathError> {
+    Ok(match (keystore, profile, env_keystore, &profiles.keystore) {
+        (Some(keystore), _, _, _) => (paths::expand(keystore)?, "--keystore".to_string()),
+        (None, Some((name, profile)), _, _) => (paths::expand(&profile.path)?, format!("profile {}", name)),
+        (None, None, Some(keystore), _) => (paths::expand(&keystore)?, format!("${}", KEYSTORE_ENV)),
+        (None, None, None, Some(keystore)) => (paths::expand(keystore)?, profiles_path.display().to_string()),
+        (None, None, None, None) => (paths::expand(DEFAULT_KEYSTORE)?, "the default".to_string()),
+    })
+}
+
+// The scheme keygen uses without --scheme, chosen in the same order as the keystore
+fn default_keygen_scheme(profile: Option<&Profile>, env_scheme: Option<String>, profiles: &Profiles) -> Option<String> {
+    profile.and_then(|profile| profile.scheme.clone())
+        .or(env_scheme)
+        .or_else(|| profiles.scheme.clone())
+}
+
+// A default from the environment; a variable set to nothing counts as unset
+fn env_setting(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|value| !value.is_empty())
+}
+
+// Clap parser for path arguments, expanding ~ and environment variables the shell left
+// alone, e.g. in quotes
+fn parse_path(path: &str) -> Result<PathBuf, String> {
+    paths::expand(path).map_err(|e| e.to_string())
+}
+
+// Clap parser for arguments naming a key to create, so bad names fail before any work
+fn parse_key_name(name: &str) -> Result<String, String> {
+    validate_key_name(name).map_err(|e| e.to_string())?;
+    Ok(name.to_string())
+}
+
+// Parse an expiry as a duration from now (30m, 12h, 90d, 2w) or a UTC date (YYYY-MM-DD),
+// returning the Unix time the key expires at
+fn parse_expiry(spec: &str) -> Result<u64, CliError> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    let invalid = |expected| CliError::InvalidValue { argument: "--expires-in", value: spec.to_string(), expected };
+    
+    let expires_at = if spec.split('-').count() == 3 {
+        parse_date(spec).ok_or_else(|| invalid("a real date YYYY-MM-DD"))?
+    } else {
+        parse_duration(spec).and_then(|secs| now.checked_add(secs))
+            .ok_or_else(|| invalid("a duration such as 90d or 12h, or a date YYYY-MM-DD"))?
+    };
+    
+    if expires_at <= now {
+        return Err(usage(format!("Expiry {} is already in the past", format_timestamp(expires_at))));
+    }
+    Ok(expires_at)
+}
+
+// Parse a --since time: a duration before now (24h, 7d), a date YYYY-MM-DD (UTC) or a
+// Unix time
+fn parse_since(spec: &str) -> Result<u64, CliError> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    if let Ok(secs) = spec.parse::<u64>() {
+        return Ok(secs);
+    }
+    parse_date(spec)
+        .or_else(|| parse_duration(spec).map(|secs| now.saturating_sub(secs)))
+        .ok_or_else(|| CliError::InvalidValue {
+            argument: "--since",
+            value: spec.to_string(),
+            expected: "a duration such as 24h or 7d, a date YYYY-MM-DD, or a Unix time",
+        })
+}
+
+// Seconds in a positive duration such as 90d or 12h
+fn parse_duration(spec: &str) -> Option<u64> {
+    let split = spec.len().checked_sub(1)?;
+    let unit = match spec.get(split..)? {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return None,
+    };
+    let count: u64 = spec[..split].parse().ok()?;
+    if count == 0 {
+        return None;
+    }
+    count.checked_mul(unit)
+}
+
+// Unix time of midnight UTC on a date YYYY-MM-DD
+fn parse_date(spec: &str) -> Option<u64> {
+    let [year, month, day] = spec.split('-').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, u32, u32) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    let days = days_from_civil(year, month, day);
+    if (1..=12).contains(&month) && civil_from_days(days) == (year, month, day) && days >= 0 {
+        Some(days as u64 * 86400)
+    } else {
+        None
+    }
+}
+
+// Parse an RFC 3339 time such as 2026-01-31T12:00:00Z into a Unix time
+fn parse_rfc3339(spec: &str) -> Result<u64, CliError> {
+    spec.parse::<jiff::Timestamp>().ok()
+        .and_then(|timestamp| u64::try_from(timestamp.as_second()).ok())
+        .ok_or_else(|| CliError::InvalidValue { argument: "--min-timestamp", value: spec.to_string(), expected: "RFC 3339, such as 2026-01-31T12:00:00Z" })
+}
+
+// Render a Unix time as an RFC 3339 UTC timestamp
+fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let secs_of_day = secs % 86400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, 
+            secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60)
+}
+
+// A Unix time as RFC 3339 in the local time zone, for tables people read; UTC when the
+// zone cannot be determined
+fn format_local_timestamp(secs: u64) -> String {
+    jiff::Timestamp::from_second(secs as i64)
+        .map(|timestamp| timestamp.to_zoned(jiff::tz::TimeZone::system()).strftime("%Y-%m-%dT%H:%M:%S%:z").to_string())
+        .unwrap_or_else(|_| format_timestamp(secs))
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Inverse of days_from_civil
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Read a hex-encoded seed, as written by e.g. `openssl rand -hex 32`
+fn read_seed_file(seed_file: &PathBuf) -> Result<Vec<u8>, CliError> {
+    hex::decode(read_text_file(seed_file)?.trim())
+        .map_err(|_| usage(format!("Seed file {:?} must contain a hex-encoded seed", seed_file)))
+}
+
+// A key pair outside the keystore: its scheme name and serialized private and public keys
+type Keypair = (&'static str, Vec<u8>, Vec<u8>);
+
+// A key pair generated deterministically from a seed file
+fn seeded_keypair(seed_file: &PathBuf, scheme: &str) -> Result<Keypair, CliError> {
+    let seed = read_seed_file(seed_file)?;
+    if seed.len() < 32 {
+        return Err(usage(format!("Seed file {:?} holds {} bytes; at least 32 bytes of entropy are required", 
+                                seed_file, seed.len())));
+    }
+    
+    // Every scheme draws its randomness from a ChaCha20 RNG seeded from the seed file
+    let Some(registered) = registry::by_keygen_name(scheme) else {
+        return Err(StorageError::UnsupportedScheme(scheme.to_string()).into());
+    };
+    let mut rng = registry::seeded_rng(&seed, registered.name())?;
+    let (private_key, public_key) = registered.generate_keypair_with_rng(&mut rng)?;
+    Ok((registered.name(), private_key, public_key))
+}
+
+// The key pair keygen --no-save prints: derived, generated from a seed file or random
+fn unsaved_keypair(
+    out: &Output,
+    scheme: &str,
+    derive: bool,
+    seed_file: Option<&PathBuf>,
+    path: Option<&str>,
+) -> Result<Keypair, CliError> {
+    if let (true, Some(seed_file), Some(path)) = (derive, seed_file, path) {
+        if scheme != "bls" {
+            return Err(usage(format!("--derive is only supported for bls keys, not {}", scheme)));
+        }
+        let (private_key, public_key) = BLS::derive_eip2333(&read_seed_file(seed_file)?, path)?;
+        return Ok((BLS::name(), BLS::serialize_private_key(&private_key)?, BLS::serialize_public_key(&public_key)?));
+    }
+    if let Some(seed_file) = seed_file {
+        return seeded_keypair(seed_file, scheme);
+    }
+    
+    let Some(registered) = registry::by_keygen_name(scheme) else {
+        return Err(StorageError::UnsupportedScheme(scheme.to_string()).into());
+    };
+    if registered.name() == RsaPss::name() {
+        infoln!(out, "Generating {}-bit RSA key, this may take a while...", RSA_KEY_BITS);
+    }
+    let (private_key, public_key) = registered.generate_keypair()?;
+    Ok((registered.name(), private_key, public_key))
+}
+
+// A key a command saved, as --json reports it
+fn key_json(out: &Output, entry: &KeyEntry) -> Result<serde_json::Value, CliError> {
+    let public_key = hex::decode(&entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+    Ok(serde_json::json!({
+        "name": entry.metadata.name,
+        "scheme": entry.metadata.scheme,
+        "public_key": encode_text(out.text_encoding, &public_key),
+        "fingerprint": entry.fingerprint()?,
+    }))
+}
+
+// Print a keygen --no-save key pair in --text-encoding; the private key only when asked for
+fn print_unsaved_keypair(out: &Output, (scheme, private_key, public_key): Keypair, show_private: bool) {
+    let fingerprint = fingerprint_of(scheme, &public_key);
+    let private_key = show_private.then(|| encode_text(out.text_encoding, &private_key));
+    if out.json {
+        let mut keypair = serde_json::json!({
+            "scheme": scheme,
+            "public_key": encode_text(out.text_encoding, &public_key),
+            "fingerprint": fingerprint,
+        });
+        if let Some(private_key) = private_key {
+            keypair["private_key"] = private_key.into();
+        }
+        out.print_json(&keypair);
+        return;
+    }
+    
+    infoln!(out, "Generated {} key pair (not saved)", scheme);
+    out.result("Public key", &encode_text(out.text_encoding, &public_key));
+    if let Some(private_key) = private_key {
+        out.result("Private key", &private_key);
+    }
+    infoln!(out, "Fingerprint: {}", fingerprint);
+}
+
+// The passphrase of an export or backup: from --passphrase-file, else SIG_TOOL_PASSPHRASE,
+// else typed at the terminal, twice when `confirm` so a typo cannot lock the keys away.
+// Prompts read the terminal itself, leaving stdin to piped input.
+fn get_passphrase(passphrase_file: Option<&PathBuf>, confirm: bool) -> Result<Vec<u8>, CliError> {
+    if let Some(path) = passphrase_file {
+        return read_passphrase_file(path);
+    }
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if passphrase.is_empty() => return Err(usage(format!("{} is set but empty", PASSPHRASE_ENV))),
+        Ok(passphrase) => return Ok(passphrase.into_bytes()),
+        Err(std::env::VarError::NotUnicode(_)) => return Err(usage(format!("{} is not valid UTF-8", PASSPHRASE_ENV))),
+        Err(std::env::VarError::NotPresent) => {}
+    }
+    
+    let no_terminal = |e: std::io::Error| {
+        usage(format!("No terminal to prompt for the passphrase on ({}); pass --passphrase-file or set {}", e, PASSPHRASE_ENV))
+    };
+    let passphrase = rpassword::prompt_password("Passphrase: ").map_err(no_terminal)?;
+    if passphrase.is_empty() {
+        return Err(usage("The passphrase is empty"));
+    }
+    if confirm && rpassword::prompt_password("Repeat passphrase: ").map_err(no_terminal)? != passphrase {
+        return Err(usage("The passphrases do not match"));
+    }
+    Ok(passphrase.into_bytes())
+}
+
+// Read a passphrase from a file, ignoring the trailing newline editors add
+fn read_passphrase_file(path: &PathBuf) -> Result<Vec<u8>, CliError> {
+    let contents = read_file(path)?;
+    let passphrase = contents.strip_suffix(b"\n").unwrap_or(&contents);
+    let passphrase = passphrase.strip_suffix(b"\r").unwrap_or(passphrase);
+    if passphrase.is_empty() {
+        return Err(usage(format!("Passphrase file {:?} is empty", path)));
+    }
+    
+    Ok(passphrase.to_vec())
+}
+
+// Check that a private and public key of the given scheme parse and belong together by
+// signing and verifying a probe message with them
+fn check_key_pair(scheme: &str, private_key: &[u8], public_key: &[u8]) -> Result<(), CliError> {
+    const PROBE: &[u8] = b"sig-tool key pair check";
+    let registered = registered_scheme(scheme)?;
+    let signature = registered.sign_bytes(private_key, PROBE)?;
+    let matches = registered.verify_bytes(public_key, PROBE, &signature)?;
+    if !matches {
+        return Err(CliError::KeyMismatch(format!("The {} private key does not match its public key", scheme)));
+    }
+    
+    Ok(())
+}
+
+// A key entry for the hex private key in an environment variable, with its public key
+// derived from it. Errors name the variable but never repeat what it holds.
+fn env_key_entry(var: &str, scheme: &str) -> Result<KeyEntry, CliError> {
+    let value = match std::env::var(var) {
+        Ok(value) => value,
+        Err(std::env::VarError::NotPresent) => return Err(usage(format!("Environment variable {} is not set", var))),
+        Err(std::env::VarError::NotUnicode(_)) => return Err(usage(format!("Environment variable {} is not valid UTF-8", var))),
+    };
+    hex_key_entry(&format!("{}{}", ENV_KEY_PREFIX, var), &format!("Environment variable {}", var), &value, scheme)
+}
+
+// A key entry for sign --private-key: the hex key itself, or @path for a file holding it
+fn private_key_entry(private_key: &str, scheme: &str) -> Result<KeyEntry, CliError> {
+    match private_key.strip_prefix('@') {
+        Some(path) => {
+            let value = read_text_file(path)?;
+            hex_key_entry(PRIVATE_KEY_NAME, &format!("The --private-key file {}", path), &value, scheme)
+        }
+        None => hex_key_entry(PRIVATE_KEY_NAME, PRIVATE_KEY_NAME, private_key, scheme),
+    }
+}
+
+// A key entry named `name` for a hex private key of a keygen scheme, with its public key
+// derived from it. Errors say where the key came from (`source`) but never what it is.
+fn hex_key_entry(name: &str, source: &str, value: &str, scheme: &str) -> Result<KeyEntry, CliError> {
+    let scheme_name = match scheme {
+        "bls" => BLS::name(),
+        "bls-min-sig" => BLSMinSig::name(),
+        _ => ECDSA::name(),
+    };
+    let invalid = || CliError::InvalidPrivateKey { origin: source.to_string(), scheme: scheme_name };
+    
+    let private_key = hex::decode(value.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+    let public_key = match scheme {
+        "bls" => {
+            let private_key = BLS::deserialize_private_key(&private_key).map_err(|_| invalid())?;
+            BLS::serialize_public_key(&BLS::public_key(&private_key))?
+        }
+        "bls-min-sig" => {
+            let private_key = BLSMinSig::deserialize_private_key(&private_key).map_err(|_| invalid())?;
+            BLSMinSig::serialize_public_key(&BLSMinSig::public_key(&private_key))?
+        }
+        _ => {
+            let private_key = ECDSA::deserialize_private_key(&private_key).map_err(|_| invalid())?;
+            ECDSA::serialize_public_key(private_key.verifying_key())?
+        }
+    };
+    
+    Ok(KeyEntry {
+        version: CURRENT_VERSION,
+        metadata: KeyMetadata::new(name, scheme_name),
+        private_key: hex::encode(&private_key),
+        public_key: hex::encode(&public_key),
+    })
+}
+
+// A key entry for verify --public-key, which has no keystore metadata beyond the scheme.
+// Keys that do not parse are reported under `name` with the length the scheme expects.
+fn public_key_entry(name: &str, public_key: &str, scheme: &str) -> Result<KeyEntry, CliError> {
+    let bytes = hex::decode(public_key.trim().trim_start_matches("0x"))
+        .map_err(|e| usage(format!("{} is not valid hex: {}", name, e)))?;
+    let registered = registered_scheme(scheme)?;
+    registered.check_public_key(&bytes).map_err(|e| {
+        usage(format!("{} is not a valid {} public key: got {} bytes, expected {} ({})", name, scheme, bytes.len(), registered.public_key_lengths(), e))
+    })?;
+
+    Ok(KeyEntry {
+        version: CURRENT_VERSION,
+        metadata: KeyMetadata::new(PUBLIC_KEY_NAME, scheme),
+        private_key: String::new(),
+        public_key: hex::encode(&bytes),
+    })
+}
+
+// The scheme name keys and signature files record for a keygen --scheme value
+fn scheme_name_of(keygen_scheme: &str) -> Option<&'static str> {
+    registry::by_keygen_name(keygen_scheme).map(|registered| registered.name())
+}
+
+// The registered scheme of a name recorded in a key entry or signature file
+fn registered_scheme(scheme: &str) -> Result<&'static dyn DynScheme, CliError> {
+    registry::lookup(scheme).ok_or_else(|| StorageError::UnsupportedScheme(scheme.to_string()).into())
+}
+
+// Map the scheme recorded in a signature file to the scheme of the key that produced it
+fn key_scheme_of(signature_scheme: &str) -> &str {
+    match signature_scheme {
+        KECCAK256_SCHEME | RECOVERABLE_SCHEME | KECCAK256_RECOVERABLE_SCHEME | EIP191_SCHEME
+        | BITCOIN_MESSAGE_SCHEME => "ECDSA-secp256k1",
+        SSH_AGENT_SCHEME => "Ed25519",
+        bls::AUG_SCHEME => "BLS12-381-min-pk",
+        other => other,
+    }
+}
+
+// Whether a key or signature scheme is one of the BLS ciphersuites
+fn is_bls_scheme(scheme: &str) -> bool {
+    registry::lookup(scheme).is_some_and(|registered| registered.as_aggregatable().is_some())
+}
+
+// Default DST of a BLS scheme, as recorded in signature files
+fn default_dst(scheme: &str) -> String {
+    // Other schemes have no DST
+    let dst = registry::lookup(key_scheme_of(scheme.trim_end_matches("-aggregated")))
+        .and_then(|registered| registered.as_aggregatable())
+        .map_or(&b""[..], |bls| bls.default_dst());
+    String::from_utf8_lossy(dst).into_owned()
+}
+
+// Turn a --ciphersuite preset into the DST it stands for; without one, --dst is used as given
+fn preset_dst(scheme: &str, dst: Option<String>, ciphersuite: Option<&str>) -> Result<Option<String>, CliError> {
+    let Some(name) = ciphersuite else {
+        return Ok(dst);
+    };
+    
+    if key_scheme_of(scheme.trim_end_matches("-aggregated")) != "BLS12-381-min-pk" {
+        return Err(usage(format!("Ciphersuite presets are only defined for BLS12-381-min-pk, not {}", scheme)));
+    }
+    
+    Ok(Some(String::from_utf8_lossy(bls::ciphersuite_dst(name)?).into_owned()))
+}
+
+// Parse --encoding; only BLS points have an uncompressed form
+fn point_encoding(scheme: &str, encoding: Option<&str>) -> Result<PointEncoding, CliError> {
+    match encoding {
+        None | Some("compressed") => Ok(PointEncoding::Compressed),
+        Some(_) if !scheme.starts_with("BLS12-381-") => {
+            Err(usage(format!("--encoding uncompressed is only supported for BLS keys, not {}", scheme)))
+        }
+        Some(_) => Ok(PointEncoding::Uncompressed),
+    }
+}
+
+// Pick the DST to verify under: --dst wins, then the DST recorded with the signature,
+// then the scheme default. A flag that contradicts the recorded DST is reported, since
+// the signature will not verify under it.
+fn resolve_dst(scheme: &str, flag: Option<String>, stored: Option<String>) -> String {
+    match (flag, stored) {
+        (Some(flag), Some(stored)) if flag != stored => {
+            eprintln!("Warning: DST mismatch: signature was made under {:?} but verifying under {:?}", stored, flag);
+            flag
+        }
+        (Some(flag), _) => flag,
+        (None, Some(stored)) => stored,
+        (None, None) => default_dst(scheme),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_entry(scheme: &str, public_key: &[u8]) -> KeyEntry {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "scheme": scheme, "created_at": 0, "name": "test" },
+            "private_key": "",
+            "public_key": hex::encode(public_key),
+        }))
+        .unwrap()
+    }
+
+    // The well-known addresses of the secp256k1 private keys 1 and 2, in EIP-55 casing
+    #[test]
+    fn ethereum_address_of_uses_eip55_casing() {
+        for (scalar, address) in [
+            (1u8, "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+            (2u8, "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF"),
+        ] {
+            let mut private_key = [0u8; 32];
+            private_key[31] = scalar;
+            let private_key = k256::ecdsa::SigningKey::from_slice(&private_key).unwrap();
+            for compressed in [true, false] {
+                let public_key = private_key.verifying_key().to_encoded_point(compressed);
+                let entry = key_entry("ECDSA-secp256k1", public_key.as_bytes());
+                assert_eq!(ethereum_address_of(&entry).unwrap(), address);
+            }
+        }
     }
-}
\ No newline at end of file
+
+    // A scheme without BLS operations is an error, never checked as either ciphersuite
+    #[test]
+    fn bls_helpers_refuse_other_schemes() {
+        let (private_key, public_key) = BLSMinSig::generate_keypair().unwrap();
+        let public_key = BLSMinSig::serialize_public_key(&public_key).unwrap();
+        let signature = BLSMinSig::serialize_signature(&BLSMinSig::sign(&private_key, b"epoch 7").unwrap()).unwrap();
+        let dst = bls_min_sig::DST;
+        assert!(verify_bls_signature(BLSMinSig::name(), &public_key, b"epoch 7", &signature, dst, false).unwrap());
+
+        for scheme in ["Ed25519", "BLS12-381-min-sig-pop", "BLS12-381-unknown"] {
+            assert!(verify_bls_signature(scheme, &public_key, b"epoch 7", &signature, dst, false).is_err(), "{}", scheme);
+            assert!(verify_bls_aggregate(scheme, std::slice::from_ref(&public_key), &[b"epoch 7"], &signature, dst, false).is_err(), "{}", scheme);
+            let items = [BatchItem {
+                scheme: scheme.to_string(),
+                dst: String::from_utf8_lossy(dst).into_owned(),
+                public_key: public_key.clone(),
+                message: b"epoch 7".to_vec(),
+                signature: signature.clone(),
+            }];
+            assert!(verify_bls_batch(scheme, dst, &items, &[0]).is_err(), "{}", scheme);
+        }
+    }
+
+    #[test]
+    fn parse_duration_and_date() {
+        assert_eq!(parse_duration("90d"), Some(90 * 86400));
+        assert_eq!(parse_duration("12h"), Some(12 * 3600));
+        assert_eq!(parse_duration("2w"), Some(14 * 86400));
+        for invalid in ["", "d", "0d", "12", "12y", "-1d", "1.5h"] {
+            assert_eq!(parse_duration(invalid), None, "{:?}", invalid);
+        }
+
+        assert_eq!(parse_date("2025-12-31"), Some(1767139200));
+        assert_eq!(parse_date("2024-02-29"), Some(1709164800));
+        for invalid in ["2025-02-29", "2025-13-01", "2025-12-32", "25-12-31", "2025-1-31", "1969-12-31"] {
+            assert_eq!(parse_date(invalid), None, "{:?}", invalid);
+        }
+        assert_eq!(format_timestamp(1767139200 + 3661), "2025-12-31T01:01:01Z");
+    }
+
+    #[test]
+    fn parse_expiry_is_in_the_future() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let expires_at = parse_expiry("90d").unwrap();
+        assert!((now + 90 * 86400..=now + 90 * 86400 + 60).contains(&expires_at));
+        assert_eq!(parse_expiry("2999-01-01").unwrap(), parse_date("2999-01-01").unwrap());
+
+        assert!(parse_expiry("2025-12-31").unwrap_err().to_string().contains("already in the past"));
+        assert!(parse_expiry("90 days").is_err());
+        assert!(parse_expiry("2025-02-30").is_err());
+    }
+
+    // Hands out a fixed number of zero bytes, recording the most it was asked for at once
+    struct Zeros {
+        remaining: u64,
+        largest_read: usize,
+    }
+
+    impl Read for Zeros {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.largest_read = self.largest_read.max(buf.len());
+            let read = buf.len().min(self.remaining.try_into().unwrap_or(usize::MAX));
+            buf[..read].fill(0);
+            self.remaining -= read as u64;
+            Ok(read)
+        }
+    }
+
+    #[test]
+    fn hash_stream_reads_a_gigabyte_through_one_buffer() {
+        let mut zeros = Zeros { remaining: 1 << 30, largest_read: 0 };
+        let mut hashers = [Prehasher::new("blake3").unwrap()];
+        assert_eq!(hash_stream(&mut zeros, &mut hashers, None).unwrap(), 1 << 30);
+        assert_eq!(zeros.remaining, 0);
+        assert_eq!(zeros.largest_read, HASH_CHUNK_BYTES);
+    }
+
+    #[test]
+    fn hash_stream_matches_hashing_all_at_once() {
+        let len = 3 * HASH_CHUNK_BYTES + 5;
+        for algorithm in ["sha256", "blake3"] {
+            let mut streamed = [Prehasher::new(algorithm).unwrap()];
+            let mut zeros = Zeros { remaining: len as u64, largest_read: 0 };
+            hash_stream(&mut zeros, &mut streamed, None).unwrap();
+
+            let mut whole = Prehasher::new(algorithm).unwrap();
+            whole.update(&vec![0; len]);
+            let [streamed] = streamed;
+            assert_eq!(streamed.finalize(), whole.finalize(), "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn keystore_location_precedence() {
+        let profile = Profile { path: "/profile".into(), scheme: Some("bls".into()) };
+        let profiles = Profiles { keystore: Some("/profiles-file".into()), scheme: Some("ed25519".into()), ..Default::default() };
+        let profiles_path = Path::new("/home/profiles.toml");
+        let location = |keystore, profile, env: Option<&str>, profiles| {
+            let (path, source) = keystore_location(keystore, profile, env.map(String::from), profiles, profiles_path).unwrap();
+            (path.display().to_string(), source)
+        };
+
+        let everything = location(Some("/flag"), Some(("work", &profile)), Some("/env"), &profiles);
+        assert_eq!(everything, ("/flag".to_string(), "--keystore".to_string()));
+        let no_flag = location(None, Some(("work", &profile)), Some("/env"), &profiles);
+        assert_eq!(no_flag, ("/profile".to_string(), "profile work".to_string()));
+        let environment = location(None, None, Some("/env"), &profiles);
+        assert_eq!(environment, ("/env".to_string(), "$SIG_TOOL_KEYSTORE".to_string()));
+        let file = location(None, None, None, &profiles);
+        assert_eq!(file, ("/profiles-file".to_string(), "/home/profiles.toml".to_string()));
+
+        let (path, source) = keystore_location(None, None, None, &Profiles::default(), profiles_path).unwrap();
+        assert_eq!((path, source.as_str()), (paths::expand(DEFAULT_KEYSTORE).unwrap(), "the default"));
+        let (path, _) = keystore_location(None, None, Some("~/keys".into()), &profiles, profiles_path).unwrap();
+        assert_eq!(path, dirs::home_dir().unwrap().join("keys"));
+
+        assert_eq!(default_keygen_scheme(Some(&profile), Some("ecdsa".into()), &profiles).as_deref(), Some("bls"));
+        assert_eq!(default_keygen_scheme(None, Some("ecdsa".into()), &profiles).as_deref(), Some("ecdsa"));
+        assert_eq!(default_keygen_scheme(None, None, &profiles).as_deref(), Some("ed25519"));
+        assert_eq!(default_keygen_scheme(None, None, &Profiles::default()), None);
+    }
+
+    // A keystore named by the environment that does not exist yet is created, as one named
+    // by --keystore is
+    #[test]
+    fn keystore_from_the_environment_is_created() {
+        let dir = std::env::temp_dir().join(format!("sig-tool-cli-env-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let env_keystore = dir.join("nested").display().to_string();
+        let (path, _) = keystore_location(None, None, Some(env_keystore), &Profiles::default(), Path::new("profiles.toml")).unwrap();
+
+        KeyStore::new(&path).unwrap();
+        assert!(path.is_dir());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
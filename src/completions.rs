@@ -0,0 +1,111 @@
+use clap::Command;
+use clap_complete::Shell;
+use thiserror::Error;
+
+// Shell completion scripts. clap_complete generates the static part from the command
+// definitions, so --scheme and other fixed-choice flags complete to exactly the values
+// the binary accepts. Key names are only known at run time: each script is extended to
+// ask `sig-tool __complete-keys` for them when completing a --key or -k value.
+
+pub const SHELLS: [&str; 4] = ["bash", "zsh", "fish", "powershell"];
+
+// The hidden subcommand the scripts call for key names
+pub const COMPLETE_KEYS: &str = "__complete-keys";
+
+const BIN_NAME: &str = "sig-tool";
+
+#[derive(Error, Debug)]
+pub enum CompletionError {
+    #[error("Unsupported shell {0}; use one of {shells}", shells = SHELLS.join(", "))]
+    UnsupportedShell(String),
+
+    #[error("The generated completion script does not have the expected layout; key names will not complete")]
+    Layout,
+}
+
+// The completion script for a shell, written for a command named sig-tool
+pub fn script(shell: &str, command: Command) -> Result<String, CompletionError> {
+    let generator: Shell = shell.parse().map_err(|_| CompletionError::UnsupportedShell(shell.to_string()))?;
+    // Hidden subcommands are not offered, and clap_complete cannot name the bash
+    // functions for __complete-keys, so the script is generated without them
+    let mut visible = Command::new(BIN_NAME)
+        .version(env!("CARGO_PKG_VERSION"))
+        .args(command.get_arguments().cloned())
+        .subcommands(command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()).cloned());
+    let mut generated = Vec::new();
+    clap_complete::generate(generator, &mut visible, BIN_NAME, &mut generated);
+    let generated = String::from_utf8(generated).map_err(|_| CompletionError::Layout)?;
+
+    Ok(match generator {
+        // Registering a wrapper replaces the generated completion function
+        Shell::Bash => {
+            let function = generated.split("complete -F ").nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .ok_or(CompletionError::Layout)?;
+            format!(r#"{generated}
+_sig-tool_with_keys() {{
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ "$prev" == "--key" || "$prev" == "-k" ]]; then
+        COMPREPLY=($(compgen -W "$(sig-tool {COMPLETE_KEYS} 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+        return 0
+    fi
+    {function} "$@"
+}}
+complete -F _sig-tool_with_keys -o bashdefault -o default sig-tool
+"#)
+        }
+        // The generated script ends by running or registering _sig-tool; both have to go
+        // through the wrapper instead
+        Shell::Zsh => replace_once(&generated, r#"if [ "$funcstack[1]" = "_sig-tool" ]; then
+    _sig-tool "$@"
+else
+    compdef _sig-tool sig-tool
+fi"#, &format!(r#"_sig-tool_with_keys() {{
+    if [[ "${{words[CURRENT-1]}}" == (--key|-k) ]]; then
+        compadd -- ${{(f)"$(sig-tool {COMPLETE_KEYS} 2>/dev/null)"}}
+    else
+        _sig-tool "$@"
+    fi
+}}
+
+if [ "$funcstack[1]" = "_sig-tool" ]; then
+    _sig-tool_with_keys "$@"
+else
+    compdef _sig-tool_with_keys sig-tool
+fi"#))?,
+        // Fish offers the candidates of every rule that matches the option
+        Shell::Fish => format!(r#"{generated}complete -c sig-tool -s k -l key -r -f -a "(sig-tool {COMPLETE_KEYS} 2>/dev/null)"
+"#),
+        // Keep the generated completer as a script block and call it for everything but keys
+        Shell::PowerShell => {
+            let generated = replace_once(&generated,
+                "Register-ArgumentCompleter -Native -CommandName 'sig-tool' -ScriptBlock {",
+                "$sigToolCompleter = {")?;
+            format!(r#"{generated}
+Register-ArgumentCompleter -Native -CommandName 'sig-tool' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $previous = $commandAst.CommandElements |
+        Where-Object {{ $_.Extent.EndOffset -lt $cursorPosition }} |
+        Select-Object -Last 1
+    if ($previous -and ($previous.ToString() -eq '--key' -or $previous.ToString() -eq '-k')) {{
+        sig-tool {COMPLETE_KEYS} 2>$null |
+            Where-Object {{ $_ -like "$wordToComplete*" }} |
+            ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}
+        return
+    }}
+    & $sigToolCompleter $wordToComplete $commandAst $cursorPosition
+}}
+"#)
+        }
+        _ => generated,
+    })
+}
+
+// Replace a part of a generated script that the key completion hooks into
+fn replace_once(script: &str, from: &str, to: &str) -> Result<String, CompletionError> {
+    if !script.contains(from) {
+        return Err(CompletionError::Layout);
+    }
+    Ok(script.replacen(from, to, 1))
+}
@@ -0,0 +1,242 @@
+use crate::crypto::bitcoin::{self, hash160, varint};
+use crate::crypto::scheme::{os_rng, SignatureError};
+use crate::crypto::schnorr::tagged_hash;
+use crate::crypto::ECDSA;
+use k256::ecdsa::SigningKey;
+use k256::schnorr::{Signature as SchnorrSignature, SigningKey as SchnorrSigningKey, VerifyingKey as XOnlyPublicKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+/// Scheme recorded for BIP-322 "simple" signatures: a consensus-encoded witness stack
+pub const BIP322_SCHEME: &str = "BIP322-simple";
+
+const SIGHASH_DEFAULT: u8 = 0x00;
+const SIGHASH_ALL: u8 = 0x01;
+const OP_RETURN: u8 = 0x6a;
+
+/// Address types BIP-322 signatures can be made for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddressType {
+    /// Segwit v0 pay-to-witness-pubkey-hash, signed with ECDSA
+    #[default]
+    P2wpkh,
+    /// Segwit v1 taproot key path, signed with BIP-340 Schnorr
+    P2tr,
+}
+
+/// Sign a message for the P2WPKH address of an ECDSA key, returning the witness stack
+pub fn sign_p2wpkh(private_key: &SigningKey, message: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    let public_key = private_key.verifying_key().to_encoded_point(true);
+    let pubkey_hash = hash160(public_key.as_bytes());
+
+    let to_spend = to_spend_txid(&bitcoin::segwit_script_pubkey(0, &pubkey_hash), message);
+    let sighash = segwit_v0_sighash(&to_spend, &pubkey_hash);
+
+    let signature = ECDSA::sign_prehash(private_key, &sighash)?;
+    let mut signature_item = signature.to_der().as_bytes().to_vec();
+    signature_item.push(SIGHASH_ALL);
+
+    Ok(encode_witness(&[signature_item, public_key.as_bytes().to_vec()]))
+}
+
+/// Sign a message for the P2TR address of an already tweaked output key, returning the
+/// witness stack
+pub fn sign_p2tr(output_key: &SchnorrSigningKey, message: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    sign_p2tr_with_rng(output_key, message, &mut os_rng()?)
+}
+
+/// `sign_p2tr` with the BIP-340 auxiliary randomness drawn from rng
+pub fn sign_p2tr_with_rng(output_key: &SchnorrSigningKey, message: &[u8], rng: &mut impl CryptoRngCore) -> Result<Vec<u8>, SignatureError> {
+    let script_pubkey = bitcoin::segwit_script_pubkey(1, &output_key.verifying_key().to_bytes());
+
+    let to_spend = to_spend_txid(&script_pubkey, message);
+    let sighash = taproot_sighash(&to_spend, &script_pubkey, SIGHASH_DEFAULT);
+
+    let mut aux_rand = [0u8; 32];
+    rng.fill_bytes(&mut aux_rand);
+    let signature = output_key
+        .sign_prehash_with_aux_rand(&sighash, &aux_rand)
+        .map_err(|e| SignatureError::Signing(e.to_string()))?;
+
+    Ok(encode_witness(&[signature.to_bytes().to_vec()]))
+}
+
+/// Verify a BIP-322 simple signature (an encoded witness stack) for a P2WPKH or P2TR address
+pub fn verify(address: &str, message: &[u8], signature: &[u8]) -> Result<bool, SignatureError> {
+    let (version, program) = bitcoin::decode_segwit_address(address)?;
+    let script_pubkey = bitcoin::segwit_script_pubkey(version, &program);
+    let witness = decode_witness(signature)?;
+    let to_spend = to_spend_txid(&script_pubkey, message);
+
+    match (version, program.len()) {
+        (0, 20) => {
+            let [signature_item, public_key] = witness.as_slice() else {
+                return Err(SignatureError::Deserialization(format!(
+                    "P2WPKH witness must have 2 items, got {}", witness.len()
+                )));
+            };
+            let Some((&SIGHASH_ALL, der)) = signature_item.split_last() else {
+                return Err(SignatureError::Deserialization("Only SIGHASH_ALL P2WPKH signatures are supported".into()));
+            };
+            if hash160(public_key) != program.as_slice() {
+                return Ok(false);
+            }
+
+            let public_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|e| SignatureError::Deserialization(e.to_string()))?;
+            ECDSA::verify_prehash(&public_key, &segwit_v0_sighash(&to_spend, &program), der)
+        }
+        (1, 32) => {
+            let [signature_item] = witness.as_slice() else {
+                return Err(SignatureError::Deserialization(format!(
+                    "P2TR key-path witness must have 1 item, got {}", witness.len()
+                )));
+            };
+            // 64 bytes means SIGHASH_DEFAULT; a 65th byte names the sighash type explicitly
+            let (signature, hash_type) = match signature_item.len() {
+                64 => (&signature_item[..], SIGHASH_DEFAULT),
+                65 if signature_item[64] == SIGHASH_ALL => (&signature_item[..64], SIGHASH_ALL),
+                _ => return Err(SignatureError::Deserialization(
+                    "P2TR signature must be 64 bytes, or 65 bytes ending in SIGHASH_ALL".into()
+                )),
+            };
+
+            let output_key = XOnlyPublicKey::from_bytes(&program)
+                .map_err(|e| SignatureError::Deserialization(e.to_string()))?;
+            let signature = SchnorrSignature::try_from(signature)
+                .map_err(|e| SignatureError::Deserialization(e.to_string()))?;
+            let sighash = taproot_sighash(&to_spend, &script_pubkey, hash_type);
+            Ok(output_key.verify_raw(&sighash, &signature).is_ok())
+        }
+        _ => Err(SignatureError::Verification(format!(
+            "BIP-322 verification supports P2WPKH and P2TR addresses, not witness v{} with a {}-byte program",
+            version, program.len()
+        ))),
+    }
+}
+
+// Txid of the virtual to_spend transaction, whose single output is locked by the
+// address's script and whose input commits to the message
+fn to_spend_txid(script_pubkey: &[u8], message: &[u8]) -> [u8; 32] {
+    let message_hash = tagged_hash(b"BIP0322-signed-message", message);
+
+    let mut tx = Vec::new();
+    tx.extend_from_slice(&0u32.to_le_bytes()); // version
+    tx.push(1); // input count
+    tx.extend_from_slice(&[0u8; 32]); // null prevout txid
+    tx.extend_from_slice(&0xffff_ffffu32.to_le_bytes()); // prevout index
+    tx.push(34); // scriptSig: OP_0 PUSH32 message_hash
+    tx.extend_from_slice(&[0x00, 0x20]);
+    tx.extend_from_slice(&message_hash);
+    tx.extend_from_slice(&0u32.to_le_bytes()); // sequence
+    tx.push(1); // output count
+    tx.extend_from_slice(&0u64.to_le_bytes()); // value
+    tx.extend_from_slice(&varint(script_pubkey.len() as u64));
+    tx.extend_from_slice(script_pubkey);
+    tx.extend_from_slice(&0u32.to_le_bytes()); // locktime
+
+    double_sha256(&tx)
+}
+
+// The virtual to_sign transaction spends output 0 of to_spend into a single OP_RETURN
+// output; these are the pieces of it that the sighash algorithms commit to
+fn to_sign_outpoint(to_spend: &[u8; 32]) -> Vec<u8> {
+    [&to_spend[..], &0u32.to_le_bytes()].concat()
+}
+
+fn to_sign_outputs() -> Vec<u8> {
+    [&0u64.to_le_bytes()[..], &[1, OP_RETURN]].concat()
+}
+
+// BIP-143 SIGHASH_ALL digest of the to_sign input spending a P2WPKH output
+fn segwit_v0_sighash(to_spend: &[u8; 32], pubkey_hash: &[u8]) -> [u8; 32] {
+    let mut script_code = vec![0x19, 0x76, 0xa9, 0x14];
+    script_code.extend_from_slice(pubkey_hash);
+    script_code.extend_from_slice(&[0x88, 0xac]);
+
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(&0u32.to_le_bytes()); // version
+    preimage.extend_from_slice(&double_sha256(&to_sign_outpoint(to_spend)));
+    preimage.extend_from_slice(&double_sha256(&0u32.to_le_bytes())); // sequences
+    preimage.extend_from_slice(&to_sign_outpoint(to_spend));
+    preimage.extend_from_slice(&script_code);
+    preimage.extend_from_slice(&0u64.to_le_bytes()); // amount
+    preimage.extend_from_slice(&0u32.to_le_bytes()); // sequence
+    preimage.extend_from_slice(&double_sha256(&to_sign_outputs()));
+    preimage.extend_from_slice(&0u32.to_le_bytes()); // locktime
+    preimage.extend_from_slice(&(SIGHASH_ALL as u32).to_le_bytes());
+
+    double_sha256(&preimage)
+}
+
+// BIP-341 key-path digest of the to_sign input, for SIGHASH_DEFAULT or SIGHASH_ALL
+fn taproot_sighash(to_spend: &[u8; 32], script_pubkey: &[u8], hash_type: u8) -> [u8; 32] {
+    let mut sig_msg = vec![0x00, hash_type]; // epoch, hash type
+    sig_msg.extend_from_slice(&0u32.to_le_bytes()); // version
+    sig_msg.extend_from_slice(&0u32.to_le_bytes()); // locktime
+    sig_msg.extend_from_slice(&Sha256::digest(to_sign_outpoint(to_spend)));
+    sig_msg.extend_from_slice(&Sha256::digest(0u64.to_le_bytes())); // amounts
+    sig_msg.extend_from_slice(&Sha256::digest([varint(script_pubkey.len() as u64), script_pubkey.to_vec()].concat()));
+    sig_msg.extend_from_slice(&Sha256::digest(0u32.to_le_bytes())); // sequences
+    sig_msg.extend_from_slice(&Sha256::digest(to_sign_outputs()));
+    sig_msg.push(0x00); // spend type: key path, no annex
+    sig_msg.extend_from_slice(&0u32.to_le_bytes()); // input index
+
+    tagged_hash(b"TapSighash", &sig_msg)
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+fn encode_witness(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut encoded = varint(items.len() as u64);
+    for item in items {
+        encoded.extend_from_slice(&varint(item.len() as u64));
+        encoded.extend_from_slice(item);
+    }
+    encoded
+}
+
+fn decode_witness(bytes: &[u8]) -> Result<Vec<Vec<u8>>, SignatureError> {
+    let truncated = || SignatureError::Deserialization("Truncated BIP-322 witness stack".into());
+
+    let mut rest = bytes;
+    let count = read_varint(&mut rest).ok_or_else(truncated)?;
+    let mut items = Vec::new();
+    for _ in 0..count {
+        let len = read_varint(&mut rest).ok_or_else(truncated)? as usize;
+        if rest.len() < len {
+            return Err(truncated());
+        }
+        let (item, remaining) = rest.split_at(len);
+        items.push(item.to_vec());
+        rest = remaining;
+    }
+
+    if !rest.is_empty() {
+        return Err(SignatureError::Deserialization("Trailing bytes after BIP-322 witness stack".into()));
+    }
+    Ok(items)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = bytes.split_first()?;
+    let width = match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        _ => {
+            *bytes = rest;
+            return Some(first as u64);
+        }
+    };
+    if rest.len() < width {
+        return None;
+    }
+
+    let mut value = [0u8; 8];
+    value[..width].copy_from_slice(&rest[..width]);
+    *bytes = &rest[width..];
+    Some(u64::from_le_bytes(value))
+}
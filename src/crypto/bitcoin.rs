@@ -0,0 +1,366 @@
+use crate::crypto::scheme::SignatureError;
+use crate::crypto::ECDSA;
+use k256::ecdsa::{SigningKey, VerifyingKey};
+use k256::schnorr::VerifyingKey as XOnlyPublicKey;
+use sha2::{Digest, Sha256};
+
+/// Scheme recorded for legacy Bitcoin Core signed messages
+pub const BITCOIN_MESSAGE_SCHEME: &str = "ECDSA-secp256k1-bitcoin-message";
+
+/// Version byte of mainnet pay-to-pubkey-hash addresses
+const P2PKH_VERSION: u8 = 0x00;
+
+/// Human-readable part of mainnet segwit addresses
+const SEGWIT_HRP: &str = "bc";
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+// Checksum constants: bech32 for witness version 0 (BIP-173), bech32m for later versions (BIP-350)
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+/// Digest Bitcoin Core signs for a message: double SHA-256 of
+/// `"\x18Bitcoin Signed Message:\n" || varint(len) || message`
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut framed = b"\x18Bitcoin Signed Message:\n".to_vec();
+    framed.extend_from_slice(&varint(message.len() as u64));
+    framed.extend_from_slice(message);
+
+    Sha256::digest(Sha256::digest(&framed)).into()
+}
+
+/// Sign a message in the Bitcoin Core format and return the 65-byte `header||r||s`
+/// signature, where the header encodes the recovery id and marks a compressed key
+pub fn sign_message(private_key: &SigningKey, message: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    // sign_recoverable returns r||s||v with v = 27 + recovery id
+    let signature = ECDSA::sign_recoverable(private_key, &message_hash(message))?;
+
+    let mut bytes = vec![signature[64] + 4];
+    bytes.extend_from_slice(&signature[..64]);
+    Ok(bytes)
+}
+
+/// Recover the signer of a Bitcoin Core signed message, along with whether the
+/// signature commits to the compressed form of its public key
+pub fn recover_message_signer(message: &[u8], signature: &[u8]) -> Result<(VerifyingKey, bool), SignatureError> {
+    if signature.len() != 65 {
+        return Err(SignatureError::Deserialization(format!(
+            "Invalid Bitcoin message signature length: expected 65 bytes, got {}", signature.len()
+        )));
+    }
+
+    let header = signature[0];
+    if !(27..=34).contains(&header) {
+        return Err(SignatureError::Deserialization(format!(
+            "Invalid Bitcoin message signature header {}: expected 27-34", header
+        )));
+    }
+    let compressed = header >= 31;
+
+    let mut recoverable = signature[1..].to_vec();
+    recoverable.push((header - 27) & 3);
+    let public_key = ECDSA::recover_public_key(&message_hash(message), &recoverable)?;
+
+    Ok((public_key, compressed))
+}
+
+/// Mainnet P2PKH address: base58check of HASH160 of the SEC1 public key
+pub fn p2pkh_address(public_key: &VerifyingKey, compressed: bool) -> String {
+    let point = public_key.to_encoded_point(compressed);
+
+    let mut payload = vec![P2PKH_VERSION];
+    payload.extend_from_slice(&hash160(point.as_bytes()));
+    base58check_encode(&payload)
+}
+
+/// Mainnet P2WPKH address: witness v0 program of HASH160 of the compressed key
+pub fn p2wpkh_address(public_key: &VerifyingKey) -> String {
+    segwit_address(0, &hash160(public_key.to_encoded_point(true).as_bytes()))
+}
+
+/// Mainnet P2TR address: witness v1 program of the x-only taproot output key
+pub fn p2tr_address(output_key: &XOnlyPublicKey) -> String {
+    segwit_address(1, &output_key.to_bytes())
+}
+
+/// Decode a segwit address of any network into its witness version and program
+pub fn decode_segwit_address(address: &str) -> Result<(u8, Vec<u8>), SignatureError> {
+    let invalid = |reason: &str| SignatureError::Deserialization(format!("Invalid segwit address {}: {}", address, reason));
+
+    if address.chars().any(|c| c.is_ascii_lowercase()) && address.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(invalid("mixed case"));
+    }
+    let address_lower = address.to_ascii_lowercase();
+    let (hrp, data_part) = address_lower.rsplit_once('1').ok_or_else(|| invalid("missing separator"))?;
+    if hrp.is_empty() || data_part.len() < 7 {
+        return Err(invalid("too short"));
+    }
+
+    let mut data = Vec::new();
+    for c in data_part.bytes() {
+        let value = BECH32_CHARSET.iter().position(|&x| x == c).ok_or_else(|| invalid("invalid character"))?;
+        data.push(value as u8);
+    }
+
+    let version = data[0];
+    let expected_const = if version == 0 { BECH32_CONST } else { BECH32M_CONST };
+    if bech32_polymod(&[hrp_expand(hrp), data.clone()].concat()) != expected_const {
+        return Err(invalid("bad checksum"));
+    }
+
+    let program = convert_bits(&data[1..data.len() - 6], 5, 8, false).ok_or_else(|| invalid("bad padding"))?;
+    if version > 16 || !(2..=40).contains(&program.len()) || (version == 0 && program.len() != 20 && program.len() != 32) {
+        return Err(invalid("bad witness program"));
+    }
+
+    Ok((version, program))
+}
+
+/// Output script that pays to a segwit address: `OP_n <program>`
+pub fn segwit_script_pubkey(version: u8, program: &[u8]) -> Vec<u8> {
+    let mut script = vec![if version == 0 { 0x00 } else { 0x50 + version }, program.len() as u8];
+    script.extend_from_slice(program);
+    script
+}
+
+// Bech32 (v0) or bech32m (v1+) encoding of a witness program
+fn segwit_address(version: u8, program: &[u8]) -> String {
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true).expect("padding is always allowed"));
+
+    let checksum_const = if version == 0 { BECH32_CONST } else { BECH32M_CONST };
+    let polymod = bech32_polymod(&[hrp_expand(SEGWIT_HRP), data.clone(), vec![0; 6]].concat()) ^ checksum_const;
+    data.extend((0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8));
+
+    let encoded: String = data.iter().map(|&value| BECH32_CHARSET[value as usize] as char).collect();
+    format!("{}1{}", SEGWIT_HRP, encoded)
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    let mut checksum = 1u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|c| c & 31))
+        .collect()
+}
+
+// Regroup bits, e.g. bytes into 5-bit bech32 values; None if the leftover bits are not zero padding
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max_value = (1u32 << to) - 1;
+    let max_acc = (1u32 << (from + to - 1)) - 1;
+    let mut result = Vec::new();
+
+    for &value in data {
+        acc = ((acc << from) | value as u32) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return None;
+    }
+
+    Some(result)
+}
+
+// HASH160: RIPEMD-160 of SHA-256
+pub(crate) fn hash160(data: &[u8]) -> [u8; 20] {
+    ripemd160(&Sha256::digest(data))
+}
+
+// Base58 of the payload followed by the first four bytes of its double SHA-256
+fn base58check_encode(payload: &[u8]) -> String {
+    let checksum = Sha256::digest(Sha256::digest(payload));
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&checksum[..4]);
+
+    // Repeated division by 58 over the big-endian number, least significant digit first
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in &data {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // Every leading zero byte is written as a leading '1'
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat_n(BASE58_ALPHABET[0], zeros)
+        .chain(digits.iter().rev().map(|&digit| BASE58_ALPHABET[digit as usize]))
+        .map(char::from)
+        .collect()
+}
+
+// Bitcoin's CompactSize length prefix
+pub(crate) fn varint(n: u64) -> Vec<u8> {
+    match n {
+        0..=0xfc => vec![n as u8],
+        0xfd..=0xffff => [&[0xfd][..], &(n as u16).to_le_bytes()].concat(),
+        0x10000..=0xffff_ffff => [&[0xfe][..], &(n as u32).to_le_bytes()].concat(),
+        _ => [&[0xff][..], &n.to_le_bytes()].concat(),
+    }
+}
+
+// RIPEMD-160, only needed for HASH160 so kept local rather than pulling in a crate
+fn ripemd160(data: &[u8]) -> [u8; 20] {
+    const R: [usize; 80] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+        7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+        3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+        1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+        4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+    ];
+    const R_PRIME: [usize; 80] = [
+        5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+        6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+        15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+        8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+        12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+    ];
+    const S: [u32; 80] = [
+        11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+        7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+        11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+        11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+        9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+    ];
+    const S_PRIME: [u32; 80] = [
+        8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+        9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+        9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+        15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+        8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+    ];
+    const K: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+    const K_PRIME: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+    fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+        match round {
+            0 => x ^ y ^ z,
+            1 => (x & y) | (!x & z),
+            2 => (x | !y) ^ z,
+            3 => (x & z) | (y & !z),
+            _ => x ^ (y | !z),
+        }
+    }
+
+    // MD4-style padding: 0x80, zeros, then the bit length as a little-endian u64
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_le_bytes());
+
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    for block in message.chunks(64) {
+        let x: Vec<u32> = block
+            .chunks(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        let (mut a2, mut b2, mut c2, mut d2, mut e2) = (h[0], h[1], h[2], h[3], h[4]);
+        for j in 0..80 {
+            let round = j / 16;
+
+            let t = a
+                .wrapping_add(f(round, b, c, d))
+                .wrapping_add(x[R[j]])
+                .wrapping_add(K[round])
+                .rotate_left(S[j])
+                .wrapping_add(e);
+            (a, e, d, c, b) = (e, d, c.rotate_left(10), b, t);
+
+            let t = a2
+                .wrapping_add(f(4 - round, b2, c2, d2))
+                .wrapping_add(x[R_PRIME[j]])
+                .wrapping_add(K_PRIME[round])
+                .rotate_left(S_PRIME[j])
+                .wrapping_add(e2);
+            (a2, e2, d2, c2, b2) = (e2, d2, c2.rotate_left(10), b2, t);
+        }
+
+        let t = h[1].wrapping_add(c).wrapping_add(d2);
+        h[1] = h[2].wrapping_add(d).wrapping_add(e2);
+        h[2] = h[3].wrapping_add(e).wrapping_add(a2);
+        h[3] = h[4].wrapping_add(a).wrapping_add(b2);
+        h[4] = h[0].wrapping_add(b).wrapping_add(c2);
+        h[0] = t;
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::prelude::*;
+
+    // The signed message example from bitcoinjs-message, which Bitcoin Core's verifymessage
+    // accepts: the key is WIF L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1, compressed
+    const PRIVATE_KEY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    const ADDRESS: &str = "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV";
+    const MESSAGE: &[u8] = b"This is an example of a signed message.";
+    const SIGNATURE: &str = "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=";
+
+    #[test]
+    fn recovers_the_address_of_a_known_signature() {
+        let signature = BASE64_STANDARD.decode(SIGNATURE).unwrap();
+        let (public_key, compressed) = recover_message_signer(MESSAGE, &signature).unwrap();
+        assert!(compressed);
+        assert_eq!(p2pkh_address(&public_key, compressed), ADDRESS);
+
+        let (public_key, compressed) = recover_message_signer(b"Another message.", &signature).unwrap();
+        assert_ne!(p2pkh_address(&public_key, compressed), ADDRESS);
+    }
+
+    #[test]
+    fn sign_message_reproduces_the_known_signature() {
+        let private_key = SigningKey::from_slice(&hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+        assert_eq!(BASE64_STANDARD.encode(sign_message(&private_key, MESSAGE).unwrap()), SIGNATURE);
+    }
+
+    #[test]
+    fn ripemd160_test_vectors() {
+        assert_eq!(hex::encode(ripemd160(b"")), "9c1185a5c5e9fc54612808977ee8f548b2258d31");
+        assert_eq!(hex::encode(ripemd160(b"abc")), "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc");
+    }
+}
@@ -1,7 +1,44 @@
 use crate::crypto::scheme::{SignatureScheme, SignatureError};
-use blst::{min_pk::*, BLST_ERROR};
-use rand::{rngs::OsRng, RngCore};
+use blst::{min_pk::*, blst_p2, blst_p2_affine, blst_scalar, MultiPoint, BLST_ERROR};
+use crypto_bigint::{impl_modulus, modular::constant_mod::Residue, Encoding, U256};
+use crate::crypto::wrapper::impl_wrapper_encoding;
+use rand_core::CryptoRngCore;
 
+/// Default domain separation tag, used unless a caller supplies its own
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+// Proofs of possession sign the serialized public key under a separate DST
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Scheme recorded in proof-of-possession files
+pub const POP_SCHEME: &str = "BLS12-381-min-pk-pop";
+
+/// Scheme recorded for message-augmentation signatures, which hash PK || message
+pub const AUG_SCHEME: &str = "BLS12-381-min-pk-aug";
+
+/// Named DST presets for common ecosystems, all hashing signatures to G2
+pub const CIPHERSUITES: &[(&str, &[u8])] = &[
+    ("eth2", b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_"),
+    ("filecoin", b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_"),
+    ("generic-nul", b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_"),
+    ("generic-pop", b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_"),
+];
+
+// Look up the DST of a named ciphersuite preset
+pub fn ciphersuite_dst(name: &str) -> Result<&'static [u8], SignatureError> {
+    CIPHERSUITES
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, dst)| *dst)
+        .ok_or_else(|| {
+            let available: Vec<&str> = CIPHERSUITES.iter().map(|(preset, _)| *preset).collect();
+            SignatureError::Signing(format!(
+                "Unknown ciphersuite {}, available: {}", name, available.join(", ")
+            ))
+        })
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug)]
 pub struct BLS;
 
@@ -15,6 +52,29 @@ pub struct BLSPublicKey(PublicKey);
 #[derive(Clone, Debug)]
 pub struct BLSSignature(Signature);
 
+impl_wrapper_encoding!(BLSPublicKey, BLS, serialize_public_key, deserialize_public_key, "public key");
+impl_wrapper_encoding!(BLSSignature, BLS, serialize_signature, deserialize_signature, "signature");
+
+/// Point encoding for BLS public keys and signatures
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointEncoding {
+    #[default]
+    Compressed,
+    Uncompressed,
+}
+
+// Map a blst point validation failure to a deserialization error naming the problem
+pub(crate) fn point_error(what: &str, err: BLST_ERROR) -> SignatureError {
+    let reason = match err {
+        BLST_ERROR::BLST_PK_IS_INFINITY => "is the identity point",
+        BLST_ERROR::BLST_POINT_NOT_IN_GROUP => "is not in the prime-order subgroup",
+        BLST_ERROR::BLST_POINT_NOT_ON_CURVE => "is not on the curve",
+        _ => "could not be decoded",
+    };
+    
+    SignatureError::Deserialization(format!("{} {}", what, reason))
+}
+
 // Implement aggregation for BLS signatures (not part of the trait)
 impl BLSSignature {
     pub fn aggregate(signatures: &[BLSSignature]) -> Result<Self, SignatureError> {
@@ -38,45 +98,323 @@ impl BLSSignature {
     }
 }
 
-impl SignatureScheme for BLS {
-    type PrivateKey = BLSPrivateKey;
-    type PublicKey = BLSPublicKey;
-    type Signature = BLSSignature;
+// Implement aggregation for BLS public keys (not part of the trait)
+impl BLSPublicKey {
+    pub fn aggregate(public_keys: &[BLSPublicKey]) -> Result<Self, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::KeyGeneration("Cannot aggregate empty public key list".into()));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let agg = AggregatePublicKey::aggregate(&pks, true)
+            .map_err(|_| SignatureError::KeyGeneration("Failed to aggregate public keys".into()))?;
+        
+        Ok(BLSPublicKey(agg.to_public_key()))
+    }
+}
+
+// Aggregate verification and proofs of possession (not part of the trait)
+impl BLS {
+    pub fn fast_aggregate_verify(
+        public_keys: &[BLSPublicKey],
+        message: &[u8],
+        signature: &BLSSignature,
+        dst: &[u8],
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify against an empty key list".into()));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let result = signature.0.fast_aggregate_verify(true, message, dst, &pks);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
     
-    fn name() -> &'static str {
-        "BLS12-381-min-pk"
+    // Distinct-message aggregate verification: public_keys[i] signed messages[i]
+    pub fn aggregate_verify(
+        public_keys: &[BLSPublicKey],
+        messages: &[&[u8]],
+        signature: &BLSSignature,
+        dst: &[u8],
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify against an empty key list".into()));
+        }
+        if public_keys.len() != messages.len() {
+            return Err(SignatureError::Verification(format!(
+                "Got {} public keys but {} messages", public_keys.len(), messages.len()
+            )));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let result = signature.0.aggregate_verify(true, messages, dst, &pks, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
     }
     
-    fn generate_keypair() -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
-        let mut ikm = [0u8; 32];
-        OsRng.fill_bytes(&mut ikm);
+    // Batch verification of independent signatures: public_keys[i] signed messages[i]
+    // producing signatures[i], all under one DST. A random linear combination, with
+    // scalars drawn from rng, folds them into one check.
+    pub fn verify_batch(
+        public_keys: &[BLSPublicKey],
+        messages: &[&[u8]],
+        signatures: &[BLSSignature],
+        dst: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify an empty batch".into()));
+        }
+        if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
+            return Err(SignatureError::Verification(format!(
+                "Got {} public keys, {} messages and {} signatures",
+                public_keys.len(), messages.len(), signatures.len()
+            )));
+        }
         
-        let sk = match SecretKey::key_gen(&ikm, &[]) {
-            Ok(key) => key,
-            Err(_) => return Err(SignatureError::KeyGeneration("Failed to generate BLS key".into())),
-        };
+        // 64-bit non-zero blinding scalars, as recommended by blst
+        let rands: Vec<blst_scalar> = (0..public_keys.len())
+            .map(|_| {
+                let mut scalar = blst_scalar { b: [0u8; 32] };
+                rng.fill_bytes(&mut scalar.b[..8]);
+                scalar.b[0] |= 1;
+                scalar
+            })
+            .collect();
         
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let sigs: Vec<&Signature> = signatures.iter().map(|sig| &sig.0).collect();
+        let result = Signature::verify_multiple_aggregate_signatures(
+            messages, dst, &pks, true, &sigs, true, &rands, 64,
+        );
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // The public key belonging to a secret key
+    pub fn public_key(private_key: &BLSPrivateKey) -> BLSPublicKey {
+        BLSPublicKey(private_key.0.sk_to_pk())
+    }
+    
+    // KeyGen from the IETF BLS draft, with the seed as the input keying material
+    pub fn keypair_from_seed(seed: &[u8]) -> Result<(BLSPrivateKey, BLSPublicKey), SignatureError> {
+        let sk = SecretKey::key_gen(seed, &[]).map_err(|_| {
+            SignatureError::KeyGeneration(format!("Seed must be at least 32 bytes, got {}", seed.len()))
+        })?;
         let pk = sk.sk_to_pk();
         
         Ok((BLSPrivateKey(sk), BLSPublicKey(pk)))
     }
     
-    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
-        let dst = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+    // EIP-2333 hierarchical derivation from a seed along a path such as m/12381/3600/0/0/0
+    pub fn derive_eip2333(seed: &[u8], path: &str) -> Result<(BLSPrivateKey, BLSPublicKey), SignatureError> {
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(SignatureError::KeyGeneration(format!("Derivation path must start with m: {}", path)));
+        }
+        
+        let mut sk = SecretKey::derive_master_eip2333(seed).map_err(|_| {
+            SignatureError::KeyGeneration(format!("Seed must be at least 32 bytes, got {}", seed.len()))
+        })?;
+        
+        for component in components {
+            let index: u32 = component.parse().map_err(|_| {
+                SignatureError::KeyGeneration(format!("Invalid derivation path component: {}", component))
+            })?;
+            sk = sk.derive_child_eip2333(index);
+        }
+        
+        let pk = sk.sk_to_pk();
+        
+        Ok((BLSPrivateKey(sk), BLSPublicKey(pk)))
+    }
+    
+    // Sign and verify under a caller-chosen DST; the trait methods use the default DST
+    pub fn sign_with_dst(private_key: &BLSPrivateKey, message: &[u8], dst: &[u8]) -> Result<BLSSignature, SignatureError> {
         let sig = private_key.0.sign(message, dst, &[]);
         
         Ok(BLSSignature(sig))
     }
     
-    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
-        let dst = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+    pub fn verify_with_dst(public_key: &BLSPublicKey, message: &[u8], signature: &BLSSignature, dst: &[u8]) -> Result<bool, SignatureError> {
+        let result = signature.0.verify(true, message, dst, &[], &public_key.0, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Message augmentation: the compressed public key is passed to blst as the aug
+    // prefix, so every signer hashes a distinct message and no PoP is needed
+    pub fn sign_aug(private_key: &BLSPrivateKey, message: &[u8], dst: &[u8]) -> Result<BLSSignature, SignatureError> {
+        let aug = private_key.0.sk_to_pk().compress();
+        let sig = private_key.0.sign(message, dst, &aug);
+        
+        Ok(BLSSignature(sig))
+    }
+    
+    pub fn verify_aug(public_key: &BLSPublicKey, message: &[u8], signature: &BLSSignature, dst: &[u8]) -> Result<bool, SignatureError> {
+        let aug = public_key.0.compress();
+        let result = signature.0.verify(true, message, dst, &aug, &public_key.0, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Aggregate verification of AUG signatures: public_keys[i] signed PK_i || messages[i]
+    pub fn aggregate_verify_aug(
+        public_keys: &[BLSPublicKey],
+        messages: &[&[u8]],
+        signature: &BLSSignature,
+        dst: &[u8],
+    ) -> Result<bool, SignatureError> {
+        if public_keys.len() != messages.len() {
+            return Err(SignatureError::Verification(format!(
+                "Got {} public keys but {} messages", public_keys.len(), messages.len()
+            )));
+        }
+        
+        let augmented: Vec<Vec<u8>> = public_keys
+            .iter()
+            .zip(messages)
+            .map(|(pk, msg)| [pk.0.compress().as_slice(), msg].concat())
+            .collect();
+        let augmented: Vec<&[u8]> = augmented.iter().map(|msg| msg.as_slice()).collect();
+        
+        Self::aggregate_verify(public_keys, &augmented, signature, dst)
+    }
+    
+    // Encode in either point format; the trait serializers always compress
+    pub fn encode_public_key(public_key: &BLSPublicKey, encoding: PointEncoding) -> Vec<u8> {
+        match encoding {
+            PointEncoding::Compressed => public_key.0.compress().to_vec(),
+            PointEncoding::Uncompressed => public_key.0.serialize().to_vec(),
+        }
+    }
+    
+    pub fn encode_signature(signature: &BLSSignature, encoding: PointEncoding) -> Vec<u8> {
+        match encoding {
+            PointEncoding::Compressed => signature.0.compress().to_vec(),
+            PointEncoding::Uncompressed => signature.0.serialize().to_vec(),
+        }
+    }
+    
+    // Proof of possession: a signature over the signer's own serialized public key
+    pub fn prove_possession(private_key: &BLSPrivateKey) -> Result<BLSSignature, SignatureError> {
+        let pk_bytes = Self::serialize_public_key(&BLSPublicKey(private_key.0.sk_to_pk()))?;
+        let pop = private_key.0.sign(&pk_bytes, POP_DST, &[]);
+        
+        Ok(BLSSignature(pop))
+    }
+    
+    pub fn verify_possession(public_key: &BLSPublicKey, pop: &BLSSignature) -> Result<bool, SignatureError> {
+        let pk_bytes = Self::serialize_public_key(public_key)?;
+        let result = pop.0.verify(true, &pk_bytes, POP_DST, &[], &public_key.0, true);
         
-        let result = signature.0.verify(true, message, dst, &[], &public_key.0, false);
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+}
+
+// Order r of the BLS12-381 groups; secret keys and Shamir shares live in the scalar field
+impl_modulus!(ScalarModulus, U256, "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+type Scalar = Residue<ScalarModulus, { U256::LIMBS }>;
+
+// Threshold signing with Shamir shares of the secret scalar (not part of the trait)
+impl BLS {
+    // Split a key into shares 1..=shares, any `threshold` of which can produce a signature:
+    // share i is f(i) for a polynomial f of degree threshold - 1 with f(0) = sk and its
+    // other coefficients drawn from rng
+    pub fn split_key(
+        private_key: &BLSPrivateKey,
+        threshold: u32,
+        shares: u32,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<(u32, BLSPrivateKey)>, SignatureError> {
+        if threshold == 0 || threshold > shares {
+            return Err(SignatureError::KeyGeneration(format!(
+                "Threshold must be between 1 and the number of shares ({}), got {}", shares, threshold
+            )));
+        }
         
-        match result {
-            BLST_ERROR::BLST_SUCCESS => Ok(true),
-            _ => Ok(false),
+        let mut coefficients = vec![Scalar::new(&U256::from_be_slice(&private_key.0.to_bytes()))];
+        for _ in 1..threshold {
+            let (coefficient, _) = Self::generate_keypair_with_rng(rng)?;
+            coefficients.push(Scalar::new(&U256::from_be_slice(&coefficient.0.to_bytes())));
         }
+        
+        (1..=shares)
+            .map(|index| {
+                // Horner evaluation of f(index)
+                let x = Scalar::new(&U256::from_u32(index));
+                let y = coefficients.iter().rev().fold(Scalar::ZERO, |acc, c| acc * x + *c);
+                
+                let share = SecretKey::from_bytes(&y.retrieve().to_be_bytes())
+                    .map_err(|_| SignatureError::KeyGeneration(format!("Share {} is not a valid secret key", index)))?;
+                Ok((index, BLSPrivateKey(share)))
+            })
+            .collect()
+    }
+    
+    // Recover the full signature from partial signatures by Lagrange interpolation in the
+    // exponent: sigma = sum of lambda_i * sigma_i, with the lambda_i evaluated at x = 0
+    pub fn combine_partials(partials: &[(u32, BLSSignature)]) -> Result<BLSSignature, SignatureError> {
+        if partials.is_empty() {
+            return Err(SignatureError::Signing("Cannot combine an empty list of partial signatures".into()));
+        }
+        for (n, (index, _)) in partials.iter().enumerate() {
+            if *index == 0 {
+                return Err(SignatureError::Signing("Share index 0 is reserved for the secret key".into()));
+            }
+            if partials[..n].iter().any(|(other, _)| other == index) {
+                return Err(SignatureError::Signing(format!("Duplicate partial signature for share {}", index)));
+            }
+        }
+        
+        let xs: Vec<Scalar> = partials.iter().map(|(index, _)| Scalar::new(&U256::from_u32(*index))).collect();
+        
+        let mut scalars = Vec::with_capacity(32 * xs.len());
+        for (i, xi) in xs.iter().enumerate() {
+            let mut numerator = Scalar::ONE;
+            let mut denominator = Scalar::ONE;
+            for (j, xj) in xs.iter().enumerate() {
+                if i != j {
+                    numerator *= xj;
+                    denominator *= *xj - xi;
+                }
+            }
+            
+            let (denominator_inv, _) = denominator.invert();
+            let lambda = numerator * denominator_inv;
+            scalars.extend_from_slice(&lambda.retrieve().to_le_bytes());
+        }
+        
+        let points: Vec<blst_p2_affine> = partials.iter().map(|(_, sig)| *<&blst_p2_affine>::from(&sig.0)).collect();
+        let combined: blst_p2 = points.as_slice().mult(&scalars, 255);
+        
+        Ok(BLSSignature(AggregateSignature::from(combined).to_signature()))
+    }
+}
+
+impl SignatureScheme for BLS {
+    type PrivateKey = BLSPrivateKey;
+    type PublicKey = BLSPublicKey;
+    type Signature = BLSSignature;
+    
+    fn name() -> &'static str {
+        "BLS12-381-min-pk"
+    }
+    
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let mut ikm = [0u8; 32];
+        rng.fill_bytes(&mut ikm);
+        
+        Self::keypair_from_seed(&ikm)
+    }
+    
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_dst(private_key, message, DST)
+    }
+    
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        Self::verify_with_dst(public_key, message, signature, DST)
     }
     
     // Serialization methods for BLS keys and signatures
@@ -92,24 +430,191 @@ impl SignatureScheme for BLS {
     }
     
     fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
-        Ok(public_key.0.serialize().to_vec())
+        Ok(Self::encode_public_key(public_key, PointEncoding::Compressed))
     }
     
+    // Accepts both encodings: 48-byte compressed or 96-byte uncompressed G1 points.
+    // The identity and points outside the subgroup are rejected.
     fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
-        match PublicKey::deserialize(bytes) {
-            Ok(pk) => Ok(BLSPublicKey(pk)),
-            Err(_) => Err(SignatureError::Deserialization("Failed to deserialize BLS public key".into())),
+        let pk = match bytes.len() {
+            48 => PublicKey::uncompress(bytes),
+            96 => PublicKey::deserialize(bytes),
+            len => return Err(SignatureError::Deserialization(format!(
+                "Invalid BLS public key length: expected 48 or 96 bytes, got {}", len
+            ))),
         }
+        .map_err(|e| point_error("BLS public key", e))?;
+        
+        pk.validate().map_err(|e| point_error("BLS public key", e))?;
+        Ok(BLSPublicKey(pk))
     }
     
     fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
-        Ok(signature.0.serialize().to_vec())
+        Ok(Self::encode_signature(signature, PointEncoding::Compressed))
     }
     
+    // Accepts both encodings: 96-byte compressed or 192-byte uncompressed G2 points.
+    // The identity and points outside the subgroup are rejected.
     fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
-        match Signature::deserialize(bytes) {
-            Ok(sig) => Ok(BLSSignature(sig)),
-            Err(_) => Err(SignatureError::Deserialization("Failed to deserialize BLS signature".into())),
+        let sig = match bytes.len() {
+            96 => Signature::uncompress(bytes),
+            192 => Signature::deserialize(bytes),
+            len => return Err(SignatureError::Deserialization(format!(
+                "Invalid BLS signature length: expected 96 or 192 bytes, got {}", len
+            ))),
+        }
+        .map_err(|e| point_error("BLS signature", e))?;
+        
+        sig.validate(true).map_err(|e| point_error("BLS signature", e))?;
+        Ok(BLSSignature(sig))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn split_key_draws_its_coefficients_from_rng() {
+        let (private_key, public_key) = BLS::generate_keypair_with_rng(&mut ChaCha20Rng::seed_from_u64(1)).unwrap();
+        let shares = BLS::split_key(&private_key, 2, 3, &mut ChaCha20Rng::seed_from_u64(2)).unwrap();
+        let again = BLS::split_key(&private_key, 2, 3, &mut ChaCha20Rng::seed_from_u64(2)).unwrap();
+        let bytes = |shares: &[(u32, BLSPrivateKey)]| shares.iter().map(|(_, share)| share.0.to_bytes()).collect::<Vec<_>>();
+        assert_eq!(bytes(&shares), bytes(&again));
+
+        // Any two of the three shares sign for the key
+        let partials: Vec<(u32, BLSSignature)> = shares[1..].iter()
+            .map(|(index, share)| (*index, BLS::sign(share, b"message").unwrap()))
+            .collect();
+        let signature = BLS::combine_partials(&partials).unwrap();
+        assert!(BLS::verify(&public_key, b"message", &signature).unwrap());
+    }
+
+    #[test]
+    fn verify_batch_with_rng() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let keys: Vec<_> = (0..3).map(|_| BLS::generate_keypair_with_rng(&mut rng).unwrap()).collect();
+        let messages: [&[u8]; 3] = [b"one", b"two", b"three"];
+        let public_keys: Vec<BLSPublicKey> = keys.iter().map(|(_, public_key)| public_key.clone()).collect();
+        let mut signatures: Vec<BLSSignature> = keys.iter().zip(messages)
+            .map(|((private_key, _), message)| BLS::sign(private_key, message).unwrap())
+            .collect();
+        let dst = DST;
+        assert!(BLS::verify_batch(&public_keys, &messages, &signatures, dst, &mut rng).unwrap());
+        assert!(!BLS::verify_batch(&public_keys, &messages, &signatures, b"another DST", &mut rng).unwrap());
+        signatures.swap(0, 1);
+        assert!(!BLS::verify_batch(&public_keys, &messages, &signatures, dst, &mut rng).unwrap());
+    }
+
+    #[test]
+    fn fast_aggregate_verify_with_1_2_and_10_signers() {
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let keys: Vec<_> = (0..10).map(|_| BLS::generate_keypair_with_rng(&mut rng).unwrap()).collect();
+        for signers in [1, 2, 10] {
+            let mut public_keys: Vec<BLSPublicKey> = keys[..signers].iter().map(|(_, public_key)| public_key.clone()).collect();
+            let signatures: Vec<BLSSignature> = keys[..signers].iter()
+                .map(|(private_key, _)| BLS::sign(private_key, b"message").unwrap())
+                .collect();
+            let aggregate = BLSSignature::aggregate(&signatures).unwrap();
+            assert!(BLS::fast_aggregate_verify(&public_keys, b"message", &aggregate, DST).unwrap(), "{} signers", signers);
+            assert!(!BLS::fast_aggregate_verify(&public_keys, b"other message", &aggregate, DST).unwrap(), "{} signers", signers);
+
+            // One key that did not sign in place of one that did
+            let (_, wrong_key) = BLS::generate_keypair_with_rng(&mut rng).unwrap();
+            public_keys[signers - 1] = wrong_key;
+            assert!(!BLS::fast_aggregate_verify(&public_keys, b"message", &aggregate, DST).unwrap(), "{} signers", signers);
+        }
+
+        let (private_key, _) = &keys[0];
+        let signature = BLS::sign(private_key, b"message").unwrap();
+        assert!(BLS::fast_aggregate_verify(&[], b"message", &signature, DST).is_err());
+    }
+
+    // A message-augmented signature is over PK || message, so it is not a basic (NUL)
+    // signature over the message, and a basic one is not an augmented one
+    #[test]
+    fn aug_and_basic_signatures_do_not_cross_verify() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6);
+        let (private_key, public_key) = BLS::generate_keypair_with_rng(&mut rng).unwrap();
+        let aug = BLS::sign_aug(&private_key, b"message", DST).unwrap();
+        let basic = BLS::sign_with_dst(&private_key, b"message", DST).unwrap();
+        assert!(BLS::verify_aug(&public_key, b"message", &aug, DST).unwrap());
+        assert!(BLS::verify_with_dst(&public_key, b"message", &basic, DST).unwrap());
+
+        assert!(!BLS::verify_with_dst(&public_key, b"message", &aug, DST).unwrap());
+        assert!(!BLS::verify_aug(&public_key, b"message", &basic, DST).unwrap());
+        assert!(!BLS::aggregate_verify_aug(std::slice::from_ref(&public_key), &[b"message"], &basic, DST).unwrap());
+        assert!(!BLS::fast_aggregate_verify(&[public_key], b"message", &aug, DST).unwrap());
+    }
+
+    #[test]
+    fn proof_of_possession() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        let (private_key, public_key) = BLS::generate_keypair_with_rng(&mut rng).unwrap();
+        let (other_private_key, other_public_key) = BLS::generate_keypair_with_rng(&mut rng).unwrap();
+        let pop = BLS::prove_possession(&private_key).unwrap();
+        assert!(BLS::verify_possession(&public_key, &pop).unwrap());
+
+        // Another key's proof does not stand in for one this key never made
+        assert!(!BLS::verify_possession(&other_public_key, &pop).unwrap());
+
+        // Nor does the public key signed under the message DST
+        let pk_bytes = BLS::serialize_public_key(&other_public_key).unwrap();
+        let forged = BLS::sign(&other_private_key, &pk_bytes).unwrap();
+        assert!(!BLS::verify_possession(&other_public_key, &forged).unwrap());
+    }
+
+    // Test cases 0 and 1 from EIP-2333
+    #[test]
+    fn derive_eip2333_test_vectors() {
+        let cases: [(&str, &str, &str, &str); 2] = [
+            (
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                "0",
+                "0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070",
+                "2d18bd6c14e6d15bf8b5085c9b74f3daae3b03cc2014770a599d8c1539e50f8e",
+            ),
+            (
+                "3141592653589793238462643383279502884197169399375105820974944592",
+                "3141592653",
+                "41c9e07822b092a93fd6797396338c3ada4170cc81829fdfce6b5d34bd5e7ec7",
+                "384843fad5f3d777ea39de3e47a8f999ae91f89e42bffa993d91d9782d152a0f",
+            ),
+        ];
+        for (seed, index, master_sk, child_sk) in cases {
+            let seed = hex::decode(seed).unwrap();
+            let (master, _) = BLS::derive_eip2333(&seed, "m").unwrap();
+            assert_eq!(hex::encode(BLS::serialize_private_key(&master).unwrap()), master_sk);
+
+            let (child, public_key) = BLS::derive_eip2333(&seed, &format!("m/{}", index)).unwrap();
+            assert_eq!(hex::encode(BLS::serialize_private_key(&child).unwrap()), child_sk);
+            assert_eq!(BLS::serialize_public_key(&public_key).unwrap(), BLS::serialize_public_key(&BLS::public_key(&child)).unwrap());
         }
+
+        assert!(BLS::derive_eip2333(&[0; 32], "12381/3600").is_err());
+        assert!(BLS::derive_eip2333(&[0; 32], "m/12381/x").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rejects_identity_and_off_subgroup_points() {
+        let error = |result: Result<(), SignatureError>| match result {
+            Err(SignatureError::Deserialization(message)) => message,
+            _ => panic!("expected a deserialization error"),
+        };
+
+        // Compressed encodings: the flag bits 0xc0 mark the identity, 0x80 alone the point
+        // (0, 2), which is on the curve but of order 3
+        let mut identity_pk = [0u8; 48];
+        identity_pk[0] = 0xc0;
+        assert_eq!(error(BLS::deserialize_public_key(&identity_pk).map(drop)), "BLS public key is the identity point");
+
+        let mut order_3_pk = [0u8; 48];
+        order_3_pk[0] = 0x80;
+        assert_eq!(error(BLS::deserialize_public_key(&order_3_pk).map(drop)), "BLS public key is not in the prime-order subgroup");
+
+        let mut identity_sig = [0u8; 96];
+        identity_sig[0] = 0xc0;
+        assert_eq!(error(BLS::deserialize_signature(&identity_sig).map(drop)), "BLS signature is the identity point");
+    }
+}
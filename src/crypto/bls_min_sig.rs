@@ -0,0 +1,285 @@
+use crate::crypto::bls::{point_error, PointEncoding};
+use crate::crypto::scheme::{SignatureScheme, SignatureError};
+use blst::{min_sig::*, blst_scalar, BLST_ERROR};
+use crate::crypto::wrapper::impl_wrapper_encoding;
+use rand_core::CryptoRngCore;
+
+/// Default domain separation tag, used unless a caller supplies its own.
+/// Min-sig signatures are hashed to G1, so this is the G1 DST.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+// Proofs of possession sign the serialized public key under a separate DST
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Scheme recorded in proof-of-possession files
+pub const POP_SCHEME: &str = "BLS12-381-min-sig-pop";
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub struct BLSMinSig;
+
+// Wrapper types for BLS min-sig keys (96-byte G2) and signatures (48-byte G1)
+#[derive(Clone, Debug)]
+pub struct BLSMinSigPrivateKey(SecretKey);
+
+#[derive(Clone, Debug)]
+pub struct BLSMinSigPublicKey(PublicKey);
+
+#[derive(Clone, Debug)]
+pub struct BLSMinSigSignature(Signature);
+
+impl_wrapper_encoding!(BLSMinSigPublicKey, BLSMinSig, serialize_public_key, deserialize_public_key, "public key");
+impl_wrapper_encoding!(BLSMinSigSignature, BLSMinSig, serialize_signature, deserialize_signature, "signature");
+
+// Implement aggregation for BLS min-sig signatures (not part of the trait)
+impl BLSMinSigSignature {
+    pub fn aggregate(signatures: &[BLSMinSigSignature]) -> Result<Self, SignatureError> {
+        if signatures.is_empty() {
+            return Err(SignatureError::Signing("Cannot aggregate empty signature list".into()));
+        }
+        
+        // Start with the first signature and build an aggregate
+        let first_sig = &signatures[0].0;
+        let mut agg = AggregateSignature::from_signature(first_sig);
+        
+        // Add the remaining signatures
+        for sig in &signatures[1..] {
+            agg.add_signature(&sig.0, false)
+                .map_err(|_| SignatureError::Signing("Failed to add signature to aggregate".into()))?;
+        }
+        
+        // Convert to final signature
+        let final_sig = agg.to_signature();
+        Ok(BLSMinSigSignature(final_sig))
+    }
+}
+
+// Implement aggregation for BLS public keys (not part of the trait)
+impl BLSMinSigPublicKey {
+    pub fn aggregate(public_keys: &[BLSMinSigPublicKey]) -> Result<Self, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::KeyGeneration("Cannot aggregate empty public key list".into()));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let agg = AggregatePublicKey::aggregate(&pks, true)
+            .map_err(|_| SignatureError::KeyGeneration("Failed to aggregate public keys".into()))?;
+        
+        Ok(BLSMinSigPublicKey(agg.to_public_key()))
+    }
+}
+
+// Aggregate verification and proofs of possession (not part of the trait)
+impl BLSMinSig {
+    pub fn fast_aggregate_verify(
+        public_keys: &[BLSMinSigPublicKey],
+        message: &[u8],
+        signature: &BLSMinSigSignature,
+        dst: &[u8],
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify against an empty key list".into()));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let result = signature.0.fast_aggregate_verify(true, message, dst, &pks);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Distinct-message aggregate verification: public_keys[i] signed messages[i]
+    pub fn aggregate_verify(
+        public_keys: &[BLSMinSigPublicKey],
+        messages: &[&[u8]],
+        signature: &BLSMinSigSignature,
+        dst: &[u8],
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify against an empty key list".into()));
+        }
+        if public_keys.len() != messages.len() {
+            return Err(SignatureError::Verification(format!(
+                "Got {} public keys but {} messages", public_keys.len(), messages.len()
+            )));
+        }
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let result = signature.0.aggregate_verify(true, messages, dst, &pks, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Batch verification of independent signatures: public_keys[i] signed messages[i]
+    // producing signatures[i], all under one DST. A random linear combination, with
+    // scalars drawn from rng, folds them into one check.
+    pub fn verify_batch(
+        public_keys: &[BLSMinSigPublicKey],
+        messages: &[&[u8]],
+        signatures: &[BLSMinSigSignature],
+        dst: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<bool, SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::Verification("Cannot verify an empty batch".into()));
+        }
+        if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
+            return Err(SignatureError::Verification(format!(
+                "Got {} public keys, {} messages and {} signatures",
+                public_keys.len(), messages.len(), signatures.len()
+            )));
+        }
+        
+        // 64-bit non-zero blinding scalars, as recommended by blst
+        let rands: Vec<blst_scalar> = (0..public_keys.len())
+            .map(|_| {
+                let mut scalar = blst_scalar { b: [0u8; 32] };
+                rng.fill_bytes(&mut scalar.b[..8]);
+                scalar.b[0] |= 1;
+                scalar
+            })
+            .collect();
+        
+        let pks: Vec<&PublicKey> = public_keys.iter().map(|pk| &pk.0).collect();
+        let sigs: Vec<&Signature> = signatures.iter().map(|sig| &sig.0).collect();
+        let result = Signature::verify_multiple_aggregate_signatures(
+            messages, dst, &pks, true, &sigs, true, &rands, 64,
+        );
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Sign and verify under a caller-chosen DST; the trait methods use the default DST
+    pub fn sign_with_dst(private_key: &BLSMinSigPrivateKey, message: &[u8], dst: &[u8]) -> Result<BLSMinSigSignature, SignatureError> {
+        let sig = private_key.0.sign(message, dst, &[]);
+        
+        Ok(BLSMinSigSignature(sig))
+    }
+    
+    pub fn verify_with_dst(public_key: &BLSMinSigPublicKey, message: &[u8], signature: &BLSMinSigSignature, dst: &[u8]) -> Result<bool, SignatureError> {
+        let result = signature.0.verify(true, message, dst, &[], &public_key.0, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // Encode in either point format; the trait serializers always compress
+    pub fn encode_public_key(public_key: &BLSMinSigPublicKey, encoding: PointEncoding) -> Vec<u8> {
+        match encoding {
+            PointEncoding::Compressed => public_key.0.compress().to_vec(),
+            PointEncoding::Uncompressed => public_key.0.serialize().to_vec(),
+        }
+    }
+    
+    pub fn encode_signature(signature: &BLSMinSigSignature, encoding: PointEncoding) -> Vec<u8> {
+        match encoding {
+            PointEncoding::Compressed => signature.0.compress().to_vec(),
+            PointEncoding::Uncompressed => signature.0.serialize().to_vec(),
+        }
+    }
+    
+    // Proof of possession: a signature over the signer's own serialized public key
+    pub fn prove_possession(private_key: &BLSMinSigPrivateKey) -> Result<BLSMinSigSignature, SignatureError> {
+        let pk_bytes = Self::serialize_public_key(&BLSMinSigPublicKey(private_key.0.sk_to_pk()))?;
+        let pop = private_key.0.sign(&pk_bytes, POP_DST, &[]);
+        
+        Ok(BLSMinSigSignature(pop))
+    }
+    
+    pub fn verify_possession(public_key: &BLSMinSigPublicKey, pop: &BLSMinSigSignature) -> Result<bool, SignatureError> {
+        let pk_bytes = Self::serialize_public_key(public_key)?;
+        let result = pop.0.verify(true, &pk_bytes, POP_DST, &[], &public_key.0, true);
+        
+        Ok(result == BLST_ERROR::BLST_SUCCESS)
+    }
+    
+    // The public key belonging to a secret key
+    pub fn public_key(private_key: &BLSMinSigPrivateKey) -> BLSMinSigPublicKey {
+        BLSMinSigPublicKey(private_key.0.sk_to_pk())
+    }
+    
+    // KeyGen from the IETF BLS draft, with the seed as the input keying material
+    pub fn keypair_from_seed(seed: &[u8]) -> Result<(BLSMinSigPrivateKey, BLSMinSigPublicKey), SignatureError> {
+        let sk = SecretKey::key_gen(seed, &[]).map_err(|_| {
+            SignatureError::KeyGeneration(format!("Seed must be at least 32 bytes, got {}", seed.len()))
+        })?;
+        let pk = sk.sk_to_pk();
+        
+        Ok((BLSMinSigPrivateKey(sk), BLSMinSigPublicKey(pk)))
+    }
+}
+
+impl SignatureScheme for BLSMinSig {
+    type PrivateKey = BLSMinSigPrivateKey;
+    type PublicKey = BLSMinSigPublicKey;
+    type Signature = BLSMinSigSignature;
+    
+    fn name() -> &'static str {
+        "BLS12-381-min-sig"
+    }
+    
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let mut ikm = [0u8; 32];
+        rng.fill_bytes(&mut ikm);
+        
+        Self::keypair_from_seed(&ikm)
+    }
+    
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_dst(private_key, message, DST)
+    }
+    
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        Self::verify_with_dst(public_key, message, signature, DST)
+    }
+    
+    // Serialization methods for BLS min-sig keys and signatures (compressed points)
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.0.serialize().to_vec())
+    }
+    
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        match SecretKey::deserialize(bytes) {
+            Ok(sk) => Ok(BLSMinSigPrivateKey(sk)),
+            Err(_) => Err(SignatureError::Deserialization("Failed to deserialize BLS min-sig private key".into())),
+        }
+    }
+    
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(Self::encode_public_key(public_key, PointEncoding::Compressed))
+    }
+    
+    // Accepts both encodings: 96-byte compressed or 192-byte uncompressed G2 points.
+    // The identity and points outside the subgroup are rejected.
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        let pk = match bytes.len() {
+            96 => PublicKey::uncompress(bytes),
+            192 => PublicKey::deserialize(bytes),
+            len => return Err(SignatureError::Deserialization(format!(
+                "Invalid BLS min-sig public key length: expected 96 or 192 bytes, got {}", len
+            ))),
+        }
+        .map_err(|e| point_error("BLS min-sig public key", e))?;
+        
+        pk.validate().map_err(|e| point_error("BLS min-sig public key", e))?;
+        Ok(BLSMinSigPublicKey(pk))
+    }
+    
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(Self::encode_signature(signature, PointEncoding::Compressed))
+    }
+    
+    // Accepts both encodings: 48-byte compressed or 96-byte uncompressed G1 points.
+    // The identity and points outside the subgroup are rejected.
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        let sig = match bytes.len() {
+            48 => Signature::uncompress(bytes),
+            96 => Signature::deserialize(bytes),
+            len => return Err(SignatureError::Deserialization(format!(
+                "Invalid BLS min-sig signature length: expected 48 or 96 bytes, got {}", len
+            ))),
+        }
+        .map_err(|e| point_error("BLS min-sig signature", e))?;
+        
+        sig.validate(true).map_err(|e| point_error("BLS min-sig signature", e))?;
+        Ok(BLSMinSigSignature(sig))
+    }
+}
\ No newline at end of file
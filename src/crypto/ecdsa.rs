@@ -1,11 +1,200 @@
 use crate::crypto::scheme::{SignatureError,SignatureScheme};
-use rand::rngs::OsRng;
-use k256::ecdsa::{SigningKey,VerifyingKey, Signature as ECDSASignature};
+use rand_core::CryptoRngCore;
+use k256::ecdsa::{SigningKey,VerifyingKey, RecoveryId, Signature as ECDSASignature};
+use k256::FieldBytes;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
 use std::convert::TryFrom;
 
+/// Scheme recorded in signature files for Keccak-256 prehashed (Ethereum-style) ECDSA
+pub const KECCAK256_SCHEME: &str = "ECDSA-secp256k1-keccak256";
+
+/// Schemes recorded for 65-byte `r||s||v` recoverable signatures
+pub const RECOVERABLE_SCHEME: &str = "ECDSA-secp256k1-recoverable";
+pub const KECCAK256_RECOVERABLE_SCHEME: &str = "ECDSA-secp256k1-keccak256-recoverable";
+
+/// Scheme recorded for EIP-191 `personal_sign` signatures (recoverable, Keccak-256)
+pub const EIP191_SCHEME: &str = "ECDSA-secp256k1-eip191";
+
+/// Length of a recoverable signature: 64-byte `r||s` plus the recovery byte
+pub const RECOVERABLE_SIGNATURE_LEN: usize = 65;
+
+/// Wire format of a non-recoverable ECDSA signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    /// ASN.1 DER, as produced by OpenSSL
+    Der,
+    /// Fixed 64-byte `r||s`, as expected by most blockchain tooling
+    Compact,
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug)]
 pub struct ECDSA;
 
+// Ethereum-style signing over the Keccak-256 digest (not part of the trait)
+impl ECDSA {
+    pub fn sign_keccak256(private_key: &SigningKey, message: &[u8]) -> Result<ECDSASignature, SignatureError> {
+        use k256::ecdsa::signature::DigestSigner;
+
+        Ok(private_key.sign_digest(Keccak256::new_with_prefix(message)))
+    }
+
+    pub fn verify_keccak256(public_key: &VerifyingKey, message: &[u8], signature: &ECDSASignature) -> Result<bool, SignatureError> {
+        use k256::ecdsa::signature::DigestVerifier;
+
+        match public_key.verify_digest(Keccak256::new_with_prefix(message), signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Frame a message as EIP-191 `personal_sign` does before hashing it with Keccak-256
+    pub fn eip191_message(message: &[u8]) -> Vec<u8> {
+        let mut framed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        framed.extend_from_slice(message);
+        framed
+    }
+
+    /// Digest that recoverable signatures are computed over
+    pub fn message_digest(message: &[u8], keccak256: bool) -> [u8; 32] {
+        if keccak256 {
+            Keccak256::digest(message).into()
+        } else {
+            Sha256::digest(message).into()
+        }
+    }
+
+    /// Sign an already computed 32-byte digest
+    pub fn sign_prehash(private_key: &SigningKey, digest: &[u8]) -> Result<ECDSASignature, SignatureError> {
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+        private_key
+            .sign_prehash(digest)
+            .map_err(|e| SignatureError::Signing(e.to_string()))
+    }
+
+    /// Sign a 32-byte digest and return `r||s||v`, where `v` is the recovery
+    /// id offset by 27 as expected by Ethereum's `ecrecover`
+    pub fn sign_recoverable(private_key: &SigningKey, digest: &[u8]) -> Result<Vec<u8>, SignatureError> {
+        let (signature, recovery_id) = private_key
+            .sign_prehash_recoverable(digest)
+            .map_err(|e| SignatureError::Signing(e.to_string()))?;
+
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        Ok(bytes)
+    }
+
+    /// Sign a 32-byte digest with a hedged nonce: RFC 6979 with 32 bytes of extra entropy
+    /// mixed into `k` (section 3.6), so a fault during signing cannot leak the key through
+    /// two signatures sharing a nonce. Returns a low-S signature and its recovery id.
+    pub fn sign_prehash_hedged(private_key: &SigningKey, digest: &[u8], extra_entropy: &[u8; 32]) -> Result<(ECDSASignature, RecoveryId), SignatureError> {
+        use k256::ecdsa::hazmat::SignPrimitive;
+
+        if digest.len() != 32 {
+            return Err(SignatureError::Signing(format!("Digest must be 32 bytes, got {}", digest.len())));
+        }
+
+        let (signature, recovery_id) = private_key
+            .as_nonzero_scalar()
+            .as_ref()
+            .try_sign_prehashed_rfc6979::<Sha256>(FieldBytes::from_slice(digest), extra_entropy)
+            .map_err(|e| SignatureError::Signing(e.to_string()))?;
+        let recovery_id = recovery_id
+            .ok_or_else(|| SignatureError::Signing("Signing did not produce a recovery id".into()))?;
+
+        Ok((signature, recovery_id))
+    }
+
+    /// Hedged counterpart of `sign_recoverable`
+    pub fn sign_recoverable_hedged(private_key: &SigningKey, digest: &[u8], extra_entropy: &[u8; 32]) -> Result<Vec<u8>, SignatureError> {
+        let (signature, recovery_id) = Self::sign_prehash_hedged(private_key, digest, extra_entropy)?;
+
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        Ok(bytes)
+    }
+
+    /// Split a 65-byte recoverable signature into the signature and recovery id,
+    /// accepting `v` as either 0/1 or 27/28
+    pub fn split_recoverable(bytes: &[u8]) -> Result<(ECDSASignature, RecoveryId), SignatureError> {
+        if bytes.len() != RECOVERABLE_SIGNATURE_LEN {
+            return Err(SignatureError::Deserialization(format!(
+                "Invalid recoverable signature length: expected {} bytes, got {}",
+                RECOVERABLE_SIGNATURE_LEN, bytes.len()
+            )));
+        }
+
+        let signature = ECDSASignature::from_slice(&bytes[..64])
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))?;
+        let v = bytes[64];
+        let recovery_id = RecoveryId::from_byte(if v >= 27 { v - 27 } else { v })
+            .ok_or_else(|| SignatureError::Deserialization(format!(
+                "Invalid recovery byte v = {}: expected 0-3 or 27-30", v
+            )))?;
+
+        Ok((signature, recovery_id))
+    }
+
+    /// Verify a compact or DER signature over an already computed digest
+    pub fn verify_prehash(public_key: &VerifyingKey, digest: &[u8], signature: &[u8]) -> Result<bool, SignatureError> {
+        use k256::ecdsa::signature::hazmat::PrehashVerifier;
+
+        let signature = Self::deserialize_signature(signature)?;
+
+        match public_key.verify_prehash(digest, &signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Recover the signer's public key from a digest and a 65-byte signature
+    pub fn recover_public_key(digest: &[u8], bytes: &[u8]) -> Result<VerifyingKey, SignatureError> {
+        let (signature, recovery_id) = Self::split_recoverable(bytes)?;
+
+        VerifyingKey::recover_from_prehash(digest, &signature, recovery_id)
+            .map_err(|e| SignatureError::Verification(e.to_string()))
+    }
+
+    /// The compact low-S form of a compact or DER signature with a high S, or None if its S
+    /// is already low. k256 only verifies low-S signatures, but tools such as OpenSSL and
+    /// Node's crypto emit either, so verify --allow-high-s normalizes theirs first.
+    pub fn normalize_high_s(bytes: &[u8]) -> Result<Option<Vec<u8>>, SignatureError> {
+        let signature = Self::deserialize_signature(bytes)?;
+        Ok(signature.normalize_s().map(|low| low.to_bytes().to_vec()))
+    }
+
+    /// Encode a signature in either wire format; the trait serializer always emits DER
+    pub fn encode_signature(signature: &ECDSASignature, encoding: SignatureEncoding) -> Vec<u8> {
+        use k256::ecdsa::signature::SignatureEncoding as _;
+
+        match encoding {
+            SignatureEncoding::Der => signature.to_der().to_vec(),
+            SignatureEncoding::Compact => signature.to_bytes().to_vec(),
+        }
+    }
+
+    /// Ethereum address of a public key: the last 20 bytes of the Keccak-256 hash of
+    /// the uncompressed point, written with the EIP-55 mixed-case checksum
+    pub fn ethereum_address(public_key: &VerifyingKey) -> String {
+        let point = public_key.to_encoded_point(false);
+        let address = hex::encode(&Keccak256::digest(&point.as_bytes()[1..])[12..]);
+        let checksum = Keccak256::digest(address.as_bytes());
+
+        let mixed_case: String = address
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = if i % 2 == 0 { checksum[i / 2] >> 4 } else { checksum[i / 2] & 0x0f };
+                if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+            })
+            .collect();
+
+        format!("0x{}", mixed_case)
+    }
+}
+
 impl SignatureScheme for ECDSA{
 
     type PrivateKey = SigningKey;
@@ -16,9 +205,9 @@ impl SignatureScheme for ECDSA{
         "ECDSA-secp256k1"
     }
 
-    fn generate_keypair()->Result<(Self::PrivateKey,Self::PublicKey),SignatureError>{
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore)->Result<(Self::PrivateKey,Self::PublicKey),SignatureError>{
         
-        let private_key = SigningKey::random(&mut OsRng);
+        let private_key = SigningKey::random(rng);
 
         let public_key = VerifyingKey::from(&private_key);
 
@@ -51,8 +240,7 @@ impl SignatureScheme for ECDSA{
     }
 
     fn serialize_signature( signature: &Self::Signature)-> Result<Vec<u8>,SignatureError>{
-        use k256::ecdsa::signature::SignatureEncoding;
-        Ok(signature.to_der().to_vec())
+        Ok(Self::encode_signature(signature, SignatureEncoding::Der))
     }
 
     //deserialization
@@ -79,9 +267,128 @@ impl SignatureScheme for ECDSA{
         .map_err(|e| SignatureError::Deserialization(e.to_string()))
     }
 
+    // Auto-detects the encoding: exactly 64 bytes is compact r||s, anything else is DER.
+    // When neither parses, both failures are reported. A high-S signature is returned as it
+    // is and fails to verify: the two S values of one signature are the malleability BIP 62
+    // and Ethereum rule out, so accepting either is left to callers that ask for it through
+    // normalize_high_s.
     fn deserialize_signature(bytes: &[u8])->Result<Self::Signature,SignatureError> {
-        ECDSASignature::try_from(bytes)
-            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+        let compact = if bytes.len() == 64 {
+            ECDSASignature::try_from(bytes).map_err(|e| e.to_string())
+        } else {
+            Err(format!("expected 64 bytes, got {}", bytes.len()))
+        };
+
+        let signature = compact.or_else(|compact_error| {
+            ECDSASignature::from_der(bytes).map_err(|der_error| {
+                SignatureError::Deserialization(format!(
+                    "Signature is neither compact r||s ({}) nor DER ({})", compact_error, der_error
+                ))
+            })
+        })?;
+
+        Ok(signature)
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A key generated with `openssl ecparam -name secp256k1 -genkey`, and its signatures
+    // over MESSAGE from `openssl dgst -sha256 -sign` (DER) and from Node's crypto.sign with
+    // dsaEncoding ieee-p1363 (compact). Both happen to be high-S.
+    const PUBLIC_KEY: &str = "04a8bd15fc3826dabb3736ed361144774c5c3072d060ba7434dc3aa6e469b691333613841626f4a29714ba8146cb23f3b92dd2e7578dad9d713793251f8956f922";
+    const MESSAGE: &[u8] = b"sig-tool test message";
+    const OPENSSL_DER: &str = "30450220201e73d92897da55973f3a72253a3ca7211ee6bc0fa12d868f281c28834a5e2b0221008eb376aea7ced2aad4ebef0fd24c4b047043eca7786619c083a8576d3cb179ad";
+    const NODE_COMPACT: &str = "88ca19eee5a69d924cd57c2673657ba44572a8426e4fff5e9f1af9f589a9d263a29c6c833754129393974356af15aa9b73b2f45d1335d5bc4426cb6d3b0c67f2";
+
+    #[test]
+    fn deserialize_signature_accepts_der_and_compact() {
+        let public_key = ECDSA::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        for encoded in [OPENSSL_DER, NODE_COMPACT] {
+            let normalized = ECDSA::normalize_high_s(&hex::decode(encoded).unwrap()).unwrap().unwrap();
+            let signature = ECDSA::deserialize_signature(&normalized).unwrap();
+            assert!(ECDSA::verify(&public_key, MESSAGE, &signature).unwrap(), "{}", encoded);
+            assert!(!ECDSA::verify(&public_key, b"another message", &signature).unwrap(), "{}", encoded);
+            assert_eq!(ECDSA::normalize_high_s(&normalized).unwrap(), None);
+        }
+    }
+
+    // Without normalize_high_s a high-S signature parses but does not verify
+    #[test]
+    fn high_s_signatures_are_invalid_unless_normalized() {
+        let public_key = ECDSA::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        for encoded in [OPENSSL_DER, NODE_COMPACT] {
+            let bytes = hex::decode(encoded).unwrap();
+            let signature = ECDSA::deserialize_signature(&bytes).unwrap();
+            assert!(!ECDSA::verify(&public_key, MESSAGE, &signature).unwrap(), "{}", encoded);
+            let digest = ECDSA::message_digest(MESSAGE, false);
+            assert!(!ECDSA::verify_prehash(&public_key, &digest, &bytes).unwrap(), "{}", encoded);
+        }
+    }
+
+    // A signature over the Keccak-256 digest is not one over the SHA-256 digest of the same
+    // message, nor the other way round
+    #[test]
+    fn keccak256_and_sha256_signatures_do_not_cross_verify() {
+        let private_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let public_key = *private_key.verifying_key();
+
+        let keccak = ECDSA::sign_keccak256(&private_key, MESSAGE).unwrap();
+        let sha256 = ECDSA::sign(&private_key, MESSAGE).unwrap();
+        assert!(ECDSA::verify_keccak256(&public_key, MESSAGE, &keccak).unwrap());
+        assert!(ECDSA::verify(&public_key, MESSAGE, &sha256).unwrap());
+        assert!(!ECDSA::verify(&public_key, MESSAGE, &keccak).unwrap());
+        assert!(!ECDSA::verify_keccak256(&public_key, MESSAGE, &sha256).unwrap());
+
+        // The same holds for signatures over a digest computed up front
+        let digest = ECDSA::message_digest(MESSAGE, true);
+        let prehashed = ECDSA::encode_signature(&ECDSA::sign_prehash(&private_key, &digest).unwrap(), SignatureEncoding::Compact);
+        assert!(ECDSA::verify_prehash(&public_key, &digest, &prehashed).unwrap());
+        assert!(!ECDSA::verify_prehash(&public_key, &ECDSA::message_digest(MESSAGE, false), &prehashed).unwrap());
+    }
+
+    // The example of web3.js's accounts.sign documentation, which ethers' signMessage agrees
+    // with: "Some data" signed with EIP-191 framing by a known key
+    const ETH_PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const ETH_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const ETH_MESSAGE_HASH: &str = "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655";
+    const ETH_R: &str = "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd";
+    const ETH_S: &str = "6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a029";
+    const ETH_V: u8 = 0x1c;
+
+    #[test]
+    fn keccak256_signature_matches_web3() {
+        let private_key = SigningKey::from_slice(&hex::decode(ETH_PRIVATE_KEY).unwrap()).unwrap();
+        let public_key = *private_key.verifying_key();
+        assert_eq!(ECDSA::ethereum_address(&public_key), ETH_ADDRESS);
+
+        let framed = ECDSA::eip191_message(b"Some data");
+        let digest = ECDSA::message_digest(&framed, true);
+        assert_eq!(hex::encode(digest), ETH_MESSAGE_HASH);
+
+        // Over the raw 32-byte digest and over the message, r and s are the same
+        let signature = ECDSA::sign_recoverable(&private_key, &digest).unwrap();
+        assert_eq!(hex::encode(&signature[..32]), ETH_R);
+        assert_eq!(hex::encode(&signature[32..64]), ETH_S);
+        assert_eq!(signature[64], ETH_V);
+        let keccak = ECDSA::sign_keccak256(&private_key, &framed).unwrap();
+        assert_eq!(keccak.to_bytes()[..], signature[..64]);
+
+        assert!(ECDSA::verify_keccak256(&public_key, &framed, &keccak).unwrap());
+        assert_eq!(ECDSA::recover_public_key(&digest, &signature).unwrap(), public_key);
+    }
+
+    #[test]
+    fn deserialize_signature_reports_both_attempts() {
+        match ECDSA::deserialize_signature(&[0x30; 10]) {
+            Err(SignatureError::Deserialization(message)) => {
+                assert!(message.contains("compact r||s (expected 64 bytes, got 10)"), "{}", message);
+                assert!(message.contains("DER ("), "{}", message);
+            }
+            other => panic!("expected a deserialization error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
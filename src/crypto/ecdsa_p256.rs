@@ -0,0 +1,73 @@
+use crate::crypto::scheme::{SignatureError, SignatureScheme};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use rand_core::CryptoRngCore;
+
+#[derive(Debug)]
+pub struct EcdsaP256;
+
+impl SignatureScheme for EcdsaP256 {
+    type PrivateKey = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "ECDSA-P256"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::random(rng);
+        let public_key = VerifyingKey::from(&private_key);
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        use p256::ecdsa::signature::Signer;
+
+        Ok(private_key.sign(message))
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        use p256::ecdsa::signature::Verifier;
+
+        match public_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_encoded_point(true).as_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_der().as_bytes().to_vec())
+    }
+
+    //deserialization
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        if bytes.len() != 32 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid private key length: expected 32 bytes, got {}", bytes.len())
+            ));
+        }
+
+        SigningKey::from_slice(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        VerifyingKey::from_sec1_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::from_der(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
@@ -0,0 +1,82 @@
+use crate::crypto::scheme::{SignatureError, SignatureScheme};
+use p384::ecdsa::{Signature, SigningKey, VerifyingKey};
+use rand_core::CryptoRngCore;
+
+#[derive(Debug)]
+pub struct EcdsaP384;
+
+impl SignatureScheme for EcdsaP384 {
+    type PrivateKey = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "ECDSA-P384"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::random(rng);
+        let public_key = VerifyingKey::from(&private_key);
+
+        Ok((private_key, public_key))
+    }
+
+    // The `p384` signer hashes the message with SHA-384 before signing, as
+    // required by CNSA, and `verify` applies the same digest.
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        use p384::ecdsa::signature::Signer;
+
+        Ok(private_key.sign(message))
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        use p384::ecdsa::signature::Verifier;
+
+        match public_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_encoded_point(true).as_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_der().as_bytes().to_vec())
+    }
+
+    //deserialization
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        if bytes.len() != 48 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid private key length: expected 48 bytes, got {}", bytes.len())
+            ));
+        }
+
+        SigningKey::from_slice(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        // SEC1 compressed (49 bytes) or uncompressed (97 bytes) points only
+        if bytes.len() != 49 && bytes.len() != 97 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid public key length: expected 49 or 97 bytes, got {}", bytes.len())
+            ));
+        }
+
+        VerifyingKey::from_sec1_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::from_der(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
@@ -0,0 +1,111 @@
+use crate::crypto::scheme::{SignatureScheme, SignatureError};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::CryptoRngCore;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+pub struct ED25519;
+
+impl SignatureScheme for ED25519 {
+    type PrivateKey = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "Ed25519"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::generate(rng);
+        let public_key = private_key.verifying_key();
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Ok(private_key.sign(message))
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        match public_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods for Ed25519 keys and signatures
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        let key_bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+            SignatureError::Deserialization(format!(
+                "Invalid private key length: expected 32 bytes, got {}", bytes.len()
+            ))
+        })?;
+
+        Ok(SigningKey::from_bytes(&key_bytes))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        let key_bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+            SignatureError::Deserialization(format!(
+                "Invalid public key length: expected 32 bytes, got {}", bytes.len()
+            ))
+        })?;
+
+        VerifyingKey::from_bytes(&key_bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::from_slice(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032 section 7.1, TEST 2: a one-byte message
+    const SECRET_KEY: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+    const PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const MESSAGE: &[u8] = b"\x72";
+    const SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+    #[test]
+    fn rfc_8032_test_vector() {
+        let private_key = ED25519::deserialize_private_key(&hex::decode(SECRET_KEY).unwrap()).unwrap();
+        let public_key = ED25519::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        assert_eq!(ED25519::serialize_public_key(&private_key.verifying_key()).unwrap(), hex::decode(PUBLIC_KEY).unwrap());
+
+        let signature = ED25519::sign(&private_key, MESSAGE).unwrap();
+        assert_eq!(hex::encode(ED25519::serialize_signature(&signature).unwrap()), SIGNATURE);
+        let signature = ED25519::deserialize_signature(&hex::decode(SIGNATURE).unwrap()).unwrap();
+        assert!(ED25519::verify(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!ED25519::verify(&public_key, b"\x73", &signature).unwrap());
+    }
+
+    // Flipping a bit anywhere in R or S makes the signature invalid rather than an error
+    #[test]
+    fn corrupted_signature_is_invalid() {
+        let public_key = ED25519::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        let signature = hex::decode(SIGNATURE).unwrap();
+        for i in [0, 31, 32, 63] {
+            let mut corrupted = signature.clone();
+            corrupted[i] ^= 0x01;
+            let corrupted = ED25519::deserialize_signature(&corrupted).unwrap();
+            assert!(!ED25519::verify(&public_key, MESSAGE, &corrupted).unwrap(), "byte {}", i);
+        }
+    }
+}
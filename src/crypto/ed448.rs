@@ -0,0 +1,137 @@
+use crate::crypto::scheme::{SignatureScheme, SignatureError};
+use ed448_goldilocks_plus::{
+    Signature, SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH,
+};
+use rand_core::CryptoRngCore;
+
+#[derive(Debug)]
+pub struct Ed448;
+
+impl SignatureScheme for Ed448 {
+    type PrivateKey = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "Ed448"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::generate(rng);
+        let public_key = private_key.verifying_key();
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Ok(private_key.sign_raw(message))
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        match public_key.verify_raw(signature, message) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods: 57-byte keys and 114-byte signatures (RFC 8032)
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        if bytes.len() != SECRET_KEY_LENGTH {
+            return Err(SignatureError::Deserialization(format!(
+                "Invalid private key length: expected {} bytes, got {}", SECRET_KEY_LENGTH, bytes.len()
+            )));
+        }
+
+        SigningKey::try_from(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        let key_bytes: [u8; PUBLIC_KEY_LENGTH] = bytes.try_into().map_err(|_| {
+            SignatureError::Deserialization(format!(
+                "Invalid public key length: expected {} bytes, got {}", PUBLIC_KEY_LENGTH, bytes.len()
+            ))
+        })?;
+
+        let public_key = VerifyingKey::from_bytes(&key_bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))?;
+
+        // Decompression tolerates a non-reduced y coordinate, so re-encode and compare
+        if public_key.to_edwards().compress().to_bytes() != key_bytes {
+            return Err(SignatureError::Deserialization("Non-canonical Ed448 public key encoding".into()));
+        }
+
+        Ok(public_key)
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        let sig_bytes: [u8; SIGNATURE_LENGTH] = bytes.try_into().map_err(|_| {
+            SignatureError::Deserialization(format!(
+                "Invalid signature length: expected {} bytes, got {}", SIGNATURE_LENGTH, bytes.len()
+            ))
+        })?;
+
+        // Rejects an undecodable R and a non-canonical (unreduced) S
+        Signature::from_bytes(&sig_bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032 section 7.4, the first ("blank") Ed448 test vector
+    const SECRET_KEY: &str = "6c82a562cb808d10d632be89c8513ebf6c929f34ddfa8c9f63c9960ef6e348a3528c8a3fcc2f044e39a3fc5b94492f8f032e7549a20098f95b";
+    const PUBLIC_KEY: &str = "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180";
+    const SIGNATURE: &str = "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4dbb61149f05a7363268c71d95808ff2e652600";
+
+    #[test]
+    fn rfc_8032_test_vector() {
+        let private_key = Ed448::deserialize_private_key(&hex::decode(SECRET_KEY).unwrap()).unwrap();
+        let public_key = Ed448::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        assert_eq!(Ed448::serialize_public_key(&private_key.verifying_key()).unwrap(), hex::decode(PUBLIC_KEY).unwrap());
+
+        let signature = Ed448::sign(&private_key, b"").unwrap();
+        assert_eq!(hex::encode(Ed448::serialize_signature(&signature).unwrap()), SIGNATURE);
+        let signature = Ed448::deserialize_signature(&hex::decode(SIGNATURE).unwrap()).unwrap();
+        assert!(Ed448::verify(&public_key, b"", &signature).unwrap());
+        assert!(!Ed448::verify(&public_key, b"\x03", &signature).unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_lengths_and_non_canonical_encodings() {
+        assert!(Ed448::deserialize_private_key(&[0; 56]).is_err());
+        assert!(Ed448::deserialize_public_key(&hex::decode(PUBLIC_KEY).unwrap()[1..]).is_err());
+        assert!(Ed448::deserialize_signature(&hex::decode(SIGNATURE).unwrap()[1..]).is_err());
+
+        // A point of prime order with y = 19 parses, but not with y encoded unreduced as 19 + p
+        let mut canonical = [0u8; 57];
+        canonical[0] = 19;
+        assert!(Ed448::deserialize_public_key(&canonical).is_ok());
+        let mut unreduced = [0u8; 57];
+        unreduced[0] = 18;
+        unreduced[28..56].fill(0xff);
+        assert!(matches!(
+            Ed448::deserialize_public_key(&unreduced),
+            Err(SignatureError::Deserialization(message)) if message == "Non-canonical Ed448 public key encoding"
+        ));
+
+        // S at or above the group order
+        let mut signature = hex::decode(SIGNATURE).unwrap();
+        signature[57..113].fill(0xff);
+        assert!(Ed448::deserialize_signature(&signature).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::crypto::encryption::{aes128_ctr, pbkdf2_sha256, random_uuid, scrypt, MAX_PBKDF2_ITERATIONS};
+use crate::crypto::scheme::SignatureError;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+// EIP-2335 BLS keystores, as used by Ethereum validator clients: scrypt or PBKDF2
+// derives a 32-byte key, the first half encrypts the secret with AES-128-CTR and the
+// second half is hashed with the ciphertext into a checksum that detects a wrong
+// passphrase before anything is decrypted.
+
+pub const KEYSTORE_VERSION: u32 = 4;
+
+// Cost parameters written by export, the values EIP-2335 and staking-deposit-cli use
+const SCRYPT_N: u64 = 262144;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const PBKDF2_ITERATIONS: u32 = 262144;
+const DK_LEN: usize = 32;
+
+
+/// Key derivation function used to encrypt a keystore
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    Scrypt,
+    Pbkdf2,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Keystore {
+    pub crypto: KeystoreCrypto,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub pubkey: String,     //Hex-Encoded
+    #[serde(default)]
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeystoreCrypto {
+    pub kdf: KeystoreModule,
+    pub checksum: KeystoreModule,
+    pub cipher: KeystoreModule,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeystoreModule {
+    pub function: String,
+    pub params: serde_json::Value,
+    pub message: String,    //Hex-Encoded
+}
+
+impl Keystore {
+    /// Encrypt a 32-byte BLS secret key under a passphrase
+    pub fn encrypt(
+        secret: &[u8],
+        pubkey: &[u8],
+        path: &str,
+        description: &str,
+        passphrase: &str,
+        kdf: Kdf,
+    ) -> Result<Self, SignatureError> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut iv);
+
+        let kdf = match kdf {
+            Kdf::Scrypt => KeystoreModule {
+                function: "scrypt".into(),
+                params: json!({ "dklen": DK_LEN, "n": SCRYPT_N, "r": SCRYPT_R, "p": SCRYPT_P, "salt": hex::encode(salt) }),
+                message: String::new(),
+            },
+            Kdf::Pbkdf2 => KeystoreModule {
+                function: "pbkdf2".into(),
+                params: json!({ "dklen": DK_LEN, "c": PBKDF2_ITERATIONS, "prf": "hmac-sha256", "salt": hex::encode(salt) }),
+                message: String::new(),
+            },
+        };
+        let key = derive_key(&kdf, passphrase)?;
+
+        let mut ciphertext = secret.to_vec();
+        aes128_ctr(key[..16].try_into().expect("derived key is 32 bytes"), &iv, &mut ciphertext);
+
+        Ok(Self {
+            crypto: KeystoreCrypto {
+                kdf,
+                checksum: KeystoreModule {
+                    function: "sha256".into(),
+                    params: json!({}),
+                    message: hex::encode(checksum(&key, &ciphertext)),
+                },
+                cipher: KeystoreModule {
+                    function: "aes-128-ctr".into(),
+                    params: json!({ "iv": hex::encode(iv) }),
+                    message: hex::encode(&ciphertext),
+                },
+            },
+            description: description.to_string(),
+            pubkey: hex::encode(pubkey),
+            path: path.to_string(),
+            uuid: random_uuid(),
+            version: KEYSTORE_VERSION,
+        })
+    }
+
+    /// Check the passphrase against the checksum and decrypt the secret key
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, SignatureError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(invalid(format!("unsupported keystore version {}", self.version)));
+        }
+        if self.crypto.checksum.function != "sha256" {
+            return Err(invalid(format!("unsupported checksum function {}", self.crypto.checksum.function)));
+        }
+        if self.crypto.cipher.function != "aes-128-ctr" {
+            return Err(invalid(format!("unsupported cipher {}", self.crypto.cipher.function)));
+        }
+
+        let key = derive_key(&self.crypto.kdf, passphrase)?;
+        let mut ciphertext = decode_hex(&self.crypto.cipher.message, "cipher message")?;
+        let expected = decode_hex(&self.crypto.checksum.message, "checksum")?;
+        if checksum(&key, &ciphertext)[..] != expected[..] {
+            return Err(SignatureError::Deserialization(
+                "Keystore checksum mismatch: wrong passphrase or corrupted keystore".into(),
+            ));
+        }
+
+        let iv: [u8; 16] = decode_hex(string_param(&self.crypto.cipher.params, "iv")?, "iv")?
+            .try_into()
+            .map_err(|_| invalid("iv must be 16 bytes".into()))?;
+        aes128_ctr(key[..16].try_into().expect("derived key is 32 bytes"), &iv, &mut ciphertext);
+        Ok(ciphertext)
+    }
+}
+
+// EIP-2335 passphrases are NFKD-normalized with control characters removed. Only ASCII
+// is accepted, where normalization changes nothing, rather than risk a key derived from
+// bytes other wallets would not produce.
+fn process_passphrase(passphrase: &str) -> Result<Vec<u8>, SignatureError> {
+    if !passphrase.is_ascii() {
+        return Err(SignatureError::Serialization(
+            "EIP-2335 passphrases must be ASCII; Unicode normalization is not supported".into(),
+        ));
+    }
+    Ok(passphrase.bytes().filter(|b| !b.is_ascii_control()).collect())
+}
+
+fn derive_key(kdf: &KeystoreModule, passphrase: &str) -> Result<[u8; DK_LEN], SignatureError> {
+    let passphrase = process_passphrase(passphrase)?;
+    let params = &kdf.params;
+    if uint_param(params, "dklen")? != DK_LEN as u64 {
+        return Err(invalid(format!("dklen must be {}", DK_LEN)));
+    }
+    let salt = decode_hex(string_param(params, "salt")?, "salt")?;
+
+    let mut key = [0u8; DK_LEN];
+    match kdf.function.as_str() {
+        "scrypt" => {
+            let n = uint_param(params, "n")?;
+            let r = u32::try_from(uint_param(params, "r")?).map_err(|_| invalid("r is too large".into()))?;
+            let p = u32::try_from(uint_param(params, "p")?).map_err(|_| invalid("p is too large".into()))?;
+            scrypt(&passphrase, &salt, n, r, p, &mut key)?;
+        }
+        "pbkdf2" => {
+            if string_param(params, "prf")? != "hmac-sha256" {
+                return Err(invalid("only the hmac-sha256 PBKDF2 PRF is supported".into()));
+            }
+            let c = uint_param(params, "c")?;
+            if c == 0 || c > MAX_PBKDF2_ITERATIONS as u64 {
+                return Err(invalid(format!("PBKDF2 iteration count {} is out of range", c)));
+            }
+            pbkdf2_sha256(&passphrase, &salt, c as u32, &mut key);
+        }
+        other => return Err(invalid(format!("unsupported KDF {}", other))),
+    }
+    Ok(key)
+}
+
+fn checksum(key: &[u8; DK_LEN], ciphertext: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(&key[16..]).chain_update(ciphertext).finalize().into()
+}
+
+fn invalid(message: String) -> SignatureError {
+    SignatureError::Deserialization(format!("Invalid EIP-2335 keystore: {}", message))
+}
+
+fn string_param<'a>(params: &'a serde_json::Value, name: &str) -> Result<&'a str, SignatureError> {
+    params[name].as_str().ok_or_else(|| invalid(format!("missing string parameter {}", name)))
+}
+
+fn uint_param(params: &serde_json::Value, name: &str) -> Result<u64, SignatureError> {
+    params[name].as_u64().ok_or_else(|| invalid(format!("missing integer parameter {}", name)))
+}
+
+fn decode_hex(value: &str, what: &str) -> Result<Vec<u8>, SignatureError> {
+    hex::decode(value).map_err(|_| invalid(format!("{} is not hex", what)))
+}
@@ -0,0 +1,205 @@
+use crate::crypto::scheme::SignatureError;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::Sha256;
+
+// Passphrase-based encryption for key material that leaves the keystore: Argon2id
+// (RFC 9106) derives the key and ChaCha20-Poly1305 (RFC 8439) encrypts under it.
+// scrypt, PBKDF2 and AES-128-CTR are here for the Ethereum keystore formats.
+
+/// Argon2id cost parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Argon2Params {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    // OWASP's recommended minimum for Argon2id: 19 MiB, 2 passes, 1 lane
+    fn default() -> Self {
+        Self { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+}
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+// Exports and backups are untrusted input; refuse Argon2id costs far beyond what seal writes
+pub const MAX_ARGON2_MEMORY_KIB: u32 = 1 << 20;
+pub const MAX_ARGON2_ITERATIONS: u32 = 32;
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Derive `out.len()` bytes from a passphrase and salt with Argon2id
+pub fn argon2id(passphrase: &[u8], salt: &[u8], params: &Argon2Params, out: &mut [u8]) -> Result<(), SignatureError> {
+    let Argon2Params { memory_kib, iterations, parallelism } = *params;
+    let (salt_len, out_len) = (salt.len(), out.len());
+    let invalid = || {
+        SignatureError::KeyGeneration(format!(
+            "Invalid Argon2id parameters: m={} KiB, t={}, p={}, {}-byte salt, {}-byte output",
+            memory_kib, iterations, parallelism, salt_len, out_len
+        ))
+    };
+    let min_memory = parallelism.checked_mul(8).ok_or_else(invalid)?;
+    if parallelism == 0 || iterations == 0 || memory_kib < min_memory || salt_len < 8 || out_len < 4 {
+        return Err(invalid());
+    }
+    if memory_kib > MAX_ARGON2_MEMORY_KIB || iterations > MAX_ARGON2_ITERATIONS || parallelism > MAX_ARGON2_PARALLELISM {
+        return Err(SignatureError::KeyGeneration(format!(
+            "Argon2id parameters m={} KiB, t={}, p={} exceed the limits of {} KiB, {} passes and {} lanes",
+            memory_kib, iterations, parallelism, MAX_ARGON2_MEMORY_KIB, MAX_ARGON2_ITERATIONS, MAX_ARGON2_PARALLELISM
+        )));
+    }
+
+    let params = Params::new(memory_kib, iterations, parallelism, Some(out_len)).map_err(|_| invalid())?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, out)
+        .map_err(|_| invalid())
+}
+
+/// Encrypt and authenticate `plaintext` (and authenticate `aad`), returning the
+/// ciphertext with the 16-byte tag appended
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    ChaCha20Poly1305::new(key.into())
+        .encrypt(nonce.into(), Payload { msg: plaintext, aad })
+        .expect("ChaCha20-Poly1305 encrypts messages up to 256 GiB")
+}
+
+/// Check the tag of a sealed message and decrypt it
+pub fn open(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(nonce.into(), Payload { msg: sealed, aad })
+        .map_err(|_| SignatureError::Deserialization("Decryption failed: wrong passphrase or corrupted data".into()))
+}
+
+/// Random (version 4) UUID, as keystore files use for their id
+pub fn random_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+// Keystore files are untrusted input; refuse KDF costs far beyond anything a wallet writes
+pub const MAX_PBKDF2_ITERATIONS: u32 = 1 << 24;
+const MAX_SCRYPT_MEMORY: u64 = 2 << 30;
+
+// PBKDF2 (RFC 8018) with HMAC-SHA256
+
+/// PBKDF2-HMAC-SHA256, filling `out`
+pub fn pbkdf2_sha256(passphrase: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase, salt, iterations, out);
+}
+
+// scrypt (RFC 7914)
+
+/// Derive `out.len()` bytes from a passphrase and salt with scrypt
+pub fn scrypt(passphrase: &[u8], salt: &[u8], n: u64, r: u32, p: u32, out: &mut [u8]) -> Result<(), SignatureError> {
+    let invalid = || SignatureError::KeyGeneration(format!("Invalid scrypt parameters: n={}, r={}, p={}", n, r, p));
+    if n < 2 || !n.is_power_of_two() || r == 0 || p == 0 {
+        return Err(invalid());
+    }
+    if (n as u128) * (128 * r as u128) > MAX_SCRYPT_MEMORY as u128 {
+        return Err(SignatureError::KeyGeneration(format!("scrypt parameters n={}, r={} need more than 2 GiB", n, r)));
+    }
+
+    let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, out.len()).map_err(|_| invalid())?;
+    scrypt::scrypt(passphrase, salt, &params, out).map_err(|_| invalid())
+}
+
+// AES-128 (FIPS 197) in CTR mode with a 128-bit big-endian counter
+
+/// Encrypt or decrypt `data` in place with AES-128-CTR
+pub fn aes128_ctr(key: &[u8; 16], iv: &[u8; 16], data: &mut [u8]) {
+    ctr::Ctr128BE::<aes::Aes128>::new(key.into(), iv.into()).apply_keystream(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8439 section 2.8.2
+    const AEAD_KEY: &str = "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f";
+    const AEAD_NONCE: &str = "070000004041424344454647";
+    const AEAD_AAD: &str = "50515253c0c1c2c3c4c5c6c7";
+    const AEAD_PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    const AEAD_CIPHERTEXT: &str = "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116";
+    const AEAD_TAG: &str = "1ae10b594f09e26a7e902ecbd0600691";
+
+    #[test]
+    fn rfc_8439_aead_test_vector() {
+        let key: [u8; KEY_LEN] = hex::decode(AEAD_KEY).unwrap().try_into().unwrap();
+        let nonce: [u8; NONCE_LEN] = hex::decode(AEAD_NONCE).unwrap().try_into().unwrap();
+        let aad = hex::decode(AEAD_AAD).unwrap();
+
+        let sealed = seal(&key, &nonce, &aad, AEAD_PLAINTEXT);
+        assert_eq!(hex::encode(&sealed), format!("{}{}", AEAD_CIPHERTEXT, AEAD_TAG));
+        assert_eq!(open(&key, &nonce, &aad, &sealed).unwrap(), AEAD_PLAINTEXT);
+
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 1;
+        assert!(open(&key, &nonce, &aad, &tampered).is_err());
+        assert!(open(&key, &nonce, b"", &sealed).is_err());
+        assert!(open(&key, &nonce, &aad, &sealed[..TAG_LEN - 1]).is_err());
+    }
+
+    // The Argon2id vector of the reference implementation's test suite (phc-winner-argon2
+    // src/test.c): m = 64 MiB, t = 2, p = 1, no secret or associated data
+    #[test]
+    fn argon2id_reference_test_vector() {
+        let params = Argon2Params { memory_kib: 1 << 16, iterations: 2, parallelism: 1 };
+        let mut out = [0u8; 32];
+        argon2id(b"password", b"somesalt", &params, &mut out).unwrap();
+        assert_eq!(hex::encode(out), "09316115d5cf24ed5a15a31a3ba326e5cf32edc24702987c02b6566f61913cf7");
+    }
+
+    #[test]
+    fn argon2id_refuses_out_of_range_parameters() {
+        let mut out = [0u8; 32];
+        for params in [
+            Argon2Params { memory_kib: 1 << 16, iterations: 2, parallelism: u32::MAX },
+            Argon2Params { memory_kib: u32::MAX, iterations: 2, parallelism: 1 },
+            Argon2Params { memory_kib: 1 << 16, iterations: u32::MAX, parallelism: 1 },
+            Argon2Params { memory_kib: 1 << 16, iterations: 0, parallelism: 1 },
+        ] {
+            assert!(argon2id(b"password", b"somesalt", &params, &mut out).is_err(), "{:?}", params);
+        }
+    }
+
+    // FIPS 197 appendix C.1: with the plaintext block as the counter, the first block of
+    // keystream is the block's AES-128 encryption
+    #[test]
+    fn fips_197_aes128_test_vector() {
+        let key: [u8; 16] = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap().try_into().unwrap();
+        let block: [u8; 16] = hex::decode("00112233445566778899aabbccddeeff").unwrap().try_into().unwrap();
+        let mut data = [0u8; 32];
+        aes128_ctr(&key, &block, &mut data);
+        assert_eq!(hex::encode(&data[..16]), "69c4e0d86a7b0430d8cdb78070b4c55a");
+
+        aes128_ctr(&key, &block, &mut data);
+        assert_eq!(data, [0u8; 32]);
+    }
+
+    // RFC 7914 sections 11 and 12
+    #[test]
+    fn rfc_7914_test_vectors() {
+        let mut out = [0u8; 64];
+        pbkdf2_sha256(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(hex::encode(out), "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783");
+
+        scrypt(b"", b"", 16, 1, 1, &mut out).unwrap();
+        assert_eq!(hex::encode(out), "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906");
+        scrypt(b"password", b"NaCl", 1024, 8, 16, &mut out).unwrap();
+        assert_eq!(hex::encode(out), "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640");
+
+        assert!(scrypt(b"password", b"NaCl", 1000, 8, 16, &mut out).is_err());
+        assert!(scrypt(b"password", b"NaCl", 1 << 30, 8, 1, &mut out).is_err());
+    }
+}
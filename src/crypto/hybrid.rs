@@ -0,0 +1,200 @@
+use crate::crypto::ed25519::ED25519;
+use crate::crypto::mldsa::{MlDsa65, MlDsaPrivateKey, MlDsaPublicKey, MlDsaSignature};
+use crate::crypto::scheme::{SignatureScheme, SignatureError};
+use crate::crypto::wrapper::impl_wrapper_encoding;
+use rand_core::CryptoRngCore;
+
+/// Composite scheme producing an Ed25519 and an ML-DSA-65 signature over the
+/// same message; a signature is only valid when both components verify.
+#[derive(Debug)]
+pub struct HybridEd25519MlDsa;
+
+// Bundled key pairs and signatures, one component per underlying scheme
+#[derive(Clone)]
+pub struct HybridPrivateKey {
+    classical: <ED25519 as SignatureScheme>::PrivateKey,
+    post_quantum: MlDsaPrivateKey,
+}
+
+#[derive(Clone)]
+pub struct HybridPublicKey {
+    classical: <ED25519 as SignatureScheme>::PublicKey,
+    post_quantum: MlDsaPublicKey,
+}
+
+#[derive(Clone)]
+pub struct HybridSignature {
+    classical: <ED25519 as SignatureScheme>::Signature,
+    post_quantum: MlDsaSignature,
+}
+
+impl_wrapper_encoding!(HybridPublicKey, HybridEd25519MlDsa, serialize_public_key, deserialize_public_key, "public key");
+impl_wrapper_encoding!(HybridSignature, HybridEd25519MlDsa, serialize_signature, deserialize_signature, "signature");
+
+impl SignatureScheme for HybridEd25519MlDsa {
+    type PrivateKey = HybridPrivateKey;
+    type PublicKey = HybridPublicKey;
+    type Signature = HybridSignature;
+
+    fn name() -> &'static str {
+        "Composite-Ed25519+ML-DSA-65"
+    }
+
+    fn generate_keypair() -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let (classical_sk, classical_pk) = ED25519::generate_keypair()?;
+        let (pq_sk, pq_pk) = MlDsa65::generate_keypair()?;
+
+        Ok((
+            HybridPrivateKey { classical: classical_sk, post_quantum: pq_sk },
+            HybridPublicKey { classical: classical_pk, post_quantum: pq_pk },
+        ))
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let (classical_sk, classical_pk) = ED25519::generate_keypair_with_rng(rng)?;
+        let (pq_sk, pq_pk) = MlDsa65::generate_keypair_with_rng(rng)?;
+
+        Ok((
+            HybridPrivateKey { classical: classical_sk, post_quantum: pq_sk },
+            HybridPublicKey { classical: classical_pk, post_quantum: pq_pk },
+        ))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Ok(HybridSignature {
+            classical: ED25519::sign(&private_key.classical, message)?,
+            post_quantum: MlDsa65::sign(&private_key.post_quantum, message)?,
+        })
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        let classical_ok = ED25519::verify(&public_key.classical, message, &signature.classical)?;
+        let post_quantum_ok = MlDsa65::verify(&public_key.post_quantum, message, &signature.post_quantum)?;
+
+        Ok(classical_ok && post_quantum_ok)
+    }
+
+    // Every encoding is `len(classical) || classical || len(pq) || pq`
+    // with 4-byte big-endian lengths
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(encode_components(
+            &ED25519::serialize_private_key(&private_key.classical)?,
+            &MlDsa65::serialize_private_key(&private_key.post_quantum)?,
+        ))
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(encode_components(
+            &ED25519::serialize_public_key(&public_key.classical)?,
+            &MlDsa65::serialize_public_key(&public_key.post_quantum)?,
+        ))
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(encode_components(
+            &ED25519::serialize_signature(&signature.classical)?,
+            &MlDsa65::serialize_signature(&signature.post_quantum)?,
+        ))
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        let (classical, post_quantum) = decode_components(bytes)?;
+
+        Ok(HybridPrivateKey {
+            classical: ED25519::deserialize_private_key(classical)?,
+            post_quantum: MlDsa65::deserialize_private_key(post_quantum)?,
+        })
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        let (classical, post_quantum) = decode_components(bytes)?;
+
+        Ok(HybridPublicKey {
+            classical: ED25519::deserialize_public_key(classical)?,
+            post_quantum: MlDsa65::deserialize_public_key(post_quantum)?,
+        })
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        let (classical, post_quantum) = decode_components(bytes)?;
+
+        Ok(HybridSignature {
+            classical: ED25519::deserialize_signature(classical)?,
+            post_quantum: MlDsa65::deserialize_signature(post_quantum)?,
+        })
+    }
+}
+
+fn encode_components(classical: &[u8], post_quantum: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + classical.len() + post_quantum.len());
+    for part in [classical, post_quantum] {
+        out.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+fn decode_components(bytes: &[u8]) -> Result<(&[u8], &[u8]), SignatureError> {
+    let (classical, rest) = split_component(bytes)?;
+    let (post_quantum, rest) = split_component(rest)?;
+
+    if !rest.is_empty() {
+        return Err(SignatureError::Deserialization(
+            format!("Trailing {} bytes after composite encoding", rest.len())
+        ));
+    }
+
+    Ok((classical, post_quantum))
+}
+
+fn split_component(bytes: &[u8]) -> Result<(&[u8], &[u8]), SignatureError> {
+    if bytes.len() < 4 {
+        return Err(SignatureError::Deserialization("Truncated composite length prefix".into()));
+    }
+
+    let (len_bytes, rest) = bytes.split_at(4);
+    let len = u32::from_be_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+    if rest.len() < len {
+        return Err(SignatureError::Deserialization(
+            format!("Composite component claims {} bytes but only {} remain", len, rest.len())
+        ));
+    }
+
+    Ok(rest.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = b"sig-tool hybrid test message";
+
+    // Offsets into `len || Ed25519 signature || len || ML-DSA signature`
+    const CLASSICAL: usize = 4;
+    const POST_QUANTUM: usize = 4 + 64 + 4;
+
+    // Both halves must verify: a signature with either one corrupted, or taken from a
+    // signature over another message, is rejected
+    #[test]
+    fn rejects_a_signature_with_either_half_invalid() {
+        let (private_key, public_key) = HybridEd25519MlDsa::generate_keypair().unwrap();
+        let signature = HybridEd25519MlDsa::serialize_signature(&HybridEd25519MlDsa::sign(&private_key, MESSAGE).unwrap()).unwrap();
+        let verify = |bytes: &[u8]| {
+            HybridEd25519MlDsa::verify(&public_key, MESSAGE, &HybridEd25519MlDsa::deserialize_signature(bytes).unwrap()).unwrap()
+        };
+        assert!(verify(&signature));
+
+        for offset in [CLASSICAL, CLASSICAL + 63, POST_QUANTUM, signature.len() - 1] {
+            let mut corrupted = signature.clone();
+            corrupted[offset] ^= 0x01;
+            assert!(!verify(&corrupted), "bit flipped at {}", offset);
+        }
+
+        let other = HybridEd25519MlDsa::sign(&private_key, b"another message").unwrap();
+        let other = HybridEd25519MlDsa::serialize_signature(&other).unwrap();
+        let classical_swapped = [&other[..POST_QUANTUM], &signature[POST_QUANTUM..]].concat();
+        let post_quantum_swapped = [&signature[..POST_QUANTUM], &other[POST_QUANTUM..]].concat();
+        assert!(!verify(&classical_swapped));
+        assert!(!verify(&post_quantum_swapped));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::crypto::scheme::{SignatureScheme, SignatureError};
+use crate::crypto::wrapper::impl_wrapper_encoding;
+use ml_dsa::{KeyGen, B32};
+use pqcrypto_mldsa::mldsa65::{self, DetachedSignature, PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use pqcrypto_traits::sign::{
+    DetachedSignature as _, PublicKey as _, SecretKey as _,
+};
+
+#[derive(Debug)]
+pub struct MlDsa65;
+
+// Wrapper types so the opaque pqcrypto structs satisfy the trait bounds
+#[derive(Clone)]
+pub struct MlDsaPrivateKey(SecretKey);
+
+#[derive(Clone)]
+pub struct MlDsaPublicKey(PublicKey);
+
+#[derive(Clone)]
+pub struct MlDsaSignature(DetachedSignature);
+
+impl_wrapper_encoding!(MlDsaPublicKey, MlDsa65, serialize_public_key, deserialize_public_key, "public key");
+impl_wrapper_encoding!(MlDsaSignature, MlDsa65, serialize_signature, deserialize_signature, "signature");
+
+impl MlDsa65 {
+    /// The key pair FIPS 204's ML-DSA.KeyGen_internal derives from a 32-byte seed, as
+    /// OpenSSL's `-pkeyopt hexseed:` does. pqcrypto cannot take a seed, so the ml-dsa crate
+    /// derives the key and pqcrypto reads its encodings.
+    pub fn keypair_from_seed(seed: &[u8; 32]) -> Result<(MlDsaPrivateKey, MlDsaPublicKey), SignatureError> {
+        let keypair = ml_dsa::MlDsa65::key_gen_internal(&B32::from(*seed));
+        let private_key = Self::deserialize_private_key(&keypair.signing_key().encode())?;
+        let public_key = Self::deserialize_public_key(&keypair.verifying_key().encode())?;
+
+        Ok((private_key, public_key))
+    }
+}
+
+impl SignatureScheme for MlDsa65 {
+    type PrivateKey = MlDsaPrivateKey;
+    type PublicKey = MlDsaPublicKey;
+    type Signature = MlDsaSignature;
+
+    fn name() -> &'static str {
+        "ML-DSA-65"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        Self::keypair_from_seed(&seed)
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Ok(MlDsaSignature(mldsa65::detached_sign(message, &private_key.0)))
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        match mldsa65::verify_detached_signature(&signature.0, message, &public_key.0) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods: raw FIPS 204 encodings (4032-byte secret key,
+    // 1952-byte public key, 3309-byte signature)
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.0.as_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.0.as_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.0.as_bytes().to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        SecretKey::from_bytes(bytes)
+            .map(MlDsaPrivateKey)
+            .map_err(|_| SignatureError::Deserialization(format!(
+                "Invalid ML-DSA-65 private key length: expected {} bytes, got {}",
+                mldsa65::secret_key_bytes(), bytes.len()
+            )))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        PublicKey::from_bytes(bytes)
+            .map(MlDsaPublicKey)
+            .map_err(|_| SignatureError::Deserialization(format!(
+                "Invalid ML-DSA-65 public key length: expected {} bytes, got {}",
+                mldsa65::public_key_bytes(), bytes.len()
+            )))
+    }
+
+    // pqcrypto takes anything up to the signature length, so shorter input is refused here
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        if bytes.len() != mldsa65::signature_bytes() {
+            return Err(SignatureError::Deserialization(format!(
+                "Invalid ML-DSA-65 signature length: expected {} bytes, got {}",
+                mldsa65::signature_bytes(), bytes.len()
+            )));
+        }
+
+        DetachedSignature::from_bytes(bytes)
+            .map(MlDsaSignature)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A key and signature from OpenSSL 3.5's independent ML-DSA implementation:
+    //   openssl genpkey -algorithm ML-DSA-65 -pkeyopt hexseed:000102...1e1f -out key.pem
+    //   openssl pkey -in key.pem -pubout -outform DER | tail -c 1952 > ml-dsa-65-openssl.pub
+    //   openssl pkeyutl -sign -inkey key.pem -rawin -in message -pkeyopt deterministic:1
+    const OPENSSL_PUBLIC_KEY: &[u8] = include_bytes!("testdata/ml-dsa-65-openssl.pub");
+    const OPENSSL_SIGNATURE: &[u8] = include_bytes!("testdata/ml-dsa-65-openssl.sig");
+    const MESSAGE: &[u8] = b"sig-tool ML-DSA-65 cross-check";
+
+    // OpenSSL's key above, generated from the seed bytes 0..32
+    #[test]
+    fn keypair_from_seed_matches_openssl() {
+        let seed: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let (private_key, public_key) = MlDsa65::keypair_from_seed(&seed).unwrap();
+        assert_eq!(MlDsa65::serialize_public_key(&public_key).unwrap(), OPENSSL_PUBLIC_KEY);
+
+        let signature = MlDsa65::sign(&private_key, MESSAGE).unwrap();
+        let openssl_public_key = MlDsa65::deserialize_public_key(OPENSSL_PUBLIC_KEY).unwrap();
+        assert!(MlDsa65::verify(&openssl_public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn verifies_a_signature_from_openssl() {
+        let public_key = MlDsa65::deserialize_public_key(OPENSSL_PUBLIC_KEY).unwrap();
+        let signature = MlDsa65::deserialize_signature(OPENSSL_SIGNATURE).unwrap();
+        assert!(MlDsa65::verify(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!MlDsa65::verify(&public_key, b"another message", &signature).unwrap());
+    }
+
+    #[test]
+    fn encodings_have_the_fips_204_sizes() {
+        let (private_key, public_key) = MlDsa65::generate_keypair().unwrap();
+        let signature = MlDsa65::sign(&private_key, MESSAGE).unwrap();
+        assert_eq!(MlDsa65::serialize_private_key(&private_key).unwrap().len(), 4032);
+        assert_eq!(MlDsa65::serialize_public_key(&public_key).unwrap().len(), 1952);
+        assert_eq!(MlDsa65::serialize_signature(&signature).unwrap().len(), 3309);
+
+        assert!(MlDsa65::deserialize_public_key(&OPENSSL_PUBLIC_KEY[1..]).is_err());
+        assert!(MlDsa65::deserialize_signature(&OPENSSL_SIGNATURE[1..]).is_err());
+    }
+}
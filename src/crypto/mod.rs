@@ -1,8 +1,52 @@
 pub mod scheme;
 pub mod ecdsa;
+#[cfg(feature = "bls")]
 pub mod bls;
+#[cfg(feature = "bls")]
+pub mod bls_min_sig;
+pub mod ed25519;
+pub mod schnorr;
+pub mod ecdsa_p256;
+pub mod ecdsa_p384;
+pub mod rsa_pss;
+pub mod ed448;
+#[cfg(feature = "ml-dsa")]
+pub mod mldsa;
+pub mod slh_dsa;
+pub mod sr25519;
+#[cfg(feature = "ml-dsa")]
+pub mod hybrid;
+pub mod bitcoin;
+pub mod bip322;
+pub mod prehash;
+#[cfg(feature = "keystore")]
+pub mod encryption;
+pub mod pem;
+#[cfg(feature = "keystore")]
+pub mod eip2335;
+#[cfg(feature = "keystore")]
+pub mod web3_keystore;
+pub mod registry;
+#[cfg(any(feature = "bls", feature = "ml-dsa"))]
+pub(crate) mod wrapper;
 
 // Re-export for easier use
 pub use scheme::{SignatureError,SignatureScheme};
 pub use ecdsa::ECDSA;
+#[cfg(feature = "bls")]
 pub use bls::BLS;
+#[cfg(feature = "bls")]
+pub use bls_min_sig::BLSMinSig;
+pub use ed25519::ED25519;
+pub use schnorr::Schnorr;
+pub use ecdsa_p256::EcdsaP256;
+pub use ecdsa_p384::EcdsaP384;
+pub use rsa_pss::RsaPss;
+pub use ed448::Ed448;
+#[cfg(feature = "ml-dsa")]
+pub use mldsa::MlDsa65;
+pub use slh_dsa::SlhDsa;
+pub use sr25519::Sr25519;
+#[cfg(feature = "ml-dsa")]
+pub use hybrid::HybridEd25519MlDsa;
+pub use registry::DynScheme;
@@ -0,0 +1,80 @@
+use crate::crypto::scheme::SignatureError;
+use base64::prelude::*;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+// Minimal writer for PEM-encoded SubjectPublicKeyInfo (RFC 5280), for the schemes that
+// have a standard algorithm identifier
+
+// DER-encoded object identifiers
+const ID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const SECP256K1: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+const PRIME256V1: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const SECP384R1: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22];
+const ID_ED25519: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+const ID_ED448: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x71];
+const ID_ML_DSA_65: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x12];
+const ID_SLH_DSA_SHA2_128S: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x14];
+
+/// DER SubjectPublicKeyInfo for a public key as serialized by the scheme
+pub fn public_key_der(scheme: &str, public_key: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    // EC keys are stored compressed; uncompressed points are the form every reader supports
+    let (algorithm, key) = match scheme {
+        "ECDSA-secp256k1" => {
+            let point = k256::PublicKey::from_sec1_bytes(public_key)
+                .map_err(|e| SignatureError::Serialization(e.to_string()))?;
+            ([ID_EC_PUBLIC_KEY, SECP256K1].concat(), point.to_encoded_point(false).as_bytes().to_vec())
+        }
+        "ECDSA-P256" => {
+            let point = p256::PublicKey::from_sec1_bytes(public_key)
+                .map_err(|e| SignatureError::Serialization(e.to_string()))?;
+            ([ID_EC_PUBLIC_KEY, PRIME256V1].concat(), point.to_encoded_point(false).as_bytes().to_vec())
+        }
+        "ECDSA-P384" => {
+            let point = p384::PublicKey::from_sec1_bytes(public_key)
+                .map_err(|e| SignatureError::Serialization(e.to_string()))?;
+            ([ID_EC_PUBLIC_KEY, SECP384R1].concat(), point.to_encoded_point(false).as_bytes().to_vec())
+        }
+        "Ed25519" => (ID_ED25519.to_vec(), public_key.to_vec()),
+        "Ed448" => (ID_ED448.to_vec(), public_key.to_vec()),
+        "ML-DSA-65" => (ID_ML_DSA_65.to_vec(), public_key.to_vec()),
+        "SLH-DSA-SHA2-128s" => (ID_SLH_DSA_SHA2_128S.to_vec(), public_key.to_vec()),
+        // RSA keys are already stored as SubjectPublicKeyInfo
+        "RSA-PSS-3072-SHA256" => return Ok(public_key.to_vec()),
+        other => {
+            return Err(SignatureError::Serialization(format!(
+                "{} public keys have no standard PEM encoding", other
+            )))
+        }
+    };
+
+    let bit_string = der(0x03, &[&[0x00], key.as_slice()].concat());
+    Ok(der(0x30, &[der(0x30, &algorithm), bit_string].concat()))
+}
+
+/// PEM "PUBLIC KEY" document for a public key as serialized by the scheme
+pub fn public_key_pem(scheme: &str, public_key: &[u8]) -> Result<String, SignatureError> {
+    let encoded = BASE64_STANDARD.encode(public_key_der(scheme, public_key)?);
+
+    let mut pem = String::from("-----BEGIN PUBLIC KEY-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        pem.push('\n');
+    }
+    pem.push_str("-----END PUBLIC KEY-----\n");
+    Ok(pem)
+}
+
+// A DER tag-length-value with a definite length
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        encoded.push(len as u8);
+    } else {
+        let len_bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|&b| b == 0).collect();
+        encoded.push(0x80 | len_bytes.len() as u8);
+        encoded.extend_from_slice(&len_bytes);
+    }
+    encoded.extend_from_slice(contents);
+    encoded
+}
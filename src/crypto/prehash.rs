@@ -0,0 +1,51 @@
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Keccak256, Sha3_256};
+
+/// Hash functions sign --prehash can apply to a message before signing its digest
+pub const PREHASH_ALGORITHMS: [&str; 5] = ["sha256", "sha512", "sha3-256", "keccak256", "blake3"];
+
+/// A message being hashed under one of PREHASH_ALGORITHMS, fed a chunk at a time so
+/// large files never have to be held in memory
+pub enum Prehasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Sha3_256(Sha3_256),
+    Keccak256(Keccak256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Prehasher {
+    pub fn new(algorithm: &str) -> Option<Self> {
+        Some(match algorithm {
+            "sha256" => Self::Sha256(Sha256::new()),
+            "sha512" => Self::Sha512(Sha512::new()),
+            "sha3-256" => Self::Sha3_256(Sha3_256::new()),
+            "keccak256" => Self::Keccak256(Keccak256::new()),
+            "blake3" => Self::Blake3(Box::new(blake3::Hasher::new())),
+            _ => return None,
+        })
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+            Self::Sha3_256(hasher) => hasher.update(data),
+            Self::Keccak256(hasher) => hasher.update(data),
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// The digest: 64 bytes for SHA-512, 32 for the others
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+            Self::Sha512(hasher) => hasher.finalize().to_vec(),
+            Self::Sha3_256(hasher) => hasher.finalize().to_vec(),
+            Self::Keccak256(hasher) => hasher.finalize().to_vec(),
+            Self::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::crypto::scheme::{os_rng, SignatureError, SignatureScheme};
+use rand_core::CryptoRngCore;
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rsa::pss::{BlindedSigningKey, Signature, VerifyingKey};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::Sha256;
+
+/// Modulus size used for newly generated keys
+pub const RSA_KEY_BITS: usize = 3072;
+
+#[derive(Debug)]
+pub struct RsaPss;
+
+impl RsaPss {
+    /// Sign with the PSS salt and blinding factor drawn from rng instead of the operating system
+    pub fn sign_with_rng(private_key: &RsaPrivateKey, message: &[u8], rng: &mut impl CryptoRngCore) -> Result<Signature, SignatureError> {
+        use rsa::signature::RandomizedSigner;
+
+        let signing_key = BlindedSigningKey::<Sha256>::new(private_key.clone());
+        signing_key
+            .try_sign_with_rng(rng, message)
+            .map_err(|e| SignatureError::Signing(e.to_string()))
+    }
+}
+
+impl SignatureScheme for RsaPss {
+    type PrivateKey = RsaPrivateKey;
+    type PublicKey = RsaPublicKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "RSA-PSS-3072-SHA256"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = RsaPrivateKey::new(rng, RSA_KEY_BITS)
+            .map_err(|e| SignatureError::KeyGeneration(e.to_string()))?;
+        let public_key = private_key.to_public_key();
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_rng(private_key, message, &mut os_rng()?)
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        use rsa::signature::Verifier;
+
+        let verifying_key = VerifyingKey::<Sha256>::new(public_key.clone());
+        match verifying_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Keys are stored as PKCS#8 / SubjectPublicKeyInfo DER
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        private_key
+            .to_pkcs8_der()
+            .map(|doc| doc.as_bytes().to_vec())
+            .map_err(|e| SignatureError::Serialization(e.to_string()))
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        public_key
+            .to_public_key_der()
+            .map(|doc| doc.as_bytes().to_vec())
+            .map_err(|e| SignatureError::Serialization(e.to_string()))
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        use rsa::signature::SignatureEncoding;
+
+        Ok(signature.to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        RsaPrivateKey::from_pkcs8_der(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        RsaPublicKey::from_public_key_der(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::try_from(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
@@ -1,4 +1,5 @@
 use thiserror::Error;
+use rand_core::CryptoRngCore;
 use std::fmt::Debug;
 
 #[derive(Debug,Error)]
@@ -11,7 +12,7 @@ pub enum SignatureError{
     Signing(String),
 
     #[error("Verification Error: {0}")]
-    Verififcation(String),
+    Verification(String),
 
     #[error("Serialization Error: {0}")]
     Serialization(String),
@@ -24,8 +25,48 @@ pub enum SignatureError{
     
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("This build has no system RNG (the os-rng feature is off); pass an RNG to the _with_rng functions")]
+    NoSystemRng,
+}
+
+// The operating system's RNG, for generate_keypair and the randomized schemes' sign
+#[cfg(feature = "os-rng")]
+pub(crate) fn os_rng() -> Result<rand::rngs::OsRng, SignatureError> {
+    Ok(rand::rngs::OsRng)
 }
 
+#[cfg(not(feature = "os-rng"))]
+pub(crate) fn os_rng() -> Result<NoSystemRng, SignatureError> {
+    Err(SignatureError::NoSystemRng)
+}
+
+// Stands in for OsRng in builds without it; having no values, it is never used
+#[cfg(not(feature = "os-rng"))]
+pub(crate) enum NoSystemRng {}
+
+#[cfg(not(feature = "os-rng"))]
+impl rand_core::RngCore for NoSystemRng {
+    fn next_u32(&mut self) -> u32 {
+        match *self {}
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match *self {}
+    }
+
+    fn fill_bytes(&mut self, _dest: &mut [u8]) {
+        match *self {}
+    }
+
+    fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        match *self {}
+    }
+}
+
+#[cfg(not(feature = "os-rng"))]
+impl rand_core::CryptoRng for NoSystemRng {}
+
 pub trait SignatureScheme : Send + Sync + Debug{
     
     type PrivateKey: Clone + Send + Sync;
@@ -34,7 +75,14 @@ pub trait SignatureScheme : Send + Sync + Debug{
 
     fn name() -> &'static str;
 
-    fn generate_keypair()->Result<(Self::PrivateKey,Self::PublicKey),SignatureError>;
+    // A new key pair from the operating system's RNG
+    fn generate_keypair()->Result<(Self::PrivateKey,Self::PublicKey),SignatureError>{
+        Self::generate_keypair_with_rng(&mut os_rng()?)
+    }
+
+    // A new key pair drawing all its randomness from rng, so the same seeded RNG
+    // always gives the same key pair
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore)->Result<(Self::PrivateKey,Self::PublicKey),SignatureError>;
 
     fn sign(private_key: &Self::PrivateKey,message: &[u8] )-> Result<Self::Signature,SignatureError>;
 
@@ -0,0 +1,153 @@
+use crate::crypto::scheme::{os_rng, SignatureScheme, SignatureError};
+use k256::elliptic_curve::PrimeField;
+use k256::schnorr::{Signature, SigningKey, VerifyingKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{FieldBytes, NonZeroScalar, ProjectivePoint, Scalar};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+/// Scheme recorded for keys produced by BIP-341 taproot tweaking
+pub const TWEAKED_SCHEME: &str = "Schnorr-secp256k1-BIP340-tweaked";
+
+#[derive(Debug)]
+pub struct Schnorr;
+
+// BIP-341 key tweaking and signing with a given RNG (not part of the trait)
+impl Schnorr {
+    /// Derive the taproot output key `Q = P + tG` with
+    /// `t = hash_TapTweak(P_x || merkle_root)`, returning the tweaked secret key.
+    /// Without a merkle root this is the key-path-only commitment.
+    pub fn taproot_tweak(private_key: &SigningKey, merkle_root: Option<&[u8; 32]>) -> Result<SigningKey, SignatureError> {
+        // k256 already negates the secret so that P has an even y coordinate
+        let tweak = taproot_tweak_scalar(private_key.verifying_key(), merkle_root)?;
+        let tweaked = Option::<NonZeroScalar>::from(NonZeroScalar::new(**private_key.as_nonzero_scalar() + tweak))
+            .ok_or_else(|| SignatureError::KeyGeneration("Tweaked secret key is zero".into()))?;
+
+        Ok(SigningKey::from(tweaked))
+    }
+
+    /// Public counterpart of `taproot_tweak`: the x-only output key `Q = P + tG`
+    pub fn taproot_output_key(internal_key: &VerifyingKey, merkle_root: Option<&[u8; 32]>) -> Result<VerifyingKey, SignatureError> {
+        let tweak = taproot_tweak_scalar(internal_key, merkle_root)?;
+        let output_point = (ProjectivePoint::from(*internal_key.as_affine()) + ProjectivePoint::GENERATOR * tweak)
+            .to_affine()
+            .to_encoded_point(false);
+
+        let output_x = output_point
+            .x()
+            .ok_or_else(|| SignatureError::KeyGeneration("Taproot output key is the point at infinity".into()))?;
+        VerifyingKey::from_bytes(output_x)
+            .map_err(|e| SignatureError::KeyGeneration(e.to_string()))
+    }
+
+    /// Sign with the auxiliary randomness drawn from rng instead of the operating system
+    pub fn sign_with_rng(private_key: &SigningKey, message: &[u8], rng: &mut impl CryptoRngCore) -> Result<Signature, SignatureError> {
+        use k256::schnorr::signature::RandomizedSigner;
+
+        private_key
+            .try_sign_with_rng(rng, message)
+            .map_err(|e| SignatureError::Signing(e.to_string()))
+    }
+}
+
+// BIP-341 tweak t = hash_TapTweak(P_x || merkle_root) as a scalar
+fn taproot_tweak_scalar(internal_key: &VerifyingKey, merkle_root: Option<&[u8; 32]>) -> Result<Scalar, SignatureError> {
+    let mut tweak_input = internal_key.to_bytes().to_vec();
+    if let Some(root) = merkle_root {
+        tweak_input.extend_from_slice(root);
+    }
+    let tweak_hash = tagged_hash(b"TapTweak", &tweak_input);
+
+    Option::<Scalar>::from(Scalar::from_repr(FieldBytes::from(tweak_hash)))
+        .ok_or_else(|| SignatureError::KeyGeneration("Taproot tweak exceeds the curve order".into()))
+}
+
+// BIP-340 tagged hash: SHA256(SHA256(tag) || SHA256(tag) || msg)
+pub(crate) fn tagged_hash(tag: &[u8], msg: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update(msg)
+        .finalize()
+        .into()
+}
+
+impl SignatureScheme for Schnorr {
+    type PrivateKey = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "Schnorr-secp256k1-BIP340"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::random(rng);
+        let public_key = *private_key.verifying_key();
+
+        Ok((private_key, public_key))
+    }
+
+    // The message is hashed with SHA-256 and the digest is used as the
+    // 32-byte BIP-340 message, with fresh auxiliary randomness per signature.
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_rng(private_key, message, &mut os_rng()?)
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        use k256::schnorr::signature::Verifier;
+
+        match public_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods: 32-byte scalar, 32-byte x-only key, 64-byte signature
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        if bytes.len() != 32 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid private key length: expected 32 bytes, got {}", bytes.len())
+            ));
+        }
+
+        SigningKey::from_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        if bytes.len() != 32 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid x-only public key length: expected 32 bytes, got {}", bytes.len())
+            ));
+        }
+
+        VerifyingKey::from_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        // k256 panics on fewer than 32 bytes rather than returning its error
+        if bytes.len() != 64 {
+            return Err(SignatureError::Deserialization(
+                format!("Invalid Schnorr signature length: expected 64 bytes, got {}", bytes.len())
+            ));
+        }
+
+        Signature::try_from(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
@@ -0,0 +1,83 @@
+use crate::crypto::scheme::{os_rng, SignatureScheme, SignatureError};
+use rand_core::CryptoRngCore;
+use slh_dsa::{Sha2_128s, Signature, SigningKey, VerifyingKey};
+
+#[derive(Debug)]
+pub struct SlhDsa;
+
+impl SlhDsa {
+    /// Sign with the per-signature randomizer drawn from rng instead of the operating system
+    pub fn sign_with_rng(private_key: &SigningKey<Sha2_128s>, message: &[u8], rng: &mut impl CryptoRngCore) -> Result<Signature<Sha2_128s>, SignatureError> {
+        use slh_dsa::signature::RandomizedSigner;
+
+        private_key
+            .try_sign_with_rng(rng, message)
+            .map_err(|e| SignatureError::Signing(e.to_string()))
+    }
+}
+
+impl SignatureScheme for SlhDsa {
+    type PrivateKey = SigningKey<Sha2_128s>;
+    type PublicKey = VerifyingKey<Sha2_128s>;
+    type Signature = Signature<Sha2_128s>;
+
+    // The parameter set is part of the name so keys and signatures from
+    // different SLH-DSA parameter sets are never mixed up
+    fn name() -> &'static str {
+        "SLH-DSA-SHA2-128s"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = SigningKey::<Sha2_128s>::new(rng);
+        let public_key = private_key.as_ref().clone();
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_rng(private_key, message, &mut os_rng()?)
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        use slh_dsa::signature::Verifier;
+
+        match public_key.verify(message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods: 64-byte secret key, 32-byte public key, 7856-byte signature
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        SigningKey::try_from(bytes)
+            .map_err(|_| SignatureError::Deserialization(format!(
+                "Invalid SLH-DSA-SHA2-128s private key ({} bytes)", bytes.len()
+            )))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        VerifyingKey::try_from(bytes)
+            .map_err(|_| SignatureError::Deserialization(format!(
+                "Invalid SLH-DSA-SHA2-128s public key ({} bytes)", bytes.len()
+            )))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::try_from(bytes)
+            .map_err(|_| SignatureError::Deserialization(format!(
+                "Invalid SLH-DSA-SHA2-128s signature ({} bytes)", bytes.len()
+            )))
+    }
+}
@@ -0,0 +1,78 @@
+use crate::crypto::scheme::{os_rng, SignatureScheme, SignatureError};
+use rand_core::CryptoRngCore;
+use schnorrkel::context::{attach_rng, signing_context};
+use schnorrkel::{ExpansionMode, MiniSecretKey, PublicKey, Signature};
+
+/// Signing context used by Substrate for sr25519 signatures
+const SIGNING_CONTEXT: &[u8] = b"substrate";
+
+#[derive(Debug)]
+pub struct Sr25519;
+
+impl Sr25519 {
+    /// Sign with the witness randomness drawn from rng instead of the operating system
+    pub fn sign_with_rng(private_key: &MiniSecretKey, message: &[u8], rng: &mut impl CryptoRngCore) -> Result<Signature, SignatureError> {
+        // Substrate expands the mini secret key in Ed25519 mode, so we do the same
+        // to stay compatible with keys and signatures produced by `subkey`
+        let keypair = private_key.expand_to_keypair(ExpansionMode::Ed25519);
+        let transcript = signing_context(SIGNING_CONTEXT).bytes(message);
+
+        Ok(keypair.sign(attach_rng(transcript, rng)))
+    }
+}
+
+impl SignatureScheme for Sr25519 {
+    type PrivateKey = MiniSecretKey;
+    type PublicKey = PublicKey;
+    type Signature = Signature;
+
+    fn name() -> &'static str {
+        "Sr25519"
+    }
+
+    fn generate_keypair_with_rng(rng: &mut impl CryptoRngCore) -> Result<(Self::PrivateKey, Self::PublicKey), SignatureError> {
+        let private_key = MiniSecretKey::generate_with(rng);
+        let public_key = private_key.expand_to_public(ExpansionMode::Ed25519);
+
+        Ok((private_key, public_key))
+    }
+
+    fn sign(private_key: &Self::PrivateKey, message: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Self::sign_with_rng(private_key, message, &mut os_rng()?)
+    }
+
+    fn verify(public_key: &Self::PublicKey, message: &[u8], signature: &Self::Signature) -> Result<bool, SignatureError> {
+        match public_key.verify_simple(SIGNING_CONTEXT, message, signature) {
+            Ok(()) => Ok(true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    // Serialization methods: 32-byte mini secret key, 32-byte public key, 64-byte signature
+    fn serialize_private_key(private_key: &Self::PrivateKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(private_key.to_bytes().to_vec())
+    }
+
+    fn serialize_public_key(public_key: &Self::PublicKey) -> Result<Vec<u8>, SignatureError> {
+        Ok(public_key.to_bytes().to_vec())
+    }
+
+    fn serialize_signature(signature: &Self::Signature) -> Result<Vec<u8>, SignatureError> {
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    fn deserialize_private_key(bytes: &[u8]) -> Result<Self::PrivateKey, SignatureError> {
+        MiniSecretKey::from_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_public_key(bytes: &[u8]) -> Result<Self::PublicKey, SignatureError> {
+        PublicKey::from_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+
+    fn deserialize_signature(bytes: &[u8]) -> Result<Self::Signature, SignatureError> {
+        Signature::from_bytes(bytes)
+            .map_err(|e| SignatureError::Deserialization(e.to_string()))
+    }
+}
@@ -0,0 +1,156 @@
+// Display, FromStr and, with the serde feature, Serialize and Deserialize for the public key
+// and signature wrapper types, through their scheme's serialize_* and deserialize_*
+// functions so every encoding of a value has the same bytes. The text form is lowercase
+// hex; serde uses it for human-readable formats and the raw bytes for binary ones. Private
+// keys are left out so that neither formatting nor serializing one can leak it.
+
+use crate::crypto::scheme::SignatureError;
+
+pub(crate) fn decode_hex(value: &str, what: &str) -> Result<Vec<u8>, SignatureError> {
+    hex::decode(value).map_err(|_| SignatureError::Deserialization(format!("{} is not hex", what)))
+}
+
+// Implements the traits for $wrapper as $scheme's $serialize and $deserialize see it;
+// $what names it in parse errors
+macro_rules! impl_wrapper_encoding {
+    ($wrapper:ty, $scheme:ty, $serialize:ident, $deserialize:ident, $what:literal) => {
+        impl std::fmt::Display for $wrapper {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let bytes = <$scheme as $crate::crypto::SignatureScheme>::$serialize(self).map_err(|_| std::fmt::Error)?;
+                f.write_str(&hex::encode(bytes))
+            }
+        }
+
+        impl std::str::FromStr for $wrapper {
+            type Err = $crate::crypto::SignatureError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <$scheme as $crate::crypto::SignatureScheme>::$deserialize(&$crate::crypto::wrapper::decode_hex(s, $what)?)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $wrapper {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let bytes = <$scheme as $crate::crypto::SignatureScheme>::$serialize(self).map_err(serde::ser::Error::custom)?;
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&hex::encode(bytes))
+                } else {
+                    serializer.serialize_bytes(&bytes)
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $wrapper {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let bytes = if deserializer.is_human_readable() {
+                    $crate::crypto::wrapper::decode_hex(&String::deserialize(deserializer)?, $what)
+                        .map_err(serde::de::Error::custom)?
+                } else {
+                    deserializer.deserialize_bytes($crate::crypto::wrapper::BytesVisitor)?
+                };
+                <$scheme as $crate::crypto::SignatureScheme>::$deserialize(&bytes).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_wrapper_encoding;
+
+// Takes bytes however a binary format hands them over: borrowed, owned or as a sequence
+#[cfg(feature = "serde")]
+pub(crate) struct BytesVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::crypto::SignatureScheme;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    // Wrappers have no PartialEq, so values are compared by their text form
+    fn round_trip<T>(value: &T)
+    where
+        T: Serialize + DeserializeOwned + Display + FromStr,
+        T::Err: std::fmt::Debug,
+    {
+        let text = value.to_string();
+        assert_eq!(T::from_str(&text).unwrap().to_string(), text);
+
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(json, format!("\"{}\"", text));
+        assert_eq!(serde_json::from_str::<T>(&json).unwrap().to_string(), text);
+
+        // bincode writes raw bytes behind a u64 length, not the hex
+        let binary = bincode::serialize(value).unwrap();
+        assert_eq!(binary[8..], hex::decode(&text).unwrap()[..]);
+        assert_eq!(bincode::deserialize::<T>(&binary).unwrap().to_string(), text);
+    }
+
+    #[cfg(feature = "bls")]
+    #[test]
+    fn bls_wrappers_round_trip() {
+        use crate::crypto::bls::BLS;
+        use crate::crypto::bls_min_sig::BLSMinSig;
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::SeedableRng;
+
+        let (private_key, public_key) = BLS::generate_keypair_with_rng(&mut ChaCha20Rng::seed_from_u64(1)).unwrap();
+        round_trip(&public_key);
+        round_trip(&BLS::sign(&private_key, b"message").unwrap());
+
+        let (private_key, public_key) = BLSMinSig::generate_keypair_with_rng(&mut ChaCha20Rng::seed_from_u64(2)).unwrap();
+        round_trip(&public_key);
+        round_trip(&BLSMinSig::sign(&private_key, b"message").unwrap());
+    }
+
+    #[cfg(feature = "ml-dsa")]
+    #[test]
+    fn ml_dsa_wrappers_round_trip() {
+        use crate::crypto::hybrid::HybridEd25519MlDsa;
+        use crate::crypto::mldsa::MlDsa65;
+
+        let (private_key, public_key) = MlDsa65::generate_keypair().unwrap();
+        round_trip(&public_key);
+        round_trip(&MlDsa65::sign(&private_key, b"message").unwrap());
+
+        let (private_key, public_key) = HybridEd25519MlDsa::generate_keypair().unwrap();
+        round_trip(&public_key);
+        round_trip(&HybridEd25519MlDsa::sign(&private_key, b"message").unwrap());
+    }
+
+    #[test]
+    fn rejects_what_is_not_hex() {
+        #[cfg(feature = "bls")]
+        assert!(serde_json::from_str::<crate::crypto::bls::BLSPublicKey>("\"not hex\"").is_err());
+        #[cfg(feature = "ml-dsa")]
+        assert!(serde_json::from_str::<crate::crypto::mldsa::MlDsaSignature>("\"not hex\"").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::crypto::registry::{self, DynScheme};
+use crate::crypto::SignatureError;
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+// C bindings for signing and verifying without the binary; include/sig_tool.h is generated
+// from this file with cbindgen. Schemes are named as key entries and signature files name
+// them, keys and signatures are in the encodings sig-tool writes, and every function returns
+// one of the codes below. Nothing is allocated on the caller's behalf: a signature is
+// written into a buffer the caller owns, after a call that asks for its length.
+
+/// Success; for sigtool_verify, the signature is valid
+pub const SIGTOOL_OK: i32 = 0;
+/// sigtool_verify only: the signature is well-formed but does not verify
+pub const SIGTOOL_INVALID_SIGNATURE: i32 = 1;
+/// A required pointer is NULL; pointers may only be NULL when their length is 0
+pub const SIGTOOL_ERR_NULL_POINTER: i32 = -1;
+/// The scheme name is not valid UTF-8 or names no scheme this library was built with
+pub const SIGTOOL_ERR_UNSUPPORTED_SCHEME: i32 = -2;
+/// The public key does not parse for the scheme
+pub const SIGTOOL_ERR_PUBLIC_KEY: i32 = -3;
+/// The private key does not parse for the scheme
+pub const SIGTOOL_ERR_PRIVATE_KEY: i32 = -4;
+/// The signature does not parse for the scheme
+pub const SIGTOOL_ERR_SIGNATURE_ENCODING: i32 = -5;
+/// Signing failed
+pub const SIGTOOL_ERR_SIGNING: i32 = -6;
+/// The output buffer is smaller than the signature; *sig_len holds the length needed
+pub const SIGTOOL_ERR_BUFFER_TOO_SMALL: i32 = -7;
+/// Verification failed for a reason other than a bad signature
+pub const SIGTOOL_ERR_VERIFICATION: i32 = -8;
+/// The library panicked; this is a bug
+pub const SIGTOOL_ERR_INTERNAL: i32 = -9;
+
+/// Verify a signature over a message. Returns SIGTOOL_OK when it is valid,
+/// SIGTOOL_INVALID_SIGNATURE when it is not, and a negative SIGTOOL_ERR_* code when the
+/// scheme, key or signature cannot be used.
+///
+/// # Safety
+///
+/// `scheme` must be a NUL-terminated string, and `pubkey`, `msg` and `sig` must each point
+/// to at least the given number of readable bytes (or be NULL with a length of 0).
+#[no_mangle]
+pub unsafe extern "C" fn sigtool_verify(
+    scheme: *const c_char,
+    pubkey: *const u8,
+    pubkey_len: usize,
+    msg: *const u8,
+    msg_len: usize,
+    sig: *const u8,
+    sig_len: usize,
+) -> i32 {
+    guard(|| {
+        let registered = lookup(scheme)?;
+        let (public_key, message, signature) = match (bytes(pubkey, pubkey_len), bytes(msg, msg_len), bytes(sig, sig_len)) {
+            (Some(public_key), Some(message), Some(signature)) => (public_key, message, signature),
+            _ => return Err(SIGTOOL_ERR_NULL_POINTER),
+        };
+        // Checked one at a time so the code says which of them is malformed
+        registered.check_public_key(public_key).map_err(|_| SIGTOOL_ERR_PUBLIC_KEY)?;
+        registered.check_signature(signature).map_err(|_| SIGTOOL_ERR_SIGNATURE_ENCODING)?;
+        match registered.verify_bytes(public_key, message, signature) {
+            Ok(true) => Ok(SIGTOOL_OK),
+            Ok(false) => Ok(SIGTOOL_INVALID_SIGNATURE),
+            Err(_) => Err(SIGTOOL_ERR_VERIFICATION),
+        }
+    })
+}
+
+/// Sign a message with a private key. On entry `*sig_len` is the size of the `sig_out`
+/// buffer; on return it is the signature's length. With a NULL `sig_out` nothing is written
+/// but the length, and the call returns SIGTOOL_OK, so callers ask for the length, allocate,
+/// and call again. A signature with a given key and message has the same length from call
+/// to call, even for the schemes that sign with fresh randomness.
+///
+/// # Safety
+///
+/// `scheme` must be a NUL-terminated string, `privkey` and `msg` must each point to at least
+/// the given number of readable bytes (or be NULL with a length of 0), `sig_len` must point
+/// to a writable size_t, and a non-NULL `sig_out` must point to `*sig_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sigtool_sign(
+    scheme: *const c_char,
+    privkey: *const u8,
+    privkey_len: usize,
+    msg: *const u8,
+    msg_len: usize,
+    sig_out: *mut u8,
+    sig_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        let registered = lookup(scheme)?;
+        let (private_key, message) = match (bytes(privkey, privkey_len), bytes(msg, msg_len)) {
+            (Some(private_key), Some(message)) if !sig_len.is_null() => (private_key, message),
+            _ => return Err(SIGTOOL_ERR_NULL_POINTER),
+        };
+        let signature = registered.sign_bytes(private_key, message).map_err(|e| match e {
+            SignatureError::Deserialization(_) => SIGTOOL_ERR_PRIVATE_KEY,
+            _ => SIGTOOL_ERR_SIGNING,
+        })?;
+
+        let capacity = *sig_len;
+        *sig_len = signature.len();
+        if sig_out.is_null() {
+            return Ok(SIGTOOL_OK);
+        }
+        if capacity < signature.len() {
+            return Err(SIGTOOL_ERR_BUFFER_TOO_SMALL);
+        }
+        slice::from_raw_parts_mut(sig_out, signature.len()).copy_from_slice(&signature);
+        Ok(SIGTOOL_OK)
+    })
+}
+
+/// A static, NUL-terminated description of a SIGTOOL_* code, which the caller must not free
+#[no_mangle]
+pub extern "C" fn sigtool_strerror(code: i32) -> *const c_char {
+    let message: &'static CStr = match code {
+        SIGTOOL_OK => c"success",
+        SIGTOOL_INVALID_SIGNATURE => c"signature does not verify",
+        SIGTOOL_ERR_NULL_POINTER => c"a required pointer is NULL",
+        SIGTOOL_ERR_UNSUPPORTED_SCHEME => c"unsupported signature scheme",
+        SIGTOOL_ERR_PUBLIC_KEY => c"malformed public key",
+        SIGTOOL_ERR_PRIVATE_KEY => c"malformed private key",
+        SIGTOOL_ERR_SIGNATURE_ENCODING => c"malformed signature",
+        SIGTOOL_ERR_SIGNING => c"signing failed",
+        SIGTOOL_ERR_BUFFER_TOO_SMALL => c"signature buffer too small",
+        SIGTOOL_ERR_VERIFICATION => c"verification failed",
+        SIGTOOL_ERR_INTERNAL => c"internal error",
+        _ => c"unknown error code",
+    };
+    message.as_ptr()
+}
+
+// Runs a binding's body, turning its error code, or a panic, which must not unwind into C,
+// into the return value
+fn guard(body: impl FnOnce() -> Result<i32, i32>) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(code)) | Ok(Err(code)) => code,
+        Err(_) => SIGTOOL_ERR_INTERNAL,
+    }
+}
+
+unsafe fn lookup(scheme: *const c_char) -> Result<&'static dyn DynScheme, i32> {
+    if scheme.is_null() {
+        return Err(SIGTOOL_ERR_NULL_POINTER);
+    }
+    CStr::from_ptr(scheme)
+        .to_str()
+        .ok()
+        .and_then(registry::lookup)
+        .ok_or(SIGTOOL_ERR_UNSUPPORTED_SCHEME)
+}
+
+// The bytes a pointer and length describe; NULL is only allowed for an empty slice
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, len) => Some(slice::from_raw_parts(ptr, len)),
+    }
+}
@@ -0,0 +1,61 @@
+use crate::storage::{KeyStorageBackend, StorageError};
+use keyring::Entry;
+use std::path::Path;
+
+// Private keys kept in the OS keychain through the keyring crate: the login keychain on
+// macOS, the Credential Manager on Windows and the Secret Service (GNOME Keyring, KWallet)
+// elsewhere, spoken to over D-Bus in-process. Each key is a generic password under the
+// service "sig-tool" whose account is the keystore directory and key name, so several
+// keystores can share one keychain. Secrets go straight to the platform API and are never
+// handed to another program.
+
+pub const KEYCHAIN_BACKEND: &str = "keychain";
+const SERVICE: &str = "sig-tool";
+
+pub struct KeychainBackend {
+    keystore: String,
+}
+
+impl KeychainBackend {
+    pub fn new(keystore_dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let keystore = std::fs::canonicalize(keystore_dir)?.display().to_string();
+        Ok(Self { keystore })
+    }
+
+    fn entry(&self, name: &str) -> Result<Entry, StorageError> {
+        Entry::new(SERVICE, &format!("{}/{}", self.keystore, name)).map_err(keychain_error)
+    }
+}
+
+impl KeyStorageBackend for KeychainBackend {
+    fn name(&self) -> &'static str {
+        KEYCHAIN_BACKEND
+    }
+
+    fn store_private_key(&self, name: &str, private_key: &str) -> Result<String, StorageError> {
+        self.entry(name)?.set_password(private_key).map_err(keychain_error)?;
+        Ok(String::new())
+    }
+
+    fn load_private_key(&self, name: &str, _stored: &str) -> Result<String, StorageError> {
+        match self.entry(name)?.get_password() {
+            Ok(private_key) => Ok(private_key),
+            Err(keyring::Error::NoEntry) => {
+                Err(StorageError::Keychain(format!("the keychain has no private key for {}", name)))
+            }
+            Err(e) => Err(keychain_error(e)),
+        }
+    }
+
+    // A key whose private key is already gone is deleted all the same
+    fn delete_private_key(&self, name: &str) -> Result<(), StorageError> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keychain_error(e)),
+        }
+    }
+}
+
+fn keychain_error(e: keyring::Error) -> StorageError {
+    StorageError::Keychain(e.to_string())
+}
@@ -0,0 +1,60 @@
+//! Key management and signing across ECDSA, BLS, Ed25519 and the other schemes sig-tool
+//! supports, for use from other programs as well as from the sig-tool command line.
+//!
+//! Keys live in a [`storage::KeyStore`] directory, the one the command line uses, or in a
+//! [`backend::MemoryKeyStore`]; both are [`backend::SigningKeyStore`]s.
+//!
+//! ```no_run
+//! use sig_tool::backend::sign_with_key;
+//! use sig_tool::storage::KeyStore;
+//!
+//! let keystore = KeyStore::new_read_only("/srv/sig-tool/prod")?;
+//! let bundle = sign_with_key(&keystore, "deploy", b"release 1.2.0")?;
+//! bundle.save("release-1.2.0.sig")?;
+//! println!("Signed with {} key {}", bundle.scheme, bundle.fingerprint);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The `keystore` feature (on by default, with the command line's `cli`) brings in
+//! everything that touches files or the operating system's RNG. Without it, as built for
+//! wasm32 with the `wasm` feature, the library verifies signatures and parses signature
+//! files; the `bls` and `ml-dsa` features add the schemes whose C code does not build there.
+//! The `capi` feature exports signing and verification to C, as declared in
+//! `include/sig_tool.h`.
+
+pub mod crypto;
+pub mod signature_file;
+
+#[cfg(feature = "keystore")]
+pub mod backend;
+#[cfg(feature = "keystore")]
+pub mod signer;
+#[cfg(feature = "keystore")]
+pub mod storage;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "capi")]
+pub mod ffi;
+
+// The command line, for the sig-tool binary
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod cli;
+
+#[cfg(feature = "cli")]
+mod audit;
+#[cfg(feature = "cli")]
+mod completions;
+#[cfg(feature = "cli")]
+mod keychain;
+#[cfg(feature = "cli")]
+mod paths;
+#[cfg(feature = "cli")]
+mod profiles;
+#[cfg(feature = "cli")]
+mod ssh;
+#[cfg(feature = "cli")]
+mod yubikey;
+
+#[cfg(feature = "keystore")]
+pub use backend::{sign_with_key, verify_bundle, SignatureBundle};
@@ -1,14 +1,22 @@
-mod cli;
-mod crypto;
-mod storage;
-
 use clap::Parser;
+use sig_tool::cli;
 
 fn main() {
     let cli = cli::Cli::parse();
+    let json = cli.json;
     
-    if let Err(err) = cli::run_cli(cli) {
-        eprintln!("Error: {}", err);
-        std::process::exit(1);
+    // 0: success, 1: a signature failed verification; failures to run exit with the code
+    // of their kind, listed at CliError::exit_code
+    match cli::run_cli(cli) {
+        Ok(cli::Outcome::Success) => {}
+        Ok(cli::Outcome::Invalid) => std::process::exit(1),
+        Err(err) => {
+            if json {
+                eprintln!("{}", cli::error_json(&err));
+            } else {
+                eprintln!("Error: {}", err);
+            }
+            std::process::exit(err.exit_code());
+        }
     }
-}
\ No newline at end of file
+}
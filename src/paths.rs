@@ -0,0 +1,127 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use thiserror::Error;
+
+// Expansion of paths that no shell has expanded: quoted arguments, the environment and
+// profiles.toml. A leading ~ or ~/ (also ~\ on Windows) becomes the home directory, and
+// $VAR and ${VAR} (also %VAR% on Windows) anywhere become the variable's value. ~user,
+// and a $ or % not followed by a variable name, are left as they are.
+
+#[derive(Error, Debug)]
+pub enum PathError {
+    #[error("Could not determine the home directory to expand {0}")]
+    NoHomeDirectory(String),
+
+    #[error("Environment variable {0} in {1} is not set")]
+    UnsetVariable(String, String),
+}
+
+pub fn expand(path: &str) -> Result<PathBuf, PathError> {
+    let (home, rest) = match strip_tilde(path) {
+        Some(rest) => (Some(dirs::home_dir().ok_or_else(|| PathError::NoHomeDirectory(path.to_string()))?), rest),
+        None => (None, path),
+    };
+    let rest = expand_variables(rest, path)?;
+    Ok(match home {
+        // Joining nothing would leave a trailing separator
+        Some(home) if rest.is_empty() => home,
+        Some(home) => home.join(rest),
+        None => PathBuf::from(rest),
+    })
+}
+
+// The rest of a path that starts with the home directory
+fn strip_tilde(path: &str) -> Option<&str> {
+    if path == "~" {
+        return Some("");
+    }
+    path.strip_prefix("~/").or_else(|| if cfg!(windows) { path.strip_prefix("~\\") } else { None })
+}
+
+fn expand_variables(path: &str, original: &str) -> Result<OsString, PathError> {
+    let mut expanded = OsString::new();
+    let mut rest = path;
+    while let Some(i) = rest.find(|c| c == '$' || (cfg!(windows) && c == '%')) {
+        expanded.push(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (name, remainder) = if rest[i..].starts_with('%') {
+            match after.find('%') {
+                Some(end) if end > 0 => (&after[..end], &after[end + 1..]),
+                _ => ("", after),
+            }
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) if is_variable_name(&braced[..end]) => (&braced[..end], &braced[end + 1..]),
+                _ => ("", after),
+            }
+        } else {
+            let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+            if is_variable_name(&after[..end]) { (&after[..end], &after[end..]) } else { ("", after) }
+        };
+
+        if name.is_empty() {
+            expanded.push(&rest[i..i + 1]);
+        } else {
+            expanded.push(variable(name, original)?);
+        }
+        rest = remainder;
+    }
+    expanded.push(rest);
+    Ok(expanded)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// A variable's value; the home directory variables fall back to the home directory, which
+// dirs finds without them
+fn variable(name: &str, original: &str) -> Result<OsString, PathError> {
+    if let Some(value) = std::env::var_os(name) {
+        return Ok(value);
+    }
+    match name {
+        "HOME" | "USERPROFILE" => dirs::home_dir()
+            .map(PathBuf::into_os_string)
+            .ok_or_else(|| PathError::NoHomeDirectory(original.to_string())),
+        _ => Err(PathError::UnsetVariable(name.to_string(), original.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Variables only these tests use, as the environment is shared by every test thread
+    const SET: &str = "SIG_TOOL_PATHS_TEST_DIR";
+    const UNSET: &str = "SIG_TOOL_PATHS_TEST_UNSET";
+
+    #[test]
+    fn expands_the_home_directory() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand("~").unwrap(), home);
+        assert_eq!(expand("~/keys").unwrap(), home.join("keys"));
+        assert_eq!(expand("~user/keys").unwrap(), PathBuf::from("~user/keys"));
+        assert_eq!(expand("keys/~").unwrap(), PathBuf::from("keys/~"));
+    }
+
+    #[test]
+    fn expands_variables() {
+        std::env::set_var(SET, "/srv/sig-tool");
+        std::env::remove_var(UNSET);
+
+        assert_eq!(expand(&format!("${}/keys", SET)).unwrap(), PathBuf::from("/srv/sig-tool/keys"));
+        assert_eq!(expand(&format!("${{{}}}-backup", SET)).unwrap(), PathBuf::from("/srv/sig-tool-backup"));
+        assert!(matches!(
+            expand(&format!("${}/keys", UNSET)),
+            Err(PathError::UnsetVariable(name, _)) if name == UNSET
+        ));
+        assert!(matches!(expand(&format!("${{{}}}", UNSET)), Err(PathError::UnsetVariable(..))));
+
+        // A $ that does not start a variable name is kept
+        for literal in ["price$", "a$/b", "$1", "${}", "${not closed", "$-x", "cost $ 5"] {
+            assert_eq!(expand(literal).unwrap(), PathBuf::from(literal), "{}", literal);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::storage::write_atomic;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+// Named keystores, kept in a small TOML file with one table per profile, after the
+// keystore and keygen scheme to use when no profile is chosen:
+//
+//   keystore = "~/work/keys"
+//   scheme = "ed25519"
+//
+//   [prod]
+//   path = "/srv/sig-tool/prod"
+//   scheme = "bls"
+//
+// Only this shape is read and written: bare table names holding basic string values.
+
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    #[error("I/O error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Invalid profiles file, line {0}: {1}")]
+    Parse(usize, String),
+
+    #[error("Invalid profile name {0:?}: use letters, digits, '-' and '_'")]
+    InvalidName(String),
+
+    #[error("Unknown profile {0}; {1}")]
+    Unknown(String, String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub path: String,
+    // Scheme keygen uses when --scheme is not given
+    pub scheme: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Profiles {
+    pub profiles: BTreeMap<String, Profile>,
+    // Keystore and keygen scheme for when neither the command line nor the environment sets them
+    pub keystore: Option<String>,
+    pub scheme: Option<String>,
+}
+
+impl Profiles {
+    // Read the profiles file; a missing file holds no profiles
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProfileError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(path, self.to_toml().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Profile, ProfileError> {
+        self.profiles.get(name).ok_or_else(|| {
+            let known = if self.profiles.is_empty() {
+                "no profiles are configured; add one with `sig-tool profile add`".to_string()
+            } else {
+                let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                format!("known profiles: {}", names.join(", "))
+            };
+            ProfileError::Unknown(name.to_string(), known)
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, ProfileError> {
+        let mut profiles = BTreeMap::new();
+        let mut defaults = Profile::default();
+        let mut current: Option<String> = None;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line_no = index + 1;
+            let error = |message: String| ProfileError::Parse(line_no, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let name = strip_comment(name)
+                    .strip_suffix(']')
+                    .ok_or_else(|| error("unterminated table header".into()))?
+                    .trim();
+                validate_profile_name(name).map_err(|e| error(e.to_string()))?;
+                if profiles.insert(name.to_string(), Profile::default()).is_some() {
+                    return Err(error(format!("profile {} is defined twice", name)));
+                }
+                current = Some(name.to_string());
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = \"value\"` or `[profile]`".into()))?;
+            let value = parse_string(value.trim()).map_err(error)?;
+            // Values before the first table are the defaults
+            let Some(name) = &current else {
+                match key.trim() {
+                    "keystore" => defaults.path = value,
+                    "scheme" => defaults.scheme = Some(value),
+                    other => return Err(error(format!("unknown key {} outside of a [profile] table", other))),
+                }
+                continue;
+            };
+            let profile = profiles.get_mut(name).expect("current profile was inserted");
+            match key.trim() {
+                "path" => profile.path = value,
+                "scheme" => profile.scheme = Some(value),
+                other => return Err(error(format!("unknown key {}", other))),
+            }
+        }
+
+        if let Some((name, _)) = profiles.iter().find(|(_, profile)| profile.path.is_empty()) {
+            return Err(ProfileError::Parse(0, format!("profile {} has no path", name)));
+        }
+        Ok(Self {
+            profiles,
+            keystore: Some(defaults.path).filter(|path| !path.is_empty()),
+            scheme: defaults.scheme,
+        })
+    }
+
+    fn to_toml(&self) -> String {
+        let mut toml = String::from("# sig-tool keystore profiles\n");
+        if let Some(keystore) = &self.keystore {
+            toml.push_str(&format!("keystore = {}\n", quote(keystore)));
+        }
+        if let Some(scheme) = &self.scheme {
+            toml.push_str(&format!("scheme = {}\n", quote(scheme)));
+        }
+        for (name, profile) in &self.profiles {
+            toml.push_str(&format!("\n[{}]\npath = {}\n", name, quote(&profile.path)));
+            if let Some(scheme) = &profile.scheme {
+                toml.push_str(&format!("scheme = {}\n", quote(scheme)));
+            }
+        }
+        toml
+    }
+}
+
+// Profile names are TOML bare keys
+pub fn validate_profile_name(name: &str) -> Result<(), ProfileError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(ProfileError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn strip_comment(text: &str) -> &str {
+    text.split('#').next().unwrap_or_default().trim()
+}
+
+// A TOML basic string, optionally followed by a comment
+fn parse_string(text: &str) -> Result<String, String> {
+    let mut chars = text.strip_prefix('"').ok_or("expected a double-quoted string")?.chars();
+    let mut value = String::new();
+    loop {
+        match chars.next().ok_or("unterminated string")? {
+            '"' => break,
+            '\\' => value.push(match chars.next().ok_or("unterminated string")? {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                't' => '\t',
+                other => return Err(format!("unsupported escape \\{}", other)),
+            }),
+            c => value.push(c),
+        }
+    }
+
+    let rest = chars.as_str().trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected {:?} after string", rest));
+    }
+    Ok(value)
+}
+
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+keystore = "~/work/keys"  # before any table
+scheme = "ed25519"
+
+[prod]
+path = "/srv/sig-tool/prod"
+scheme = "bls"
+"#;
+
+    #[test]
+    fn defaults_come_before_the_first_table() {
+        let profiles = Profiles::parse(FILE).unwrap();
+        assert_eq!(profiles.keystore.as_deref(), Some("~/work/keys"));
+        assert_eq!(profiles.scheme.as_deref(), Some("ed25519"));
+        assert_eq!(profiles.profiles["prod"].path, "/srv/sig-tool/prod");
+
+        let written = Profiles::parse(&profiles.to_toml()).unwrap();
+        assert_eq!(written.keystore, profiles.keystore);
+        assert_eq!(written.scheme, profiles.scheme);
+        assert_eq!(written.profiles["prod"].scheme.as_deref(), Some("bls"));
+
+        let without_defaults = Profiles::parse("[prod]\npath = \"/srv\"\n").unwrap();
+        assert_eq!((without_defaults.keystore, without_defaults.scheme), (None, None));
+        assert!(matches!(Profiles::parse("path = \"/srv\"\n"), Err(ProfileError::Parse(1, _))));
+    }
+}
@@ -0,0 +1,215 @@
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+// The signature file format: the scheme, the signature and optional details as JSON. Only
+// parsing lives here, with no file access, so it builds for wasm32; storage reads and
+// writes the files.
+
+#[derive(Error, Debug)]
+pub enum SignatureFileError {
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unsupported format version {0}; upgrade sig-tool to read it")]
+    UnsupportedVersion(u32),
+
+    #[error("Invalid format of the signature field")]
+    InvalidSignature,
+}
+
+// Participants of a committee multisignature: members in committee order and a
+// bitfield (bit i, least significant first, set when member i signed)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Committee {
+    pub id: String,
+    pub members: Vec<CommitteeMember>,
+    pub bitmap: String,   //Hex-Encoded
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommitteeMember {
+    pub key: String,
+    pub public_key: String,   //Hex-Encoded
+}
+
+// One signature of a distinct-message aggregate, as aggregate --mapping records it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AggregateSigner {
+    pub fingerprint: String,
+    pub public_key: String,       //Hex-Encoded
+    pub message_sha256: String,   //Hex-Encoded
+    // The message as the mapping gave it: a string, hex:<bytes> or @<path>
+    pub message: String,
+}
+
+// Text encodings of signatures and public keys, selected with --text-encoding
+pub const HEX_ENCODING: &str = "hex";
+pub const BASE64_ENCODING: &str = "base64";
+
+// Optional details stored alongside a signature
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SignatureInfo {
+    // When the signature was made, as a Unix time; saving fills in the current time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+
+    // Set when the timestamp is part of the signed payload rather than only recorded
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub signed_timestamp: bool,
+
+    // Domain separation tag the signature was made under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dst: Option<String>,
+
+    // Ciphersuite preset the DST came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ciphersuite: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committee: Option<Committee>,
+
+    // Signers of an aggregate made with --mapping and the messages they signed, in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signers: Option<Vec<AggregateSigner>>,
+
+    // Wire format of an ECDSA signature: "der" or "compact"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+
+    // Bitcoin address a BIP-322 signature was made for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+
+    // Set when a precomputed digest was signed instead of the message
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prehashed: bool,
+
+    // Hash function the message went through before its digest was signed, with --prehash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prehash: Option<String>,
+
+    // Set when extra entropy was mixed into the ECDSA nonce
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hedged: bool,
+
+    // Set on partial signatures made with a key share
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share: Option<ShareInfo>,
+
+    // Fingerprint of the keystore key that made the signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
+
+    // Public key that made the signature, hex-encoded, so a verifier knows which key to
+    // fetch; a hint only, since anyone editing the file can change it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+
+    // SHA-256 of the message, hex-encoded, to tell a wrong message apart from a bad signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_sha256: Option<String>,
+
+    // Text encoding of the signature string: "base64", or hex when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_encoding: Option<String>,
+
+    // Message the signature covers, base64-encoded, when signed with --embed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    // Files a sign-tree signature covers; the signature is over this manifest as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<TreeManifest>,
+}
+
+// The files under a directory, sorted by path. Symlinks are recorded with their target
+// rather than followed, and directories only through the files in them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TreeManifest {
+    pub entries: Vec<TreeEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TreeEntry {
+    // Relative to the signed directory, with / between components
+    pub path: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    // SHA-256 of the file contents, hex-encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink: Option<String>,
+}
+
+// Which share of which threshold key produced a partial signature
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShareInfo {
+    pub index: u32,
+    pub threshold: u32,
+    pub public_key: String,   //Hex-Encoded group public key
+}
+
+// Format version of signature files. Files written before versioning have no version
+// field and are version 1; every optional field still loads from them.
+//   1: the original format
+//   2: files record their version, and sign embeds the public key and message digest
+pub const SIGNATURE_FILE_VERSION: u32 = 2;
+
+// The scheme, signature and details of a signature file's contents
+pub fn parse_signature_file(contents: &[u8]) -> Result<(String, Vec<u8>, SignatureInfo), SignatureFileError> {
+    #[derive(Deserialize)]
+    struct SignatureFile {
+        #[serde(default = "legacy_version")]
+        version: u32,
+        scheme: String,
+        signature: String,
+        #[serde(flatten)]
+        info: SignatureInfo,
+    }
+    
+    let sig_file: SignatureFile = serde_json::from_slice(contents)?;
+    if sig_file.version == 0 || sig_file.version > SIGNATURE_FILE_VERSION {
+        return Err(SignatureFileError::UnsupportedVersion(sig_file.version));
+    }
+    
+    let signature_bytes = decode_signature(&sig_file.signature, sig_file.info.signature_encoding.as_deref())
+        .ok_or(SignatureFileError::InvalidSignature)?;
+    
+    Ok((sig_file.scheme, signature_bytes, sig_file.info))
+}
+
+// Decode the signature string of a signature file. Files without a signature_encoding are
+// hex, unless written by hand in base64. Some base64 strings are also valid hex, which is
+// why saving records the encoding.
+pub fn decode_signature(signature: &str, signature_encoding: Option<&str>) -> Option<Vec<u8>> {
+    match signature_encoding {
+        Some(BASE64_ENCODING) => BASE64_STANDARD.decode(signature).ok(),
+        Some(_) => None,
+        None => hex::decode(signature).ok().or_else(|| BASE64_STANDARD.decode(signature).ok()),
+    }
+}
+
+// Everything in a signature file, for inspect-sig: the fields parse_signature_file reads
+// and any it does not know, such as those of a newer format version
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SignatureFileContents {
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+    pub scheme: String,
+    pub signature: String,
+    #[serde(flatten)]
+    pub info: SignatureInfo,
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, serde_json::Value>,
+}
+
+// Files written before versioning
+fn legacy_version() -> u32 {
+    1
+}
@@ -0,0 +1,320 @@
+use crate::backend::{check_can_sign, SigningKeyStore};
+use crate::crypto::registry::{self, DynScheme};
+use crate::crypto::SignatureError;
+use crate::storage::{KeyEntry, StorageError};
+use std::future::{self, Future};
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use thiserror::Error;
+
+// Signing behind a trait that remote signers (an HSM, web3signer, anything reached over the
+// network) can implement without blocking a thread per request. Keys in a keystore sign
+// through KeyStoreSigner, so commands sign the same way whichever kind of signer holds
+// the key; BlockingSigner runs either from synchronous code such as the command line.
+
+#[derive(Error, Debug)]
+pub enum SignerError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+
+    // The signer could not be reached or did not answer; trying again may succeed
+    #[error("The signer is unavailable: {0}")]
+    Unavailable(String),
+
+    // The signer answered and will not sign this message, e.g. to protect against slashing
+    #[error("The signer refused to sign: {0}")]
+    Refused(String),
+
+    #[error("Could not start the runtime for the signer: {0}")]
+    Runtime(std::io::Error),
+
+    #[error("This build cannot run remote signers; enable the remote-signers feature")]
+    NoRuntime,
+}
+
+// What AsyncSigner::sign returns; boxed so signers can be chosen at run time as dyn AsyncSigner
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, SignerError>> + Send + 'a>>;
+
+pub trait AsyncSigner: Send + Sync {
+    // The serialized public key signatures verify against
+    fn public_key(&self) -> &[u8];
+
+    // Sign a message, naming the scheme as key entries and signature files do, and return
+    // the serialized signature
+    fn sign<'a>(&'a self, scheme: &'a str, message: &'a [u8]) -> SignFuture<'a>;
+
+    // Whether sign needs an async runtime (for its I/O or timers) to make progress; only
+    // signers that finish without one should say no
+    fn needs_runtime(&self) -> bool {
+        true
+    }
+}
+
+// For a signer picked at run time, e.g. BlockingSigner<Box<dyn AsyncSigner>>
+impl<S: AsyncSigner + ?Sized> AsyncSigner for Box<S> {
+    fn public_key(&self) -> &[u8] {
+        (**self).public_key()
+    }
+
+    fn sign<'a>(&'a self, scheme: &'a str, message: &'a [u8]) -> SignFuture<'a> {
+        (**self).sign(scheme, message)
+    }
+
+    fn needs_runtime(&self) -> bool {
+        (**self).needs_runtime()
+    }
+}
+
+// A key from a keystore, with its private key, signing in the calling thread
+pub struct KeyStoreSigner {
+    name: String,
+    scheme: String,
+    registered: &'static dyn DynScheme,
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl KeyStoreSigner {
+    // Refuses public-only and expired keys
+    pub fn new(entry: &KeyEntry) -> Result<Self, SignerError> {
+        check_can_sign(entry)?;
+        Self::new_allowing_expired(entry)
+    }
+
+    // For callers that sign with an expired key on purpose, as sign --allow-expired does
+    pub fn new_allowing_expired(entry: &KeyEntry) -> Result<Self, SignerError> {
+        let name = entry.metadata.name.clone();
+        if entry.private_key.is_empty() {
+            return Err(StorageError::PublicOnly(name).into());
+        }
+        let registered = registry::lookup(&entry.metadata.scheme)
+            .ok_or_else(|| StorageError::UnsupportedScheme(entry.metadata.scheme.clone()))?;
+        Ok(KeyStoreSigner {
+            name,
+            scheme: entry.metadata.scheme.clone(),
+            registered,
+            private_key: hex::decode(&entry.private_key).map_err(|_| StorageError::InvalidFormat("private_key"))?,
+            public_key: hex::decode(&entry.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?,
+        })
+    }
+
+    pub fn load(store: &impl SigningKeyStore, name: &str) -> Result<Self, SignerError> {
+        Self::new(&store.load(name)?)
+    }
+
+    fn sign_now(&self, scheme: &str, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        if scheme != self.scheme {
+            return Err(StorageError::WrongScheme(self.name.clone(), self.scheme.clone(), scheme.to_string()).into());
+        }
+        Ok(self.registered.sign_bytes(&self.private_key, message)?)
+    }
+}
+
+impl AsyncSigner for KeyStoreSigner {
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    fn sign<'a>(&'a self, scheme: &'a str, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(future::ready(self.sign_now(scheme, message)))
+    }
+
+    fn needs_runtime(&self) -> bool {
+        false
+    }
+}
+
+// Runs an AsyncSigner to completion from synchronous code. The tokio runtime is only
+// started for signers that need one, so signing with a keystore key starts no threads
+// and needs no reactor.
+pub struct BlockingSigner<S> {
+    signer: S,
+    #[cfg(feature = "remote-signers")]
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl<S: AsyncSigner> BlockingSigner<S> {
+    #[cfg(feature = "remote-signers")]
+    pub fn new(signer: S) -> Result<Self, SignerError> {
+        let runtime = if signer.needs_runtime() {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(SignerError::Runtime)?;
+            Some(runtime)
+        } else {
+            None
+        };
+        Ok(BlockingSigner { signer, runtime })
+    }
+
+    #[cfg(not(feature = "remote-signers"))]
+    pub fn new(signer: S) -> Result<Self, SignerError> {
+        if signer.needs_runtime() {
+            return Err(SignerError::NoRuntime);
+        }
+        Ok(BlockingSigner { signer })
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        self.signer.public_key()
+    }
+
+    pub fn sign(&self, scheme: &str, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let signing = self.signer.sign(scheme, message);
+        #[cfg(feature = "remote-signers")]
+        if let Some(runtime) = &self.runtime {
+            return runtime.block_on(signing);
+        }
+        block_on(signing)
+    }
+
+    pub fn into_inner(self) -> S {
+        self.signer
+    }
+}
+
+// Polls a future on this thread, parking between polls, for signers that need no runtime
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{SignatureScheme, ED25519};
+
+    fn ed25519_entry(name: &str) -> KeyEntry {
+        let (private_key, public_key) = ED25519::generate_keypair().unwrap();
+        KeyEntry::new::<ED25519>(name, &private_key, &public_key).unwrap()
+    }
+
+    #[test]
+    fn key_store_signer_refuses_expired_keys() {
+        let mut entry = ed25519_entry("deploy");
+        entry.metadata.expires_at = Some(1);
+        assert!(matches!(KeyStoreSigner::new(&entry), Err(SignerError::Storage(StorageError::KeyExpired(_, 1)))));
+
+        let signer = BlockingSigner::new(KeyStoreSigner::new_allowing_expired(&entry).unwrap()).unwrap();
+        let signature = signer.sign(ED25519::name(), b"release 1.2.0").unwrap();
+        let public_key = hex::decode(&entry.public_key).unwrap();
+        assert!(registry::lookup(ED25519::name()).unwrap().verify_bytes(&public_key, b"release 1.2.0", &signature).unwrap());
+
+        entry.private_key.clear();
+        assert!(matches!(KeyStoreSigner::new_allowing_expired(&entry), Err(SignerError::Storage(StorageError::PublicOnly(_)))));
+    }
+
+    // A signer whose future finishes on a later poll, after waking itself from another
+    // thread, and that may claim to need a runtime
+    struct DelayedSigner {
+        public_key: Vec<u8>,
+        needs_runtime: bool,
+    }
+
+    impl AsyncSigner for DelayedSigner {
+        fn public_key(&self) -> &[u8] {
+            &self.public_key
+        }
+
+        fn sign<'a>(&'a self, _scheme: &'a str, message: &'a [u8]) -> SignFuture<'a> {
+            let mut polled = false;
+            Box::pin(future::poll_fn(move |context| {
+                if polled {
+                    return Poll::Ready(Ok(message.to_vec()));
+                }
+                polled = true;
+                let waker = context.waker().clone();
+                thread::spawn(move || {
+                    thread::sleep(std::time::Duration::from_millis(20));
+                    waker.wake();
+                });
+                Poll::Pending
+            }))
+        }
+
+        fn needs_runtime(&self) -> bool {
+            self.needs_runtime
+        }
+    }
+
+    #[test]
+    fn blocking_signer_without_a_runtime() {
+        let signer = BlockingSigner::new(DelayedSigner { public_key: vec![1], needs_runtime: false }).unwrap();
+        #[cfg(feature = "remote-signers")]
+        assert!(signer.runtime.is_none());
+        assert_eq!(signer.sign("test", b"woken from another thread").unwrap(), b"woken from another thread");
+        assert_eq!(signer.public_key(), [1]);
+
+        let entry = ed25519_entry("deploy");
+        let signer = BlockingSigner::new(KeyStoreSigner::new(&entry).unwrap()).unwrap();
+        assert!(signer.sign(ED25519::name(), b"release").is_ok());
+        assert!(matches!(
+            signer.sign("ECDSA-secp256k1", b"release"),
+            Err(SignerError::Storage(StorageError::WrongScheme(..)))
+        ));
+    }
+
+    #[cfg(feature = "remote-signers")]
+    #[test]
+    fn blocking_signer_starts_a_runtime_for_signers_that_need_one() {
+        let signer = BlockingSigner::new(DelayedSigner { public_key: vec![1], needs_runtime: true }).unwrap();
+        assert!(signer.runtime.is_some());
+        assert_eq!(signer.sign("test", b"on the runtime").unwrap(), b"on the runtime");
+
+        // A signer that waits on the runtime's timer
+        struct TimerSigner;
+        impl AsyncSigner for TimerSigner {
+            fn public_key(&self) -> &[u8] {
+                &[]
+            }
+
+            fn sign<'a>(&'a self, _scheme: &'a str, message: &'a [u8]) -> SignFuture<'a> {
+                Box::pin(async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    Ok(message.to_vec())
+                })
+            }
+        }
+        assert_eq!(BlockingSigner::new(TimerSigner).unwrap().sign("test", b"timed").unwrap(), b"timed");
+    }
+
+    #[cfg(not(feature = "remote-signers"))]
+    #[test]
+    fn blocking_signer_refuses_signers_that_need_a_runtime() {
+        let signer = BlockingSigner::new(DelayedSigner { public_key: vec![1], needs_runtime: true });
+        assert!(matches!(signer, Err(SignerError::NoRuntime)));
+    }
+
+    // block_on parks the calling thread, which must still be woken when that thread is
+    // driving a runtime, and must not need the runtime to make progress
+    #[cfg(feature = "remote-signers")]
+    #[test]
+    fn block_on_inside_a_runtime() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let signer = BlockingSigner::new(DelayedSigner { public_key: vec![1], needs_runtime: false }).unwrap();
+        let signature = runtime.block_on(async { signer.sign("test", b"inside a runtime") }).unwrap();
+        assert_eq!(signature, b"inside a runtime");
+
+        let entry = ed25519_entry("deploy");
+        let signer = BlockingSigner::new(KeyStoreSigner::new(&entry).unwrap()).unwrap();
+        assert!(runtime.block_on(async { signer.sign(ED25519::name(), b"release") }).is_ok());
+    }
+}
@@ -0,0 +1,260 @@
+use base64::prelude::*;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use thiserror::Error;
+
+// OpenSSH public keys and a client for the ssh-agent protocol (draft-miller-ssh-agent),
+// so keys already loaded in an agent can sign without being copied into the keystore.
+// Ed25519 agent signatures are plain Ed25519 signatures over the message.
+
+/// Scheme recorded in signature files made by an ssh-agent Ed25519 key
+pub const SSH_AGENT_SCHEME: &str = "Ed25519-ssh-agent";
+pub const SSH_ED25519: &str = "ssh-ed25519";
+const SSH_RSA: &str = "ssh-rsa";
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+// Sign request flag asking for an RSA signature with SHA-256 rather than SHA-1
+const SSH_AGENT_RSA_SHA2_256: u32 = 2;
+
+// Agents hold a handful of keys; anything this large is not a well-formed reply
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+
+#[derive(Error, Debug)]
+pub enum SshError {
+    #[error("SSH_AUTH_SOCK is not set; start ssh-agent and add your key with ssh-add")]
+    NoAgent,
+
+    #[error("Could not connect to ssh-agent at {0}: {1}")]
+    Connect(String, std::io::Error),
+
+    #[error("ssh-agent connection failed: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("No key matching {0} is loaded in ssh-agent; {1}")]
+    KeyNotLoaded(String, String),
+
+    #[error("ssh-agent refused to sign with {0}")]
+    Refused(String),
+
+    #[error("Invalid ssh-agent reply: {0}")]
+    Protocol(String),
+
+    #[error("Invalid OpenSSH public key: {0}")]
+    PublicKey(String),
+}
+
+/// A key loaded in the agent
+pub struct Identity {
+    pub blob: Vec<u8>,
+    pub comment: String,
+}
+
+impl Identity {
+    pub fn key_type(&self) -> String {
+        Reader::new(&self.blob).string_utf8().unwrap_or_default()
+    }
+
+    /// Fingerprint in the form ssh-keygen -l prints
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.blob)
+    }
+
+    // Keys are named by comment or fingerprint, with or without the "SHA256:" prefix
+    fn matches(&self, query: &str) -> bool {
+        let fingerprint = self.fingerprint();
+        self.comment == query || fingerprint == query || fingerprint.strip_prefix("SHA256:") == Some(query)
+    }
+}
+
+pub struct Agent {
+    #[cfg(unix)]
+    stream: std::os::unix::net::UnixStream,
+}
+
+impl Agent {
+    /// Connect to the agent at $SSH_AUTH_SOCK
+    pub fn connect() -> Result<Self, SshError> {
+        let socket = std::env::var_os("SSH_AUTH_SOCK").ok_or(SshError::NoAgent)?;
+        #[cfg(unix)]
+        {
+            let stream = std::os::unix::net::UnixStream::connect(&socket)
+                .map_err(|e| SshError::Connect(socket.to_string_lossy().into_owned(), e))?;
+            Ok(Self { stream })
+        }
+        #[cfg(not(unix))]
+        {
+            let error = std::io::Error::new(std::io::ErrorKind::Unsupported, "agent sockets need a Unix platform");
+            Err(SshError::Connect(socket.to_string_lossy().into_owned(), error))
+        }
+    }
+
+    pub fn identities(&mut self) -> Result<Vec<Identity>, SshError> {
+        let reply = self.request(&[SSH_AGENTC_REQUEST_IDENTITIES])?;
+        let mut reader = Reader::new(&reply);
+        if reader.byte()? != SSH_AGENT_IDENTITIES_ANSWER {
+            return Err(SshError::Protocol("expected an identities answer".into()));
+        }
+
+        let count = reader.u32()?;
+        let mut identities = Vec::new();
+        for _ in 0..count {
+            let blob = reader.string()?.to_vec();
+            let comment = reader.string_utf8()?;
+            identities.push(Identity { blob, comment });
+        }
+        Ok(identities)
+    }
+
+    /// The loaded key named by a comment or fingerprint
+    pub fn find(&mut self, query: &str) -> Result<Identity, SshError> {
+        let identities = self.identities()?;
+        let loaded: Vec<String> = identities
+            .iter()
+            .map(|identity| format!("{} {} ({})", identity.fingerprint(), identity.comment, identity.key_type()))
+            .collect();
+        identities.into_iter().find(|identity| identity.matches(query)).ok_or_else(|| {
+            let loaded = if loaded.is_empty() {
+                "the agent has no keys loaded".to_string()
+            } else {
+                format!("loaded keys: {}", loaded.join(", "))
+            };
+            SshError::KeyNotLoaded(query.to_string(), loaded)
+        })
+    }
+
+    /// Ask the agent to sign data, returning the signature algorithm and signature blob.
+    /// RSA keys are asked for rsa-sha2-256; an agent answering with SHA-1 is refused.
+    pub fn sign(&mut self, identity: &Identity, data: &[u8]) -> Result<(String, Vec<u8>), SshError> {
+        let key_type = identity.key_type();
+        let (flags, expected) = match key_type.as_str() {
+            SSH_RSA => (SSH_AGENT_RSA_SHA2_256, "rsa-sha2-256".to_string()),
+            _ => (0, key_type.clone()),
+        };
+
+        let mut request = vec![SSH_AGENTC_SIGN_REQUEST];
+        put_string(&mut request, &identity.blob);
+        put_string(&mut request, data);
+        request.extend_from_slice(&flags.to_be_bytes());
+
+        let reply = self.request(&request)?;
+        let mut reader = Reader::new(&reply);
+        match reader.byte()? {
+            SSH_AGENT_SIGN_RESPONSE => {}
+            SSH_AGENT_FAILURE => return Err(SshError::Refused(format!("{} {}", identity.fingerprint(), identity.comment))),
+            other => return Err(SshError::Protocol(format!("unexpected message type {}", other))),
+        }
+
+        let signature = reader.string()?;
+        let mut reader = Reader::new(signature);
+        let algorithm = reader.string_utf8()?;
+        let blob = reader.string()?.to_vec();
+        if algorithm != expected {
+            return Err(SshError::Protocol(format!(
+                "asked for a {} signature but the agent made a {} one", expected, algorithm
+            )));
+        }
+        Ok((algorithm, blob))
+    }
+
+    fn request(&mut self, message: &[u8]) -> Result<Vec<u8>, SshError> {
+        let mut framed = (message.len() as u32).to_be_bytes().to_vec();
+        framed.extend_from_slice(message);
+        #[cfg(unix)]
+        {
+            self.stream.write_all(&framed)?;
+
+            let mut len = [0u8; 4];
+            self.stream.read_exact(&mut len)?;
+            let len = u32::from_be_bytes(len) as usize;
+            if len == 0 || len > MAX_MESSAGE_LEN {
+                return Err(SshError::Protocol(format!("message length {}", len)));
+            }
+            let mut reply = vec![0u8; len];
+            self.stream.read_exact(&mut reply)?;
+            Ok(reply)
+        }
+        #[cfg(not(unix))]
+        unreachable!("connect fails outside Unix")
+    }
+}
+
+/// Parse a public key line as in id_ed25519.pub: key type, base64 blob and comment
+pub fn parse_public_key(line: &str) -> Result<(String, Vec<u8>, String), SshError> {
+    let mut fields = line.split_whitespace();
+    let (Some(key_type), Some(encoded)) = (fields.next(), fields.next()) else {
+        return Err(SshError::PublicKey("expected \"<type> <base64 key> [comment]\"".into()));
+    };
+    let comment = fields.collect::<Vec<_>>().join(" ");
+    let blob = BASE64_STANDARD
+        .decode(encoded)
+        .map_err(|_| SshError::PublicKey("key is not base64".into()))?;
+
+    let inner_type = Reader::new(&blob).string_utf8().map_err(|_| SshError::PublicKey("truncated key".into()))?;
+    if inner_type != key_type {
+        return Err(SshError::PublicKey(format!("line says {} but the key is {}", key_type, inner_type)));
+    }
+    Ok((key_type.to_string(), blob, comment))
+}
+
+/// The 32-byte public key in an ssh-ed25519 key blob
+pub fn ed25519_public_key(blob: &[u8]) -> Result<Vec<u8>, SshError> {
+    let mut reader = Reader::new(blob);
+    let key_type = reader.string_utf8().map_err(|_| SshError::PublicKey("truncated key".into()))?;
+    if key_type != SSH_ED25519 {
+        return Err(SshError::PublicKey(format!("expected an {} key, not {}", SSH_ED25519, key_type)));
+    }
+    match reader.string() {
+        Ok(key) if key.len() == 32 => Ok(key.to_vec()),
+        _ => Err(SshError::PublicKey("Ed25519 keys are 32 bytes".into())),
+    }
+}
+
+pub fn fingerprint(blob: &[u8]) -> String {
+    format!("SHA256:{}", BASE64_STANDARD_NO_PAD.encode(Sha256::digest(blob)))
+}
+
+fn put_string(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+// Reads the SSH wire encoding (RFC 4251): bytes, big-endian u32s and length-prefixed strings
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SshError> {
+        if self.data.len() < len {
+            return Err(SshError::Protocol("message is truncated".into()));
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, SshError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SshError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], SshError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn string_utf8(&mut self) -> Result<String, SshError> {
+        Ok(String::from_utf8_lossy(self.string()?).into_owned())
+    }
+}
@@ -1,121 +1,1213 @@
-use crate::crypto::{SignatureError,SignatureScheme};
-use serde::{Serialize,Deserialize};
-use std::fs::{self,File};
-use std::io::Read;
-use std::path::{Path,PathBuf};
+use crate::crypto::encryption::{self, Argon2Params};
+use crate::crypto::{SignatureError, SignatureScheme};
+use crate::signature_file::{parse_signature_file, SignatureFileError};
+use base64::prelude::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+pub use crate::signature_file::{
+    decode_signature, AggregateSigner, Committee, CommitteeMember, ShareInfo,
+    SignatureFileContents, SignatureInfo, TreeEntry, TreeManifest, BASE64_ENCODING, HEX_ENCODING,
+    SIGNATURE_FILE_VERSION,
+};
 
-#[derive(Error,Debug)]
-pub enum StorageError{
+#[derive(Error, Debug)]
+pub enum StorageError {
     #[error("I/O error: {0}")]
     IO(#[from] std::io::Error),
-    
+
     #[error("Signature error: {0}")]
     Signature(#[from] SignatureError),
-    
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
-    
+
     #[error("Key not found: {0}")]
     KeyNotFound(String),
-    
-    #[error("Invalid key format")]
-    InvalidFormat,
 
+    #[error("Key {0} already exists; pass --force to overwrite it or choose another name")]
+    KeyExists(String),
+
+    // Names the field that failed to decode or did not hold what it should
+    #[error("Invalid format of the {0} field")]
+    InvalidFormat(&'static str),
+
+    #[error("Unsupported format version {0}; upgrade sig-tool to read it")]
+    UnsupportedVersion(u32),
+
+    #[error("Keystore files are readable by other users: {0}")]
+    InsecurePermissions(String),
+
+    #[error("Key name {0:?} is reserved for the keystore index")]
+    ReservedName(String),
+
+    #[error("Invalid key name {0:?}: {1}")]
+    InvalidKeyName(String, String),
+
+    #[error("Key {0} is kept in the {1} backend and is unavailable with the {2} backend; pass --backend {1} to use it")]
+    BackendUnavailable(String, String, String),
+
+    #[error("OS keychain error: {0}")]
+    Keychain(String),
+
+    #[error("YubiKey error: {0}")]
+    YubiKey(String),
+
+    #[error("The keystore at {0} is read-only")]
+    ReadOnly(String),
+
+    #[error("Key {0} uses the {1} scheme, not {2}")]
+    WrongScheme(String, String, String),
+
+    #[error("Key {0} is a public-only key and cannot sign")]
+    PublicOnly(String),
+
+    #[error("Key {0} expired at Unix time {1} and cannot sign")]
+    KeyExpired(String, u64),
+
+    #[error("The message given does not match the message embedded in {0}")]
+    EmbeddedMessageMismatch(String),
+
+    #[error("Unsupported signature scheme: {0}")]
+    UnsupportedScheme(String),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct KeyMetadata{
+impl StorageError {
+    /// The key the error is about, for callers that report it apart from the message
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            StorageError::KeyNotFound(key)
+            | StorageError::KeyExists(key)
+            | StorageError::ReservedName(key)
+            | StorageError::InvalidKeyName(key, _)
+            | StorageError::BackendUnavailable(key, _, _)
+            | StorageError::WrongScheme(key, _, _)
+            | StorageError::PublicOnly(key)
+            | StorageError::KeyExpired(key, _) => Some(key),
+            _ => None,
+        }
+    }
+}
 
-    pub scheme:String,
-    pub created_at:u64,
-    pub name:String
+// Signature files report the problem as the keystore reports the same one in key entries
+impl From<SignatureFileError> for StorageError {
+    fn from(err: SignatureFileError) -> Self {
+        match err {
+            SignatureFileError::Json(err) => StorageError::Json(err),
+            SignatureFileError::UnsupportedVersion(version) => {
+                StorageError::UnsupportedVersion(version)
+            }
+            SignatureFileError::InvalidSignature => StorageError::InvalidFormat("signature"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyMetadata {
+    pub scheme: String,
+    pub created_at: u64,
+    pub name: String,
+
+    // Hierarchical derivation path (e.g. EIP-2333) for derived keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+
+    // Set for keys generated deterministically from a seed file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub seed_derived: bool,
+
+    // Free-form labels for filtering, e.g. "prod"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    // Unix time after which the key may no longer sign
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+
+    // Ethereum address of keys imported from a web3 keystore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+
+    // Backend holding the private key when it is not in the key file, e.g. "keychain"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
 
+    // PIV slot of a key generated on a YubiKey, whose private key never leaves it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub piv_slot: Option<String>,
+
+    // Unix time of the key's last signature, and how many it has made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub use_count: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct KeyEntry{
-    pub metadata:KeyMetadata,
-    pub private_key:String,   //Hex-Encoded
-    pub public_key:String     //Hex_Encoded
+impl KeyMetadata {
+    pub fn new(name: &str, scheme: &str) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            name: name.to_string(),
+            derivation_path: None,
+            seed_derived: false,
+            tags: Vec::new(),
+            expires_at: None,
+            address: None,
+            backend: None,
+            piv_slot: None,
+            last_used_at: None,
+            use_count: 0,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+fn is_zero(count: &u64) -> bool {
+    *count == 0
+}
+
+// Format version of key entries. Files written before versioning have no version field
+// and are version 1; older versions are upgraded in memory when loaded and rewritten by
+// migrate.
+//   1: the original format
+//   2: entries record their version
+pub const CURRENT_VERSION: u32 = 2;
+const LEGACY_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyEntry {
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+    pub metadata: KeyMetadata,
+    pub private_key: String, //Hex-Encoded
+    pub public_key: String,  //Hex_Encoded
+}
+
+fn legacy_version() -> u32 {
+    LEGACY_VERSION
+}
+
+// Upgrades from each version to the next, applied to the raw JSON so every step only
+// needs to know the two formats involved. MIGRATIONS[i] upgrades version i + 1.
+type Migration = fn(serde_json::Value) -> Result<serde_json::Value, StorageError>;
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+fn migrate_v1_to_v2(mut entry: serde_json::Value) -> Result<serde_json::Value, StorageError> {
+    entry["version"] = 2.into();
+    Ok(entry)
+}
+
+impl KeyEntry {
+    // A new entry for a key pair of scheme S, with no metadata beyond its name and scheme
+    pub fn new<S: SignatureScheme>(
+        name: &str,
+        private_key: &S::PrivateKey,
+        public_key: &S::PublicKey,
+    ) -> Result<Self, StorageError> {
+        Ok(KeyEntry {
+            version: CURRENT_VERSION,
+            metadata: KeyMetadata::new(name, S::name()),
+            private_key: hex::encode(S::serialize_private_key(private_key)?),
+            public_key: hex::encode(S::serialize_public_key(public_key)?),
+        })
+    }
+
+    // Parse a stored entry, upgrading it to CURRENT_VERSION, and return it with the
+    // version it was stored in. Entries from a newer sig-tool are refused untouched.
+    pub fn parse(contents: &[u8]) -> Result<(Self, u32), StorageError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            #[serde(default = "legacy_version")]
+            version: u32,
+        }
+
+        let stored = serde_json::from_slice::<Versioned>(contents)?.version;
+        if stored == 0 || stored > CURRENT_VERSION {
+            return Err(StorageError::UnsupportedVersion(stored));
+        }
+
+        let mut entry: serde_json::Value = serde_json::from_slice(contents)?;
+        for migration in &MIGRATIONS[(stored - 1) as usize..] {
+            entry = migration(entry)?;
+        }
+        Ok((serde_json::from_value(entry)?, stored))
+    }
+
+    pub fn fingerprint(&self) -> Result<String, StorageError> {
+        let public_key =
+            hex::decode(&self.public_key).map_err(|_| StorageError::InvalidFormat("public_key"))?;
+        Ok(fingerprint_of(&self.metadata.scheme, &public_key))
+    }
+}
+
+// Short stable identifier of a key: the first 8 bytes of SHA-256(scheme || public key), in hex
+pub fn fingerprint_of(scheme: &str, public_key: &[u8]) -> String {
+    let digest = Sha256::new()
+        .chain_update(scheme.as_bytes())
+        .chain_update(public_key)
+        .finalize();
+    hex::encode(&digest[..8])
 }
 
 pub struct KeyStore {
     storage_dir: PathBuf,
+    backend: Box<dyn KeyStorageBackend>,
+    read_only: bool,
+}
+
+pub const FILE_BACKEND: &str = "file";
+
+// Where private keys are kept. Key files always hold the metadata and public key; the
+// private key is replaced there by whatever the backend returns from store_private_key.
+// Backends are shared along with their KeyStore, between threads too.
+pub trait KeyStorageBackend: Send + Sync {
+    // Name recorded in the metadata of keys whose private key the backend keeps elsewhere
+    fn name(&self) -> &'static str;
+
+    // Keep a hex private key, returning what the key file should record in its place
+    fn store_private_key(&self, name: &str, private_key: &str) -> Result<String, StorageError>;
+
+    // Recover a hex private key from what its key file recorded
+    fn load_private_key(&self, name: &str, stored: &str) -> Result<String, StorageError>;
+
+    fn delete_private_key(&self, name: &str) -> Result<(), StorageError>;
+}
+
+// The private key stays in the key file itself
+pub struct FileBackend;
+
+impl KeyStorageBackend for FileBackend {
+    fn name(&self) -> &'static str {
+        FILE_BACKEND
+    }
+
+    fn store_private_key(&self, _name: &str, private_key: &str) -> Result<String, StorageError> {
+        Ok(private_key.to_string())
+    }
+
+    fn load_private_key(&self, _name: &str, stored: &str) -> Result<String, StorageError> {
+        Ok(stored.to_string())
+    }
+
+    fn delete_private_key(&self, _name: &str) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+// The backend holding a stored entry's private key; public-only keys have none
+fn held_by(stored: &KeyEntry) -> Option<&str> {
+    match &stored.metadata.backend {
+        Some(backend) => Some(backend),
+        None => (!stored.private_key.is_empty()).then_some(FILE_BACKEND),
+    }
 }
 
-impl KeyStore{
-    pub fn new(storage_dir: impl AsRef<Path>)->Result<Self,StorageError>{
+// File in the keystore caching the metadata of every key, so listing reads one file
+// instead of parsing each key. The name "index" is therefore reserved for keys.
+pub const INDEX_FILE: &str = "index.json";
+// The audit log the command line appends to, which is not a key
+pub const AUDIT_FILE: &str = "audit.log";
+// Held while a key's metadata is read, changed and written back, so concurrent sig-tool
+// processes do not lose each other's updates. Hidden, so never mistaken for a key.
+const LOCK_FILE: &str = ".lock";
+pub const INDEX_VERSION: u32 = 1;
+const RESERVED_KEY_NAME: &str = "index";
+pub const MAX_KEY_NAME_LEN: usize = 128;
+
+// Key names become file names in the keystore, so only a portable set of characters is
+// allowed, and nothing that could name a hidden file or reach outside the directory
+pub fn validate_key_name(name: &str) -> Result<(), StorageError> {
+    let invalid = |reason: &str| {
+        Err(StorageError::InvalidKeyName(
+            name.to_string(),
+            reason.to_string(),
+        ))
+    };
+
+    if name.is_empty() {
+        return invalid("must not be empty");
+    }
+    if name.len() > MAX_KEY_NAME_LEN {
+        return invalid(&format!("must be at most {} characters", MAX_KEY_NAME_LEN));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return invalid(&format!(
+            "{:?} is not allowed; use letters, digits, '-', '_' and '.'",
+            c
+        ));
+    }
+    if name.starts_with('.') || name.contains("..") {
+        return invalid("must not start with '.' or contain '..'");
+    }
+    if name == RESERVED_KEY_NAME {
+        return Err(StorageError::ReservedName(name.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct KeyIndex {
+    version: u32,
+    keys: BTreeMap<String, KeySummary>,
+}
+
+// What the index records about a key: its metadata and fingerprint
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeySummary {
+    #[serde(flatten)]
+    pub metadata: KeyMetadata,
+    pub fingerprint: String,
+}
+
+impl KeySummary {
+    fn of(entry: &KeyEntry) -> Result<Self, StorageError> {
+        Ok(Self {
+            metadata: entry.metadata.clone(),
+            fingerprint: entry.fingerprint()?,
+        })
+    }
+}
+
+impl KeyStore {
+    // Open a keystore, creating its directory if needed. An existing directory that cannot
+    // be written to, such as one on a read-only mount, is opened read-only.
+    pub fn new(storage_dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let storage_dir = storage_dir.as_ref().to_path_buf();
+        if storage_dir.is_dir() && !is_writable(&storage_dir) {
+            return Self::new_read_only(storage_dir);
+        }
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&storage_dir)?;
+        Ok(Self {
+            storage_dir,
+            backend: Box::new(FileBackend),
+            read_only: false,
+        })
+    }
+
+    // Open an existing keystore for loading and listing only; anything that would change
+    // it fails with ReadOnly
+    pub fn new_read_only(storage_dir: impl AsRef<Path>) -> Result<Self, StorageError> {
         let storage_dir = storage_dir.as_ref().to_path_buf();
-        fs::create_dir_all(&storage_dir)?;
-        Ok( Self {storage_dir} )
+        if !storage_dir.is_dir() {
+            return Err(StorageError::IO(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("keystore {} does not exist", storage_dir.display()),
+            )));
+        }
+        Ok(Self {
+            storage_dir,
+            backend: Box::new(FileBackend),
+            read_only: true,
+        })
+    }
+
+    pub fn check_writable(&self) -> Result<(), StorageError> {
+        if self.read_only {
+            return Err(StorageError::ReadOnly(
+                self.storage_dir.display().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Keep private keys of keys written from now on in another backend
+    pub fn with_backend(self, backend: Box<dyn KeyStorageBackend>) -> Self {
+        Self { backend, ..self }
     }
 
-    pub fn save_keypair<S:SignatureScheme>(
+    // The keystore directory and key files that group or other users can access. With
+    // strict set, any such path is an error instead. Always empty outside Unix.
+    pub fn check_permissions(&self, strict: bool) -> Result<Vec<PathBuf>, StorageError> {
+        let mut exposed = Vec::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if fs::metadata(&self.storage_dir)?.permissions().mode() & 0o077 != 0 {
+                exposed.push(self.storage_dir.clone());
+            }
+            for entry in fs::read_dir(&self.storage_dir)? {
+                let path = entry?.path();
+                if path.is_file()
+                    && path.extension().unwrap_or_default() == "json"
+                    && fs::metadata(&path)?.permissions().mode() & 0o077 != 0
+                {
+                    exposed.push(path);
+                }
+            }
+        }
+
+        if strict && !exposed.is_empty() {
+            let paths: Vec<String> = exposed
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(StorageError::InsecurePermissions(paths.join(", ")));
+        }
+        Ok(exposed)
+    }
+
+    // Save a new key pair, failing with KeyExists rather than replacing a key of the same
+    // name unless overwrite is set
+    pub fn save_keypair<S: SignatureScheme>(
         &self,
-        name:&str,
+        name: &str,
         private_key: &S::PrivateKey,
-        public_key: &S::PublicKey
-    )->Result<(),SignatureError>{
-
-        let private_key = S::serialize_private_key(private_key)?;
-        let public_key= S::serialize_public_key(public_key)?;
-        let metadata = KeyMetadata {
-            scheme: S::name().to_string(),
-            created_at:std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-            name: name.to_string()
-        };
+        public_key: &S::PublicKey,
+        overwrite: bool,
+    ) -> Result<(), StorageError> {
+        validate_key_name(name)?;
+        let entry = KeyEntry::new::<S>(name, private_key, public_key)?;
+        if overwrite {
+            self.save_key_entry(&entry)
+        } else {
+            self.create_key_entry(&entry)
+        }
+    }
+
+    // Persist an already built entry under its metadata name
+    pub fn save_key_entry(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        self.locked(|| self.write_entry(entry))
+    }
 
-        let entry = KeyEntry{
-            metadata,
-            private_key: hex::encode(&private_key),
-            public_key:hex::encode(&public_key)
+    // save_key_entry, failing with KeyExists if the name is taken. The check and the write
+    // happen under the lock, so of two processes creating the same key only one succeeds.
+    pub fn create_key_entry(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        validate_key_name(&entry.metadata.name)?;
+        self.locked(|| {
+            if self.key_exists(&entry.metadata.name) {
+                return Err(StorageError::KeyExists(entry.metadata.name.clone()));
+            }
+            self.write_entry(entry)
+        })
+    }
+
+    // Write an entry with its private key handed to the backend. A key whose private key
+    // another backend holds is never replaced, as that would orphan it there.
+    fn write_entry(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        let name = &entry.metadata.name;
+        validate_key_name(name)?;
+        self.check_writable()?;
+        let replaced = match self.read_entry(name) {
+            Ok(existing) => {
+                self.check_backend(&existing)?;
+                held_by(&existing).is_some_and(|backend| backend != FILE_BACKEND)
+            }
+            Err(_) => false,
         };
-        let path = self.key_path(name);
-        let file = File::create(path)?;
-        serde_json::to_writer_pretty(file, &entry)?;
-       
+
+        // Entries without a private key keep the backend they name, which is how keys
+        // generated on a device are recorded
+        let mut stored = entry.clone();
+        if !entry.private_key.is_empty() {
+            stored.private_key = self.backend.store_private_key(name, &entry.private_key)?;
+            stored.metadata.backend =
+                (self.backend.name() != FILE_BACKEND).then(|| self.backend.name().to_string());
+        } else if replaced && stored.metadata.backend.is_none() {
+            self.backend.delete_private_key(name)?;
+        }
+        self.write_stored(&stored)
+    }
+
+    // Write an entry exactly as it should appear in its key file
+    fn write_stored(&self, entry: &KeyEntry) -> Result<(), StorageError> {
+        validate_key_name(&entry.metadata.name)?;
+        let contents = serde_json::to_vec_pretty(entry)?;
+        self.write_file(&format!("{}.json", entry.metadata.name), &contents)?;
+        self.update_index(|keys| {
+            keys.insert(entry.metadata.name.clone(), KeySummary::of(entry)?);
+            Ok(())
+        })?;
         Ok(())
+    }
 
+    fn write_file(&self, file_name: &str, contents: &[u8]) -> Result<(), StorageError> {
+        self.check_writable()?;
+        write_private(self.storage_dir.join(file_name), contents)?;
+        Ok(())
+    }
+    // Load a key with its private key, failing with BackendUnavailable if another
+    // backend holds it
+    pub fn load_key_entry(&self, name: &str) -> Result<KeyEntry, StorageError> {
+        let mut entry = self.read_entry(name)?;
+        self.check_backend(&entry)?;
+        if held_by(&entry).is_some() {
+            entry.private_key = self.backend.load_private_key(name, &entry.private_key)?;
+        }
+        Ok(entry)
     }
-    pub fn load_key_entry(&self,name: &str)->Result<KeyEntry,StorageError>{
 
-        let path = self.key_path(name);
+    // A key as stored in its file, without going to the backend for its private key.
+    // Enough for its metadata and public key.
+    pub fn read_entry(&self, name: &str) -> Result<KeyEntry, StorageError> {
+        let path = self.key_path(name)?;
         let mut file = File::open(path).map_err(|_| StorageError::KeyNotFound(name.to_string()))?;
-        
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        
-        let entry: KeyEntry = serde_json::from_str(&contents)?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        let (entry, _) = KeyEntry::parse(&contents)?;
         Ok(entry)
     }
 
-    pub fn list_keys(&self) -> Result<Vec<KeyMetadata>, StorageError> {
-        let mut results = Vec::new();
-        
+    fn check_backend(&self, stored: &KeyEntry) -> Result<(), StorageError> {
+        match held_by(stored) {
+            Some(backend) if backend != self.backend.name() => {
+                Err(StorageError::BackendUnavailable(
+                    stored.metadata.name.clone(),
+                    backend.to_string(),
+                    self.backend.name().to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Remove a key's file from the keystore, and its private key from the backend holding
+    // it. Files that do not parse are removed all the same.
+    pub fn delete_key(&self, name: &str) -> Result<(), StorageError> {
+        let path = self.key_path(name)?;
+        self.locked(|| {
+            if !path.is_file() {
+                return Err(StorageError::KeyNotFound(name.to_string()));
+            }
+            if let Ok(stored) = self.read_entry(name) {
+                self.check_backend(&stored)?;
+                if held_by(&stored).is_some() {
+                    self.backend.delete_private_key(name)?;
+                }
+            }
+
+            fs::remove_file(&path)?;
+            self.update_index(|keys| {
+                keys.remove(name);
+                Ok(())
+            })
+        })
+    }
+
+    // Overwrite a key's file with zeros and flush it to disk before removing it. On
+    // copy-on-write or journaling filesystems old blocks may survive regardless. The
+    // backend's copy of the private key goes first, while the file still says where it is.
+    pub fn shred_key(&self, name: &str) -> Result<(), StorageError> {
+        self.locked(|| {
+            let path = self.key_path(name)?;
+            if !path.is_file() {
+                return Err(StorageError::KeyNotFound(name.to_string()));
+            }
+            if let Ok(stored) = self.read_entry(name) {
+                self.check_backend(&stored)?;
+                if held_by(&stored).is_some() {
+                    self.backend.delete_private_key(name)?;
+                }
+            }
+
+            let len = fs::metadata(&path)?.len() as usize;
+            let mut file = OpenOptions::new().write(true).open(&path)?;
+            file.write_all(&vec![0u8; len])?;
+            file.sync_all()?;
+            drop(file);
+
+            fs::remove_file(path)?;
+            self.update_index(|keys| {
+                keys.remove(name);
+                Ok(())
+            })
+        })
+    }
+
+    // Move a key to a new name, failing with KeyExists rather than replacing a key of that
+    // name unless overwrite is set. The old file is only removed once the renamed entry is
+    // durably written, so a crash never loses the key.
+    pub fn rename_key(&self, from: &str, to: &str, overwrite: bool) -> Result<(), StorageError> {
+        self.locked(|| {
+            let mut entry = self.load_key_entry(from)?;
+            if from == to {
+                return Ok(());
+            }
+            if !overwrite && self.key_exists(to) {
+                return Err(StorageError::KeyExists(to.to_string()));
+            }
+            entry.metadata.name = to.to_string();
+            self.write_entry(&entry)?;
+
+            fs::remove_file(self.key_path(from)?)?;
+            if held_by(&entry).is_some() {
+                self.backend.delete_private_key(from)?;
+            }
+            self.update_index(|keys| {
+                keys.remove(from);
+                Ok(())
+            })
+        })
+    }
+
+    // Add and remove tags on a key, returning its new tags. Only the metadata changes; the
+    // key material is written back exactly as it was stored.
+    pub fn update_tags(
+        &self,
+        name: &str,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<String>, StorageError> {
+        self.locked(|| {
+            let mut entry = self.read_entry(name)?;
+            let tags = &mut entry.metadata.tags;
+            tags.retain(|tag| !remove.contains(tag));
+            for tag in add {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+
+            self.write_stored(&entry)?;
+            Ok(entry.metadata.tags)
+        })
+    }
+
+    // Set or clear the time a key expires at
+    pub fn set_expiry(&self, name: &str, expires_at: Option<u64>) -> Result<(), StorageError> {
+        self.locked(|| {
+            let mut entry = self.read_entry(name)?;
+            entry.metadata.expires_at = expires_at;
+            self.write_stored(&entry)
+        })
+    }
+
+    // Count a signature made with a key and note when it was made. Like tags, only the
+    // metadata changes.
+    pub fn record_use(&self, name: &str) -> Result<(), StorageError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.locked(|| {
+            let mut entry = self.read_entry(name)?;
+            entry.metadata.last_used_at = Some(now);
+            entry.metadata.use_count += 1;
+            self.write_stored(&entry)
+        })
+    }
+
+    // Run a change to the keystore's files under its lock file. The lock is released when
+    // the file is closed, even if the change fails. It is not reentrant: nothing run under
+    // it may call locked again.
+    fn locked<T>(
+        &self,
+        update: impl FnOnce() -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        self.check_writable()?;
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(false);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let lock = options.open(self.storage_dir.join(LOCK_FILE))?;
+        lock.lock()?;
+        update()
+    }
+
+    pub fn key_exists(&self, name: &str) -> bool {
+        self.key_path(name).is_ok_and(|path| path.exists())
+    }
+
+    // Every file in the keystore, skipping the hidden temporary files of in-progress writes.
+    // The audit log is left out too, so restoring an old backup cannot rewind it.
+    pub fn backup_files(&self) -> Result<Vec<BackupFile>, StorageError> {
+        let mut files = Vec::new();
         for entry in fs::read_dir(&self.storage_dir)? {
             let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_file() && path.extension().unwrap_or_default() == "json" {
-                if let Ok(file) = File::open(&path) {
-                    if let Ok(entry) = serde_json::from_reader::<_, KeyEntry>(file) {
-                        results.push(entry.metadata);
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_file()
+                && !name.starts_with('.')
+                && name != INDEX_FILE
+                && name != AUDIT_FILE
+            {
+                files.push(BackupFile {
+                    contents: hex::encode(fs::read(entry.path())?),
+                    name,
+                });
+            }
+        }
+
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    pub fn file_exists(&self, file_name: &str) -> bool {
+        self.storage_dir.join(file_name).exists()
+    }
+
+    // Write a file from a backup archive, whose names BackupArchive::open has checked
+    pub fn restore_file(&self, file: &BackupFile) -> Result<(), StorageError> {
+        let contents =
+            hex::decode(&file.contents).map_err(|_| StorageError::InvalidFormat("contents"))?;
+        self.locked(|| {
+            self.write_file(&file.name, &contents)?;
+            if let Some(name) = file.name.strip_suffix(".json") {
+                let (entry, _) = KeyEntry::parse(&contents)?;
+                self.update_index(|keys| {
+                    keys.insert(name.to_string(), KeySummary::of(&entry)?);
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    // List keys from the index, rebuilding it first when it is missing or does not name
+    // exactly the key files present
+    pub fn list_keys(&self) -> Result<KeyListing, StorageError> {
+        let names = self.key_names()?;
+        match self.read_index() {
+            Some(index) if index.keys.keys().eq(names.iter()) => Ok(KeyListing {
+                keys: index.keys.into_values().collect(),
+                unreadable: Vec::new(),
+            }),
+            _ => self.reindex(),
+        }
+    }
+
+    // Parse every key file and rewrite the index from them. Files that fail to parse are
+    // reported and left out, so the index stays stale and they are reported again.
+    pub fn reindex(&self) -> Result<KeyListing, StorageError> {
+        let rebuild = || {
+            let mut index = KeyIndex {
+                version: INDEX_VERSION,
+                keys: BTreeMap::new(),
+            };
+            let mut unreadable = Vec::new();
+
+            for name in self.key_names()? {
+                let summary = self
+                    .read_entry(&name)
+                    .and_then(|entry| KeySummary::of(&entry));
+                match summary {
+                    Ok(summary) => {
+                        index.keys.insert(name, summary);
                     }
+                    Err(e) => unreadable.push((format!("{}.json", name), e.to_string())),
                 }
             }
+
+            if !self.read_only {
+                self.write_file(INDEX_FILE, &serde_json::to_vec_pretty(&index)?)?;
+            }
+            Ok(KeyListing {
+                keys: index.keys.into_values().collect(),
+                unreadable,
+            })
+        };
+
+        // A read-only keystore is listed the slow way every time, and cannot be locked
+        if self.read_only {
+            rebuild()
+        } else {
+            self.locked(rebuild)
+        }
+    }
+
+    // Rewrite a key in the current format, returning the version it was stored in if it
+    // was older
+    pub fn migrate_key(&self, name: &str) -> Result<Option<u32>, StorageError> {
+        let path = self.key_path(name)?;
+        self.locked(|| {
+            let (entry, stored) = KeyEntry::parse(&fs::read(&path)?)?;
+            if stored == CURRENT_VERSION {
+                return Ok(None);
+            }
+
+            self.write_stored(&entry)?;
+            Ok(Some(stored))
+        })
+    }
+
+    // Names of the key files in the keystore, found without reading them
+    pub fn key_names(&self) -> Result<BTreeSet<String>, StorageError> {
+        let mut names = BTreeSet::new();
+        for entry in fs::read_dir(&self.storage_dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name == INDEX_FILE || file_name.starts_with('.') || !entry.path().is_file() {
+                continue;
+            }
+            if let Some(name) = file_name.strip_suffix(".json") {
+                names.insert(name.to_string());
+            }
         }
-        
-        Ok(results)
+        Ok(names)
+    }
+
+    fn read_index(&self) -> Option<KeyIndex> {
+        let file = File::open(self.storage_dir.join(INDEX_FILE)).ok()?;
+        let index: KeyIndex = serde_json::from_reader(file).ok()?;
+        (index.version == INDEX_VERSION).then_some(index)
+    }
+
+    // Apply a change to the index. A missing or unreadable index is left for list_keys to
+    // rebuild rather than recreated with only this change in it. Callers hold the lock, so
+    // two writers never both read the old index and lose one change.
+    fn update_index(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, KeySummary>) -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let Some(mut index) = self.read_index() else {
+            return Ok(());
+        };
+        change(&mut index.keys)?;
+        self.write_file(INDEX_FILE, &serde_json::to_vec_pretty(&index)?)?;
+        Ok(())
+    }
+
+    fn key_path(&self, name: &str) -> Result<PathBuf, StorageError> {
+        validate_key_name(name)?;
+        Ok(self.storage_dir.join(format!("{}.json", name)))
+    }
+}
+
+// Whether files can be created in a directory, found by creating one: permission bits do
+// not tell whether the filesystem is mounted read-only
+fn is_writable(dir: &Path) -> bool {
+    let mut suffix = [0u8; 8];
+    OsRng.fill_bytes(&mut suffix);
+    let probe = dir.join(format!(".write-test-{}", hex::encode(suffix)));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(e) => !matches!(
+            e.kind(),
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+        ),
+    }
+}
+
+// Keys found in the keystore, along with the files that could not be read as keys
+#[derive(Debug, Default)]
+pub struct KeyListing {
+    pub keys: Vec<KeySummary>,
+    pub unreadable: Vec<(String, String)>, // file name, error
+}
+
+// Write a file by way of a temporary file in the same directory that is synced and
+// renamed into place, so a crash or failed write never leaves a truncated file behind
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> Result<(), std::io::Error> {
+    write_atomic_with_mode(path.as_ref(), contents, 0o666)
+}
+
+// Same as write_atomic, for secrets: on Unix the file is only accessible to its owner
+// from the moment it is created
+pub fn write_private(path: impl AsRef<Path>, contents: &[u8]) -> Result<(), std::io::Error> {
+    write_atomic_with_mode(path.as_ref(), contents, 0o600)
+}
+
+fn write_atomic_with_mode(path: &Path, contents: &[u8], mode: u32) -> Result<(), std::io::Error> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Not a file path: {}", path.display()),
+        )
+    })?;
+    // A fresh name for every write, created with create_new, so concurrent writers never
+    // share a temporary file and a file or link planted under the name is never written to
+    let mut suffix = [0u8; 8];
+    OsRng.fill_bytes(&mut suffix);
+    let tmp_path = dir.join(format!(".{}.{}.tmp", file_name.to_string_lossy(), hex::encode(suffix)));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
     }
-    
-    fn key_path(&self, name: &str) -> PathBuf {
-        self.storage_dir.join(format!("{}.json", name))
+    #[cfg(not(unix))]
+    let _ = mode;
+    let mut file = options.open(&tmp_path)?;
+    let written = file.write_all(contents).and_then(|_| file.sync_all());
+    drop(file);
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
     }
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+// Current version of the export-key file format
+pub const KEY_EXPORT_VERSION: u32 = 1;
+
+// A key as written by export-key: metadata and public key in the clear, the private key
+// encrypted under a passphrase
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeyExport {
+    pub version: u32,
+    pub metadata: KeyMetadata,
+    pub public_key: String, //Hex-Encoded
+    #[serde(flatten)]
+    pub sealed: Sealed,
+}
+
+// Data encrypted with ChaCha20-Poly1305 under a key derived from a passphrase with Argon2id
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Sealed {
+    pub kdf: KdfInfo,
+    pub cipher: String,
+    pub nonce: String,      //Hex-Encoded
+    pub ciphertext: String, //Hex-Encoded, followed by the tag
+}
+
+// Argon2id parameters and salt the data was sealed with
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KdfInfo {
+    pub name: String,
+    pub salt: String, //Hex-Encoded
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Sealed {
+    pub fn seal(passphrase: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Self, StorageError> {
+        let params = Argon2Params::default();
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; encryption::NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut key = [0u8; encryption::KEY_LEN];
+        encryption::argon2id(passphrase, &salt, &params, &mut key)?;
+        let ciphertext = encryption::seal(&key, &nonce, aad, plaintext);
+
+        Ok(Self {
+            kdf: KdfInfo {
+                name: "argon2id".to_string(),
+                salt: hex::encode(salt),
+                memory_kib: params.memory_kib,
+                iterations: params.iterations,
+                parallelism: params.parallelism,
+            },
+            cipher: "chacha20-poly1305".to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    // Decrypt, failing on a wrong passphrase or any change to the ciphertext or `aad`
+    pub fn open(&self, passphrase: &[u8], aad: &[u8]) -> Result<Vec<u8>, StorageError> {
+        if self.kdf.name != "argon2id" || self.cipher != "chacha20-poly1305" {
+            return Err(StorageError::InvalidFormat("kdf"));
+        }
+
+        let salt = hex::decode(&self.kdf.salt).map_err(|_| StorageError::InvalidFormat("salt"))?;
+        let nonce: [u8; encryption::NONCE_LEN] = hex::decode(&self.nonce)
+            .ok()
+            .and_then(|nonce| nonce.try_into().ok())
+            .ok_or(StorageError::InvalidFormat("nonce"))?;
+        let ciphertext =
+            hex::decode(&self.ciphertext).map_err(|_| StorageError::InvalidFormat("ciphertext"))?;
+
+        let params = Argon2Params {
+            memory_kib: self.kdf.memory_kib,
+            iterations: self.kdf.iterations,
+            parallelism: self.kdf.parallelism,
+        };
+        let mut key = [0u8; encryption::KEY_LEN];
+        encryption::argon2id(passphrase, &salt, &params, &mut key)?;
+        Ok(encryption::open(&key, &nonce, aad, &ciphertext)?)
+    }
+}
+
+impl KeyExport {
+    pub fn seal(entry: KeyEntry, passphrase: &[u8]) -> Result<Self, StorageError> {
+        let private_key = hex::decode(&entry.private_key)
+            .map_err(|_| StorageError::InvalidFormat("private_key"))?;
+        let aad = Self::associated_data(
+            KEY_EXPORT_VERSION,
+            &entry.metadata.scheme,
+            &entry.public_key,
+        );
+
+        Ok(Self {
+            version: KEY_EXPORT_VERSION,
+            sealed: Sealed::seal(passphrase, &aad, &private_key)?,
+            metadata: entry.metadata,
+            public_key: entry.public_key,
+        })
+    }
+
+    // Decrypt the private key; a wrong passphrase or any tampering with the scheme,
+    // public key or ciphertext fails authentication
+    pub fn open(self, passphrase: &[u8]) -> Result<KeyEntry, StorageError> {
+        if self.version > KEY_EXPORT_VERSION {
+            return Err(StorageError::UnsupportedVersion(self.version));
+        }
+
+        let aad = Self::associated_data(self.version, &self.metadata.scheme, &self.public_key);
+        let private_key = self.sealed.open(passphrase, &aad)?;
+
+        Ok(KeyEntry {
+            version: CURRENT_VERSION,
+            metadata: self.metadata,
+            private_key: hex::encode(private_key),
+            public_key: self.public_key,
+        })
+    }
+
+    fn associated_data(version: u32, scheme: &str, public_key: &str) -> Vec<u8> {
+        format!("sig-tool-key-export-v{}:{}:{}", version, scheme, public_key).into_bytes()
+    }
+}
+
+// Current version of the backup archive format
+pub const BACKUP_VERSION: u32 = 1;
+
+// An encrypted archive of every file in a keystore, as written by backup
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupArchive {
+    pub version: u32,
+    pub created_at: u64,
+    #[serde(flatten)]
+    pub sealed: Sealed,
+}
+
+// A keystore file inside a backup archive
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupFile {
+    pub name: String,
+    pub contents: String, //Hex-Encoded
+}
+
+impl BackupArchive {
+    pub fn seal(files: &[BackupFile], passphrase: &[u8]) -> Result<Self, StorageError> {
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let plaintext = serde_json::to_vec(files)?;
+        let aad = Self::associated_data(BACKUP_VERSION, created_at);
+
+        Ok(Self {
+            version: BACKUP_VERSION,
+            created_at,
+            sealed: Sealed::seal(passphrase, &aad, &plaintext)?,
+        })
+    }
+
+    // Decrypt and check every file before any of them is restored: names must stay inside
+    // the keystore directory and key files must parse as key entries
+    pub fn open(&self, passphrase: &[u8]) -> Result<Vec<BackupFile>, StorageError> {
+        if self.version > BACKUP_VERSION {
+            return Err(StorageError::UnsupportedVersion(self.version));
+        }
+
+        let plaintext = self.sealed.open(
+            passphrase,
+            &Self::associated_data(self.version, self.created_at),
+        )?;
+        let files: Vec<BackupFile> = serde_json::from_slice(&plaintext)?;
+        for file in &files {
+            if file.name.is_empty() || file.name.starts_with('.') || file.name.contains(['/', '\\'])
+            {
+                return Err(StorageError::InvalidFormat("name"));
+            }
+            let contents =
+                hex::decode(&file.contents).map_err(|_| StorageError::InvalidFormat("contents"))?;
+            if file.name.ends_with(".json") {
+                KeyEntry::parse(&contents)?;
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn associated_data(version: u32, created_at: u64) -> Vec<u8> {
+        format!("sig-tool-backup-v{}:{}", version, created_at).into_bytes()
+    }
+}
+
+// Helper function to save a backup archive to file
+pub fn save_backup(path: impl AsRef<Path>, archive: &BackupArchive) -> Result<(), StorageError> {
+    write_atomic(path, &serde_json::to_vec_pretty(archive)?)?;
+    Ok(())
+}
+
+// Helper function to load a backup archive from file
+pub fn load_backup(path: impl AsRef<Path>) -> Result<BackupArchive, StorageError> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+// Helper function to save an exported key to file
+pub fn save_key_export(path: impl AsRef<Path>, export: &KeyExport) -> Result<(), StorageError> {
+    write_atomic(path, &serde_json::to_vec_pretty(export)?)?;
+    Ok(())
+}
+
+// Helper function to load an exported key from file
+pub fn load_key_export(path: impl AsRef<Path>) -> Result<KeyExport, StorageError> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+// A Shamir share of a BLS secret key, as written by split-key
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeyShare {
+    pub scheme: String,
+    pub key: String,
+    pub index: u32,
+    pub threshold: u32,
+    pub shares: u32,
+    pub public_key: String, //Hex-Encoded group public key
+    pub share: String,      //Hex-Encoded secret share
+}
+
+// Helper function to save a key share to file
+pub fn save_key_share(path: impl AsRef<Path>, share: &KeyShare) -> Result<(), StorageError> {
+    write_private(path, &serde_json::to_vec_pretty(share)?)?;
+    Ok(())
+}
+
+// Helper function to load a key share from file
+pub fn load_key_share(path: impl AsRef<Path>) -> Result<KeyShare, StorageError> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
 }
 
 // Helper function to save a signature to file
@@ -123,43 +1215,58 @@ pub fn save_signature(
     path: impl AsRef<Path>,
     scheme_name: &str,
     signature: &[u8],
+) -> Result<(), StorageError> {
+    save_signature_with_info(path, scheme_name, signature, &SignatureInfo::default())
+}
+
+// Same as save_signature, also recording the optional signature details
+pub fn save_signature_with_info(
+    path: impl AsRef<Path>,
+    scheme_name: &str,
+    signature: &[u8],
+    info: &SignatureInfo,
 ) -> Result<(), StorageError> {
     #[derive(Serialize)]
-    struct SignatureFile {
+    struct SignatureFile<'a> {
+        version: u32,
         scheme: String,
         signature: String,
-        timestamp: u64,
+        #[serde(flatten)]
+        info: &'a SignatureInfo,
     }
-    
+
     let sig_file = SignatureFile {
+        version: SIGNATURE_FILE_VERSION,
         scheme: scheme_name.to_string(),
-        signature: hex::encode(signature),
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
+        signature: match info.signature_encoding.as_deref() {
+            Some(BASE64_ENCODING) => BASE64_STANDARD.encode(signature),
+            _ => hex::encode(signature),
+        },
+        info: &SignatureInfo {
+            timestamp: Some(info.timestamp.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+            })),
+            ..info.clone()
+        },
     };
-    
-    let file = File::create(path)?;
-    serde_json::to_writer_pretty(file, &sig_file)?;
-    
+
+    write_atomic(path, &serde_json::to_vec_pretty(&sig_file)?)?;
+
     Ok(())
 }
 
 // Helper function to load a signature from file
 pub fn load_signature(path: impl AsRef<Path>) -> Result<(String, Vec<u8>), StorageError> {
-    #[derive(Deserialize)]
-    struct SignatureFile {
-        scheme: String,
-        signature: String,
-    }
-    
-    let file = File::open(path)?;
-    let sig_file: SignatureFile = serde_json::from_reader(file)?;
-    
-    let signature_bytes = hex::decode(&sig_file.signature)
-        .map_err(|_| StorageError::InvalidFormat)?;
-    
-    Ok((sig_file.scheme, signature_bytes))
+    let (scheme, signature_bytes, _) = load_signature_with_info(path)?;
+    Ok((scheme, signature_bytes))
 }
 
+// Same as load_signature, also returning the optional signature details
+pub fn load_signature_with_info(
+    path: impl AsRef<Path>,
+) -> Result<(String, Vec<u8>, SignatureInfo), StorageError> {
+    Ok(parse_signature_file(&fs::read(path)?)?)
+}
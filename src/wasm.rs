@@ -0,0 +1,91 @@
+use crate::crypto::registry;
+use crate::signature_file::{self, SignatureInfo};
+use wasm_bindgen::prelude::*;
+
+// JavaScript bindings for checking sig-tool signatures without the binary, e.g. in a
+// browser. Keys, messages and signatures are Uint8Arrays, schemes are named as signature
+// files name them, and errors are thrown as JavaScript Errors.
+
+/// Verify a signature over a message, returning whether it is valid. Throws when the
+/// scheme is not supported or the key or signature does not parse.
+#[wasm_bindgen]
+pub fn verify_signature_bytes(scheme: &str, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool, JsError> {
+    Ok(registry::verify_signature_bytes(scheme, public_key, message, signature)?)
+}
+
+/// Parse the JSON of a signature file, as written by `sig-tool sign --output`
+#[wasm_bindgen]
+pub fn parse_signature_file(json: &str) -> Result<SignatureFile, JsError> {
+    let (scheme, signature, info) = signature_file::parse_signature_file(json.as_bytes())?;
+    Ok(SignatureFile { scheme, signature, info })
+}
+
+#[wasm_bindgen]
+pub struct SignatureFile {
+    scheme: String,
+    signature: Vec<u8>,
+    info: SignatureInfo,
+}
+
+#[wasm_bindgen]
+impl SignatureFile {
+    #[wasm_bindgen(getter)]
+    pub fn scheme(&self) -> String {
+        self.scheme.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signature(&self) -> Vec<u8> {
+        self.signature.clone()
+    }
+
+    /// The signer's public key when the file embeds it; a hint only, as anyone editing
+    /// the file can change it
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Option<Vec<u8>> {
+        self.info.public_key.as_deref().and_then(|public_key| hex::decode(public_key).ok())
+    }
+
+    /// SHA-256 of the signed message, hex-encoded, when the file records it
+    #[wasm_bindgen(getter, js_name = messageSha256)]
+    pub fn message_sha256(&self) -> Option<String> {
+        self.info.message_sha256.clone()
+    }
+
+    /// Every other field of the file, as JSON
+    #[wasm_bindgen(getter)]
+    pub fn info(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.info)?)
+    }
+}
+
+// Run in Node with wasm-pack test --node -- --no-default-features --features wasm
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    // Written by `sig-tool sign --key a --message hello --output` for an ECDSA-secp256k1 key
+    const SIGNATURE_FILE: &str = r#"{
+  "version": 2,
+  "scheme": "ECDSA-secp256k1",
+  "signature": "304402201c990d1c4b8725e798c5dbb0aae20c25e7c8bb993536074d71274c990631334b0220643fe17d627d22b9dd6d596a8d64bee0bb73245dfa12671c11d93e7b7870f990",
+  "timestamp": 1792184857,
+  "encoding": "der",
+  "key_fingerprint": "b4932c6dcc762c0e",
+  "public_key": "030d6d47a055819f30fa47a5e60472861b2f8a7a8436a5203b545f2b4598edf419",
+  "message_sha256": "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+}"#;
+
+    #[wasm_bindgen_test]
+    fn verifies_an_ecdsa_signature_file() {
+        let file = parse_signature_file(SIGNATURE_FILE).unwrap();
+        assert_eq!(file.scheme(), "ECDSA-secp256k1");
+        assert_eq!(file.message_sha256().as_deref(), Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"));
+        let public_key = file.public_key().unwrap();
+
+        assert!(verify_signature_bytes(&file.scheme(), &public_key, b"hello", &file.signature()).unwrap());
+        assert!(!verify_signature_bytes(&file.scheme(), &public_key, b"hello!", &file.signature()).unwrap());
+        assert!(verify_signature_bytes("BLS12-381-min-pk", &public_key, b"hello", &file.signature()).is_err());
+    }
+}
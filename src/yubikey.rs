@@ -0,0 +1,129 @@
+use crate::storage::{KeyStorageBackend, StorageError};
+
+// Keys generated on a YubiKey's PIV applet, spoken to over PC/SC with the yubikey crate. The
+// private key never leaves the device: the keystore records the public key and the PIV
+// slot, and signing asks the device, which needs the PIN and, with the touch policy set at
+// generation, a touch. Messages are hashed here and only the digest goes to the device.
+// Only ECDSA P-256 is supported. Without the yubikey feature, which links against PC/SC
+// (pcsclite on Linux), the backend is still recognised but cannot reach a device.
+
+pub const YUBIKEY_BACKEND: &str = "yubikey";
+pub const PIV_SLOTS: [&str; 4] = ["9a", "9c", "9d", "9e"];
+
+pub struct YubiKeyBackend;
+
+impl KeyStorageBackend for YubiKeyBackend {
+    fn name(&self) -> &'static str {
+        YUBIKEY_BACKEND
+    }
+
+    fn store_private_key(&self, _name: &str, _private_key: &str) -> Result<String, StorageError> {
+        Err(StorageError::YubiKey(
+            "only keys generated on the device can be kept on a YubiKey; use keygen --slot".into(),
+        ))
+    }
+
+    // There is nothing to load; signing goes to the device
+    fn load_private_key(&self, _name: &str, _stored: &str) -> Result<String, StorageError> {
+        Ok(String::new())
+    }
+
+    // The slot keeps its key until something else is generated in it
+    fn delete_private_key(&self, _name: &str) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+/// Generate a P-256 key in a PIV slot and return the compressed public key. A slot that
+/// already holds a key is refused unless `replace` is set.
+#[cfg(feature = "yubikey")]
+pub fn generate(slot: &str, replace: bool) -> Result<Vec<u8>, StorageError> {
+    use ::yubikey::piv::{self, AlgorithmId};
+    use ::yubikey::{MgmKey, PinPolicy, TouchPolicy};
+
+    let slot_id = slot_id(slot)?;
+    let mut yubikey = open()?;
+    if !replace && slot_in_use(&mut yubikey, slot_id) {
+        return Err(StorageError::YubiKey(format!(
+            "PIV slot {} already holds a key; pass --force to replace it", slot
+        )));
+    }
+
+    // Generating needs the management key; like yubico-piv-tool, assume the factory default
+    yubikey
+        .authenticate(MgmKey::default())
+        .map_err(|e| device_error("the management key was refused", e))?;
+    let public_key_info = piv::generate(&mut yubikey, slot_id, AlgorithmId::EccP256, PinPolicy::Default, TouchPolicy::Always)
+        .map_err(|e| device_error("could not generate the key", e))?;
+    let public_key = p256::PublicKey::from_sec1_bytes(public_key_info.subject_public_key.raw_bytes())
+        .map_err(|e| StorageError::YubiKey(format!("the device returned an unreadable public key: {}", e)))?;
+    Ok(p256::elliptic_curve::sec1::ToEncodedPoint::to_encoded_point(&public_key, true).as_bytes().to_vec())
+}
+
+/// Sign a message with SHA-256 and the key in a PIV slot, returning a DER signature
+#[cfg(feature = "yubikey")]
+pub fn sign(slot: &str, message: &[u8]) -> Result<Vec<u8>, StorageError> {
+    use ::yubikey::piv::{self, AlgorithmId};
+    use sha2::{Digest, Sha256};
+
+    let slot_id = slot_id(slot)?;
+    let mut yubikey = open()?;
+
+    // Prompts, so they stay out of output that may be piped elsewhere
+    let pin = rpassword::prompt_password(format!("PIN for YubiKey PIV slot {}: ", slot))
+        .map_err(|e| StorageError::YubiKey(format!("no terminal to prompt for the PIN on ({})", e)))?;
+    yubikey.verify_pin(pin.as_bytes()).map_err(|e| match e {
+        ::yubikey::Error::WrongPin { tries } => StorageError::YubiKey(format!("wrong PIN; {} tries left", tries)),
+        e => device_error("the PIN was refused", e),
+    })?;
+    eprintln!("Touch the YubiKey when it blinks");
+
+    let digest = Sha256::digest(message);
+    let signature = piv::sign_data(&mut yubikey, &digest, AlgorithmId::EccP256, slot_id)
+        .map_err(|e| device_error("could not sign", e))?;
+    Ok(signature.to_vec())
+}
+
+#[cfg(feature = "yubikey")]
+fn open() -> Result<::yubikey::YubiKey, StorageError> {
+    ::yubikey::YubiKey::open().map_err(|e| match e {
+        ::yubikey::Error::NotFound => StorageError::YubiKey("no YubiKey is connected".into()),
+        e => device_error("could not open the YubiKey", e),
+    })
+}
+
+#[cfg(feature = "yubikey")]
+fn slot_id(slot: &str) -> Result<::yubikey::piv::SlotId, StorageError> {
+    slot.parse().map_err(|_| StorageError::YubiKey(format!("{} is not a PIV slot", slot)))
+}
+
+// Slot metadata needs firmware 5.2.3; on older devices a certificate in the slot is the only
+// sign of a key, and a key generated without one goes unnoticed
+#[cfg(feature = "yubikey")]
+fn slot_in_use(yubikey: &mut ::yubikey::YubiKey, slot: ::yubikey::piv::SlotId) -> bool {
+    match ::yubikey::piv::metadata(yubikey, slot) {
+        Ok(_) => true,
+        Err(::yubikey::Error::NotSupported) => ::yubikey::Certificate::read(yubikey, slot).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(feature = "yubikey")]
+fn device_error(what: &str, e: ::yubikey::Error) -> StorageError {
+    StorageError::YubiKey(format!("{}: {}", what, e))
+}
+
+#[cfg(not(feature = "yubikey"))]
+pub fn generate(_slot: &str, _replace: bool) -> Result<Vec<u8>, StorageError> {
+    Err(not_built())
+}
+
+#[cfg(not(feature = "yubikey"))]
+pub fn sign(_slot: &str, _message: &[u8]) -> Result<Vec<u8>, StorageError> {
+    Err(not_built())
+}
+
+#[cfg(not(feature = "yubikey"))]
+fn not_built() -> StorageError {
+    StorageError::YubiKey("this sig-tool was built without the yubikey feature; rebuild it with --features yubikey".into())
+}
@@ -0,0 +1,531 @@
+// End-to-end tests of the sig-tool binary, run against a keystore in a temporary directory
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// A keystore and the files a test writes, in a temporary directory that also stands in
+// for the home directory so no profiles file of the user's is read
+struct Scratch {
+    dir: PathBuf,
+}
+
+impl Scratch {
+    fn new(test: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("sig-tool-cli-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn write(&self, name: &str, contents: &str) -> String {
+        let path = self.path(name);
+        fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_sig-tool"))
+            .arg("--keystore")
+            .arg(self.path("keys"))
+            .args(args)
+            .env("HOME", &self.dir)
+            .env_remove("SIG_TOOL_KEYSTORE")
+            .env_remove("SIG_TOOL_DEFAULT_SCHEME")
+            .env_remove("SIG_TOOL_PASSPHRASE")
+            .output()
+            .unwrap()
+    }
+
+    // Run a command that must succeed, returning its stdout
+    fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "sig-tool {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
+
+// Every file in the keystore but the audit log, which records failed commands too
+fn keystore_files(scratch: &Scratch) -> BTreeMap<String, Vec<u8>> {
+    fs::read_dir(scratch.path("keys"))
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.file_name() != "audit.log")
+        .map(|entry| (entry.file_name().to_string_lossy().into_owned(), fs::read(entry.path()).unwrap()))
+        .collect()
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn import_with_a_wrong_passphrase_leaves_no_key() {
+    let scratch = Scratch::new("import");
+    let right = scratch.write("right.txt", "correct horse\n");
+    let wrong = scratch.write("wrong.txt", "battery staple\n");
+    let export = scratch.path("key.export").display().to_string();
+    scratch.ok(&["keygen", "--name", "offline", "--scheme", "ed25519"]);
+    scratch.ok(&["export-key", "--key", "offline", "--output", &export, "--passphrase-file", &right]);
+
+    let output = scratch.run(&["import-key", "--input", &export, "--name", "online", "--passphrase-file", &wrong]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wrong passphrase"));
+    assert!(!scratch.path("keys/online.json").exists());
+    assert_eq!(scratch.ok(&["list-keys"]).lines().filter(|line| line.contains("online")).count(), 0);
+
+    scratch.ok(&["import-key", "--input", &export, "--name", "online", "--passphrase-file", &right]);
+    assert!(scratch.path("keys/online.json").exists());
+}
+
+#[test]
+fn restore_of_a_tampered_archive_leaves_the_keystore_unchanged() {
+    let scratch = Scratch::new("restore");
+    let passphrase = scratch.write("passphrase.txt", "correct horse\n");
+    let backup = scratch.path("backup.sigstore").display().to_string();
+    scratch.ok(&["keygen", "--name", "kept", "--scheme", "ed25519"]);
+    scratch.ok(&["keygen", "--name", "lost", "--scheme", "ed25519"]);
+    scratch.ok(&["backup", "--output", &backup, "--passphrase-file", &passphrase]);
+    scratch.ok(&["delete-key", "--key", "lost", "--yes"]);
+    let before = keystore_files(&scratch);
+
+    let archive: serde_json::Value = serde_json::from_slice(&fs::read(&backup).unwrap()).unwrap();
+    let mut tampered = archive.clone();
+    let mut ciphertext = hex::decode(archive["ciphertext"].as_str().unwrap()).unwrap();
+    ciphertext[0] ^= 1;
+    tampered["ciphertext"] = hex::encode(ciphertext).into();
+    let mut expensive = archive.clone();
+    expensive["kdf"]["memory_kib"] = u32::MAX.into();
+    expensive["kdf"]["parallelism"] = u32::MAX.into();
+
+    for (name, archive) in [("tampered", tampered), ("expensive", expensive)] {
+        let path = scratch.write(&format!("{}.sigstore", name), &archive.to_string());
+        let output = scratch.run(&["restore", "--input", &path, "--passphrase-file", &passphrase, "--overwrite"]);
+        assert!(!output.status.success(), "{}", name);
+        assert_eq!(keystore_files(&scratch), before, "{}", name);
+    }
+
+    scratch.ok(&["restore", "--input", &backup, "--passphrase-file", &passphrase]);
+    assert!(scratch.path("keys/lost.json").exists());
+}
+
+#[test]
+fn bls_signature_does_not_verify_under_another_dst() {
+    let scratch = Scratch::new("dst");
+    let signature = scratch.path("signature.sig").display().to_string();
+    scratch.ok(&["keygen", "--name", "validator", "--scheme", "bls"]);
+    scratch.ok(&["sign", "--key", "validator", "--message", "attest", "--dst", "APP_A_V1", "--output", &signature]);
+    scratch.ok(&["verify", "--key", "validator", "--message", "attest", "--signature", &signature]);
+    scratch.ok(&["verify", "--key", "validator", "--message", "attest", "--signature", &signature, "--dst", "APP_A_V1"]);
+
+    let output = scratch.run(&["verify", "--key", "validator", "--message", "attest", "--signature", &signature, "--dst", "APP_B_V1"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("DST mismatch"));
+
+    // Without the DST it recorded, the file is checked under the default DST
+    let mut file: serde_json::Value = serde_json::from_slice(&fs::read(&signature).unwrap()).unwrap();
+    file.as_object_mut().unwrap().remove("dst");
+    let stripped = scratch.write("stripped.sig", &file.to_string());
+    assert!(!scratch.run(&["verify", "--key", "validator", "--message", "attest", "--signature", &stripped]).status.success());
+    scratch.ok(&["verify", "--key", "validator", "--message", "attest", "--signature", &stripped, "--dst", "APP_A_V1"]);
+}
+
+// The scheme a signature file records says whether it is checked as a message-augmented
+// (AUG) or basic (NUL) signature; relabelling one as the other does not verify
+#[test]
+fn aug_and_basic_signature_files_do_not_cross_verify() {
+    let scratch = Scratch::new("aug");
+    scratch.ok(&["keygen", "--name", "validator", "--scheme", "bls"]);
+    for (args, relabelled) in [(&["--aug"][..], "BLS12-381-min-pk"), (&[][..], "BLS12-381-min-pk-aug")] {
+        let signature = scratch.path("signature.sig").display().to_string();
+        let mut sign = vec!["sign", "--key", "validator", "--message", "attest", "--output", &signature, "--force"];
+        sign.extend(args);
+        scratch.ok(&sign);
+        scratch.ok(&["verify", "--key", "validator", "--message", "attest", "--signature", &signature]);
+
+        let mut file: serde_json::Value = serde_json::from_slice(&fs::read(&signature).unwrap()).unwrap();
+        assert_ne!(file["scheme"], relabelled);
+        file["scheme"] = relabelled.into();
+        let relabelled = scratch.write("relabelled.sig", &file.to_string());
+        let output = scratch.run(&["verify", "--key", "validator", "--message", "attest", "--signature", &relabelled]);
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("INVALID"), "{:?}", args);
+    }
+}
+
+#[test]
+fn tampered_committee_bitmap_fails_verification() {
+    let scratch = Scratch::new("bitmap");
+    for name in ["a", "b", "c"] {
+        scratch.ok(&["keygen", "--name", name, "--scheme", "bls"]);
+    }
+    let (a, c) = (scratch.path("a.sig").display().to_string(), scratch.path("c.sig").display().to_string());
+    scratch.ok(&["sign", "--key", "a", "--message", "epoch 7", "--output", &a]);
+    scratch.ok(&["sign", "--key", "c", "--message", "epoch 7", "--output", &c]);
+    let aggregate = scratch.path("aggregate.sig").display().to_string();
+    scratch.ok(&["aggregate", "--signatures", &a, "--signatures", &c, "--committee", "a,b,c", "--message", "epoch 7", "--output", &aggregate]);
+    scratch.ok(&["verify-aggregate", "--signature", &aggregate, "--message", "epoch 7"]);
+
+    let file: serde_json::Value = serde_json::from_slice(&fs::read(&aggregate).unwrap()).unwrap();
+    assert_eq!(file["committee"]["bitmap"], "05");
+    // Adding b, who did not sign, dropping c, who did, or both
+    for bit in [0x02u8, 0x04, 0x06] {
+        let mut tampered = file.clone();
+        tampered["committee"]["bitmap"] = format!("{:02x}", 0x05 ^ bit).into();
+        let path = scratch.write("tampered.sig", &tampered.to_string());
+        let output = scratch.run(&["verify-aggregate", "--signature", &path, "--message", "epoch 7"]);
+        assert!(!output.status.success(), "bitmap {:02x}", 0x05 ^ bit);
+    }
+}
+
+#[test]
+fn combine_needs_threshold_distinct_partials() {
+    let scratch = Scratch::new("combine");
+    let shares = scratch.path("shares").display().to_string();
+    scratch.ok(&["keygen", "--name", "treasury", "--scheme", "bls"]);
+    scratch.ok(&["split-key", "--key", "treasury", "--threshold", "3", "--shares", "5", "--output-dir", &shares]);
+    let partial = |index: u32| {
+        let share = scratch.path(&format!("shares/treasury-share-{}.json", index)).display().to_string();
+        let output = scratch.path(&format!("partial-{}.sig", index)).display().to_string();
+        scratch.ok(&["sign-share", "--share", &share, "--message", "release", "--output", &output]);
+        output
+    };
+    let partials: Vec<String> = (1..=4).map(partial).collect();
+    let combined = scratch.path("combined.sig").display().to_string();
+
+    let combine = |partials: &[&String]| {
+        let mut args = vec!["combine", "--output", &combined, "--force"];
+        for partial in partials {
+            args.extend(["--partials", partial.as_str()]);
+        }
+        scratch.run(&args)
+    };
+    let output = combine(&[&partials[0], &partials[2]]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Need at least 3 partial signatures"));
+    // Three files, but only two shares
+    assert!(!combine(&[&partials[0], &partials[2], &partials[0]]).status.success());
+    assert!(!scratch.path("combined.sig").exists());
+
+    assert!(combine(&[&partials[3], &partials[0], &partials[2]]).status.success());
+    scratch.ok(&["verify", "--key", "treasury", "--message", "release", "--signature", &combined]);
+}
+
+fn key_file(scratch: &Scratch, name: &str) -> serde_json::Value {
+    serde_json::from_slice(&fs::read(scratch.path(&format!("keys/{}.json", name))).unwrap()).unwrap()
+}
+
+#[test]
+fn seed_file_gives_the_same_key_every_time() {
+    let scratch = Scratch::new("seed");
+    let seed = scratch.write("seed.hex", &format!("{}\n", hex::encode((0..32).collect::<Vec<u8>>())));
+    let short = scratch.write("short.hex", &hex::encode((0..31).collect::<Vec<u8>>()));
+
+    for scheme in ["ecdsa", "bls", "ed25519", "ml-dsa", "sr25519", "hybrid-ed25519-ml-dsa"] {
+        let (first, second) = (format!("{}-1", scheme), format!("{}-2", scheme));
+        scratch.ok(&["keygen", "--name", &first, "--scheme", scheme, "--seed-file", &seed]);
+        scratch.ok(&["keygen", "--name", &second, "--scheme", scheme, "--seed-file", &seed]);
+        let (first, second) = (key_file(&scratch, &first), key_file(&scratch, &second));
+        assert_eq!(first["public_key"], second["public_key"], "{}", scheme);
+        assert_eq!(first["metadata"]["seed_derived"], true, "{}", scheme);
+
+        let output = scratch.run(&["keygen", "--name", "short", "--scheme", scheme, "--seed-file", &short]);
+        assert!(!output.status.success(), "{}", scheme);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("at least 32 bytes"), "{}", scheme);
+        assert!(!scratch.path("keys/short.json").exists(), "{}", scheme);
+    }
+
+    // One seed gives unrelated keys for different schemes
+    assert_ne!(key_file(&scratch, "ed25519-1")["public_key"], key_file(&scratch, "sr25519-1")["public_key"]);
+}
+
+#[test]
+fn hedged_ecdsa_signatures_differ_and_verify() {
+    let scratch = Scratch::new("hedged");
+    scratch.ok(&["keygen", "--name", "signer", "--scheme", "ecdsa"]);
+    let entropy = "01".repeat(32);
+    let sign = |name: &str, extra: &[&str]| {
+        let output = scratch.path(name).display().to_string();
+        let mut args = vec!["sign", "--key", "signer", "--message", "transfer 10", "--output", &output];
+        args.extend(extra);
+        scratch.ok(&args);
+        scratch.ok(&["verify", "--key", "signer", "--message", "transfer 10", "--signature", &output]);
+        let file: serde_json::Value = serde_json::from_slice(&fs::read(&output).unwrap()).unwrap();
+        (file["signature"].as_str().unwrap().to_string(), file["hedged"].as_bool().unwrap_or(false))
+    };
+
+    let (deterministic, hedged) = sign("deterministic.sig", &[]);
+    assert!(!hedged);
+    assert_eq!(sign("again.sig", &[]), (deterministic.clone(), false));
+
+    let first = sign("first.sig", &["--extra-entropy", "random"]);
+    let second = sign("second.sig", &["--extra-entropy", "random"]);
+    let fixed = sign("fixed.sig", &["--extra-entropy", &entropy]);
+    assert!(first.1 && second.1 && fixed.1);
+    assert_ne!(first.0, second.0);
+    for hedged in [&first.0, &second.0, &fixed.0] {
+        assert_ne!(hedged, &deterministic);
+    }
+}
+
+// What a script piping --json output into jq relies on: one JSON document on stdout with
+// these fields, and errors as one JSON document on stderr
+#[test]
+fn json_output_has_the_documented_fields() {
+    let scratch = Scratch::new("json");
+    let json = |args: &[&str]| -> serde_json::Value {
+        let stdout = scratch.ok(&[args, &["--json"]].concat());
+        assert_eq!(stdout.trim_end().lines().count(), 1, "{:?}: {}", args, stdout);
+        serde_json::from_str(&stdout).unwrap()
+    };
+    let fields = |value: &serde_json::Value| {
+        let mut fields: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        fields
+    };
+
+    let keygen = json(&["keygen", "--name", "ci", "--scheme", "ed25519"]);
+    assert_eq!(fields(&keygen), ["fingerprint", "name", "public_key", "scheme"]);
+    assert_eq!((&keygen["name"], &keygen["scheme"]), (&"ci".into(), &"Ed25519".into()));
+
+    let signature = scratch.path("release.sig").display().to_string();
+    let sign = json(&["sign", "--key", "ci", "--message", "release", "--output", &signature]);
+    assert_eq!(fields(&sign), ["output", "scheme", "signature"]);
+    assert_eq!(sign["output"], signature.as_str());
+    assert!(hex::decode(sign["signature"].as_str().unwrap()).is_ok());
+
+    let verify = json(&["verify", "--key", "ci", "--message", "release", "--signature", &signature]);
+    assert_eq!(fields(&verify), ["key", "scheme", "valid"]);
+    assert_eq!((&verify["valid"], &verify["key"]), (&true.into(), &"ci".into()));
+
+    let keys = json(&["list-keys"]);
+    let keys = keys["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!((&keys[0]["name"], &keys[0]["fingerprint"]), (&"ci".into(), &keygen["fingerprint"]));
+
+    let output = scratch.run(&["verify", "--key", "missing", "--message", "release", "--signature", &signature, "--json"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["kind"], "key_not_found");
+    assert!(error["error"]["message"].as_str().unwrap().contains("missing"));
+}
+
+// Every keygen path, seeded, derived or random, reports the key it saved, and the other
+// commands print one JSON object rather than refusing --json
+#[test]
+fn every_command_reports_one_json_object() {
+    let scratch = Scratch::new("json-commands");
+    let json = |args: &[&str]| -> serde_json::Value {
+        let stdout = scratch.ok(&[&["--json"], args].concat());
+        assert_eq!(stdout.trim_end().lines().count(), 1, "{:?}: {}", args, stdout);
+        let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert!(value.is_object(), "{:?}: {}", args, stdout);
+        value
+    };
+    let seed = scratch.write("seed.hex", &"42".repeat(32));
+
+    let seeded = json(&["keygen", "--name", "seeded", "--scheme", "ed25519", "--seed-file", &seed]);
+    let derived = json(&["keygen", "--name", "derived", "--scheme", "bls", "--derive", "--seed-file", &seed, "--path", "m/12381/3600/0/0/0"]);
+    for (keygen, name, scheme) in [(&seeded, "seeded", "Ed25519"), (&derived, "derived", "BLS12-381-min-pk")] {
+        let fields: Vec<&String> = keygen.as_object().unwrap().keys().collect();
+        assert_eq!(fields, ["name", "scheme", "public_key", "fingerprint"]);
+        assert_eq!((&keygen["name"], &keygen["scheme"]), (&name.into(), &scheme.into()));
+    }
+    // The same seed gives the same key, so the report is of the key that was saved
+    let again = json(&["keygen", "--no-save", "--scheme", "ed25519", "--seed-file", &seed]);
+    assert_eq!(again["public_key"], seeded["public_key"]);
+
+    assert_eq!(json(&["set-expiry", "--key", "seeded", "--never"])["expires_at"], serde_json::Value::Null);
+    assert_eq!(json(&["rename-key", "--from", "seeded", "--to", "renamed"])["to"], "renamed");
+    assert_eq!(json(&["reindex"])["indexed"], 2);
+    assert_eq!(json(&["completions", "bash"])["shell"], "bash");
+    assert_eq!(json(&["profile", "add", "ci", "--path", "keys"])["profile"], "ci");
+    assert_eq!(json(&["profile", "remove", "ci"])["removed"], "ci");
+
+    let pop = scratch.path("derived.pop").display().to_string();
+    assert_eq!(json(&["pop", "--key", "derived", "--output", &pop])["output"], pop.as_str());
+    assert_eq!(json(&["verify-pop", "--key", "derived", "--pop", &pop])["valid"], true);
+    let records = json(&["audit", "show"]);
+    assert!(!records["records"].as_array().unwrap().is_empty());
+}
+
+// Messages paired with the wrong signatures are reported by position, whether in an
+// aggregate --mapping or a verify-batch input
+#[test]
+fn message_and_signature_order_mismatches_report_their_indices() {
+    let scratch = Scratch::new("ordering");
+    let mut signatures = Vec::new();
+    for name in ["a", "b", "c"] {
+        scratch.ok(&["keygen", "--name", name, "--scheme", "bls"]);
+        let signature = scratch.path(&format!("{}.sig", name)).display().to_string();
+        scratch.ok(&["sign", "--key", name, "--message", &format!("message {}", name), "--output", &signature]);
+        signatures.push(signature);
+    }
+    let aggregate = scratch.path("aggregate.sig").display().to_string();
+
+    // The first two messages swapped
+    let mapping: Vec<serde_json::Value> = [(0, "b"), (1, "a"), (2, "c")]
+        .iter()
+        .map(|(i, message)| serde_json::json!({ "signature": signatures[*i], "message": format!("message {}", message) }))
+        .collect();
+    let mapping = scratch.write("mapping.json", &serde_json::Value::from(mapping).to_string());
+    let output = scratch.run(&["aggregate", "--mapping", &mapping, "--output", &aggregate]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("Signatures 0 ({:?}), 1 ({:?}) do not verify", signatures[0], signatures[1])), "{}", stderr);
+    assert!(!scratch.path("aggregate.sig").exists());
+
+    // --signatures in another order than the mapping
+    let output = scratch.run(&["aggregate", "--mapping", &mapping, "--signatures", &signatures[1], "--signatures", &signatures[0],
+                               "--signatures", &signatures[2], "--output", &aggregate]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Entry 0 of"));
+
+    // The last two signatures swapped
+    let batch: String = [("a", 0), ("b", 2), ("c", 1)]
+        .iter()
+        .map(|(name, i)| {
+            let message = hex::encode(format!("message {}", name));
+            format!("{}\n", serde_json::json!({ "key": name, "message_hex": message, "signature": signatures[*i] }))
+        })
+        .collect();
+    let batch = scratch.write("batch.jsonl", &batch);
+    let output = scratch.run(&["verify-batch", "--input", &batch]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Invalid entries: 1, 2"));
+}
+
+// Min-pk and min-sig signatures live in different groups and cannot be summed
+#[test]
+fn min_sig_and_min_pk_signatures_do_not_aggregate() {
+    let scratch = Scratch::new("mixed-bls");
+    scratch.ok(&["keygen", "--name", "pk", "--scheme", "bls"]);
+    scratch.ok(&["keygen", "--name", "sig", "--scheme", "bls-min-sig"]);
+    let pk = scratch.path("pk.sig").display().to_string();
+    let sig = scratch.path("sig.sig").display().to_string();
+    scratch.ok(&["sign", "--key", "pk", "--message", "epoch 7", "--output", &pk]);
+    scratch.ok(&["sign", "--key", "sig", "--message", "epoch 7", "--output", &sig]);
+    let aggregate = scratch.path("aggregate.sig").display().to_string();
+
+    for signatures in [[&pk, &sig], [&sig, &pk]] {
+        let output = scratch.run(&["aggregate", "--signatures", signatures[0], "--signatures", signatures[1], "--output", &aggregate]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot aggregate"), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(!scratch.path("aggregate.sig").exists());
+    }
+}
+
+// An OpenSSL signature with a high S is INVALID unless --allow-high-s asks for its low-S form
+#[test]
+fn high_s_signatures_need_allow_high_s() {
+    let scratch = Scratch::new("high-s");
+    let public_key = "04a8bd15fc3826dabb3736ed361144774c5c3072d060ba7434dc3aa6e469b691333613841626f4a29714ba8146cb23f3b92dd2e7578dad9d713793251f8956f922";
+    let der = hex::decode("30450220201e73d92897da55973f3a72253a3ca7211ee6bc0fa12d868f281c28834a5e2b0221008eb376aea7ced2aad4ebef0fd24c4b047043eca7786619c083a8576d3cb179ad").unwrap();
+    let signature = scratch.path("openssl.sig");
+    fs::write(&signature, der).unwrap();
+    let signature = signature.display().to_string();
+    let verify = |message: &str, extra: &[&str]| {
+        let args = ["verify", "--public-key", public_key, "--scheme", "ecdsa", "--raw", "--signature", &signature, "--message", message];
+        scratch.run(&[&args[..], extra].concat())
+    };
+
+    assert_eq!(verify("sig-tool test message", &[]).status.code(), Some(1));
+    assert_eq!(verify("sig-tool test message", &["--allow-high-s"]).status.code(), Some(0));
+    assert_eq!(verify("another message", &["--allow-high-s"]).status.code(), Some(1));
+}
+
+// The confirmation prompt stays off stdout, and --json never prompts
+#[test]
+fn delete_key_prompts_on_stderr_and_not_with_json() {
+    let scratch = Scratch::new("delete-key");
+    scratch.ok(&["keygen", "--name", "deploy", "--scheme", "ed25519"]);
+    let fingerprint: serde_json::Value = serde_json::from_str(&scratch.ok(&["--json", "show-key", "--key", "deploy"])).unwrap();
+
+    // No answer on stdin declines
+    let output = scratch.run(&["delete-key", "--key", "deploy"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Delete key"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Delete key deploy?"));
+    assert!(scratch.path("keys/deploy.json").exists());
+
+    let output = scratch.run(&["--json", "delete-key", "--key", "deploy"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(scratch.path("keys/deploy.json").exists());
+
+    let deleted: serde_json::Value = serde_json::from_str(&scratch.ok(&["--json", "delete-key", "--key", "deploy", "--yes"])).unwrap();
+    assert_eq!(deleted["deleted"], "deploy");
+    assert_eq!(deleted["fingerprint"], fingerprint["fingerprint"]);
+    assert!(!scratch.path("keys/deploy.json").exists());
+}
+
+// A --scheme that disagrees with the scheme a signature file records is an error, not ignored
+#[test]
+fn verify_refuses_a_scheme_the_signature_file_contradicts() {
+    let scratch = Scratch::new("scheme-mismatch");
+    scratch.ok(&["keygen", "--name", "release", "--scheme", "ed25519"]);
+    let signature = scratch.path("release.sig").display().to_string();
+    scratch.ok(&["sign", "--key", "release", "--message", "v1.0.0", "--output", &signature]);
+    let shown: serde_json::Value = serde_json::from_str(&scratch.ok(&["--json", "show-key", "--key", "release"])).unwrap();
+    let public_key = shown["public_key"].as_str().unwrap();
+
+    let verify = |scheme: &str| {
+        scratch.run(&["verify", "--public-key", public_key, "--scheme", scheme, "--signature", &signature, "--message", "v1.0.0"])
+    };
+    assert_eq!(verify("ed25519").status.code(), Some(0));
+    let output = verify("ecdsa");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--scheme ECDSA-secp256k1 does not match the Ed25519 signature"));
+}
+
+// Errors carry their kind, exit code and the argument, path or key they are about
+#[test]
+fn errors_report_structured_fields() {
+    let scratch = Scratch::new("typed-errors");
+    let error = |args: &[&str]| {
+        let output = scratch.run(&[&["--json"], args].concat());
+        let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+        assert_eq!(output.status.code(), error["error"]["exit_code"].as_i64().map(|code| code as i32));
+        error["error"].clone()
+    };
+
+    let expiry = error(&["keygen", "--name", "ci", "--scheme", "ed25519", "--expires-in", "90 days"]);
+    assert_eq!((&expiry["kind"], &expiry["exit_code"]), (&"invalid_value".into(), &2.into()));
+    assert_eq!((&expiry["argument"], &expiry["value"]), (&"--expires-in".into(), &"90 days".into()));
+
+    scratch.ok(&["keygen", "--name", "ci", "--scheme", "ed25519"]);
+    let existing = scratch.write("existing.sig", "");
+    let exists = error(&["sign", "--key", "ci", "--message", "release", "--output", &existing]);
+    assert_eq!((&exists["kind"], &exists["path"]), (&"output_exists".into(), &existing.as_str().into()));
+
+    let private_key = error(&["sign", "--private-key", "not hex", "--scheme", "ecdsa", "--message", "release"]);
+    assert_eq!((&private_key["kind"], &private_key["exit_code"]), (&"invalid_private_key".into(), &6.into()));
+    assert!(!private_key["message"].as_str().unwrap().contains("not hex"));
+
+    let missing = error(&["show-key", "--key", "missing"]);
+    assert_eq!((&missing["kind"], &missing["key"]), (&"key_not_found".into(), &"missing".into()));
+}
+
+// Without the yubikey feature the backend is recognised but says how to get it, and no key is
+// recorded for a slot that was never generated
+#[cfg(not(feature = "yubikey"))]
+#[test]
+fn yubikey_backend_needs_the_yubikey_feature() {
+    let scratch = Scratch::new("yubikey-feature");
+    let output = scratch.run(&["--backend", "yubikey", "keygen", "--name", "hardware", "--slot", "9c"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rebuild it with --features yubikey"));
+    assert!(!scratch.path("keys/hardware.json").exists());
+}